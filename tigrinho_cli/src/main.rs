@@ -1,107 +1,458 @@
-use chrono::Utc;
+use anyhow::Context;
+use chrono::{Duration, Utc};
 use clap::{Parser, Subcommand};
-use sha2::{Digest, Sha256};
-use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tigrinho_core::{Receipt, ReceiptSigner, RoundAction};
+use tigrinho_shared::{
+    AdminRole, ChainHead, RotationTrigger, SeedRotation, SpinLogEntry, SpinQuery, SpinResponse,
+};
+use tigrinho_store::{AdminChange, GameStore, SeedVault};
+
+mod remote;
+
+use remote::Remote;
 
 #[derive(Parser)]
 #[command(name = "tigrinho-cli", about = "Admin CLI for tigrinho server")]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
-    /// Database URL, default sqlite://tigrinho.db
+    /// Database URL (sqlite: or postgres:), default sqlite://tigrinho.db
     #[arg(long, value_parser, env = "DATABASE_URL")]
     database_url: Option<String>,
+    /// File holding the server's seed keyring, else read from SEED_KEK; only
+    /// rotate-seed, rotate-kek and create-admin-key use it
+    #[arg(long, env = "SEED_KEK_FILE")]
+    seed_kek_file: Option<PathBuf>,
+    /// Run rotate-seed and view-logs through this server's admin API instead
+    /// of the database, signing each request with --admin-key
+    #[arg(long, env = "TIGRINHO_SERVER")]
+    server: Option<String>,
+    /// Admin key (`<key id>.<secret>`) signing requests to --server
+    #[arg(long, env = "TIGRINHO_ADMIN_KEY", hide_env_values = true)]
+    admin_key: Option<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Rotate server seed to a new secret
-    RotateSeed { new_seed: String },
+    /// Rotate a game's server seed to a freshly generated secret and reveal the old one
+    RotateSeed {
+        #[arg(long, default_value = "tigrinho")]
+        game: String,
+    },
+    /// Re-wrap every server seed and admin signing key under the first key of
+    /// the seed keyring, and seal any seed still stored in plaintext
+    RotateKek,
+    /// Generate a key for the server's seed keyring (`seed_kek`)
+    NewSeedKek {
+        /// Id to store the key under, e.g. the date it was made
+        #[arg(long)]
+        id: String,
+    },
+    /// Create an admin API key; the key is printed once and only its hash is stored
+    CreateAdminKey {
+        /// Unique name, recorded as the actor in the admin audit trail
+        #[arg(long)]
+        name: String,
+        /// viewer, operator or owner
+        #[arg(long, value_parser = parse_role)]
+        role: AdminRole,
+        /// Let the key lapse after this many days
+        #[arg(long)]
+        expires_in_days: Option<i64>,
+    },
+    /// Revoke an admin API key by name
+    RevokeAdminKey {
+        #[arg(long)]
+        name: String,
+    },
+    /// List admin API keys with their roles and state
+    ListAdminKeys,
     /// View last N log entries
     ViewLogs {
         #[arg(default_value_t = 20)]
-        n: i64,
+        n: u32,
     },
     /// Export spins to CSV path
     ExportCsv { path: String },
+    /// Check the spin log hash chain against the stored chain head and,
+    /// optionally, a head published earlier
+    VerifyChain {
+        /// Length of a published chain head
+        #[arg(long, requires = "head")]
+        length: Option<i64>,
+        /// Hash of a published chain head
+        #[arg(long, requires = "length")]
+        head: Option<String>,
+    },
+    /// Commit a Merkle root over the spins logged since the last committed batch
+    CommitRoot,
+    /// Replay every step of a round from its seeds and the player's actions and
+    /// check it against the spin log
+    VerifyRound {
+        round_id: i64,
+        /// The round's server seed, when it has not been revealed by a rotation yet
+        #[arg(long)]
+        server_seed: Option<String>,
+    },
+    /// Generate a receipt signing key for the server's `receipt_signing_key`
+    NewReceiptKey,
+    /// Check the signature of a saved spin response against a key from `GET /keys`
+    VerifyReceipt {
+        /// JSON file holding the spin response
+        path: String,
+        /// Hex public key of the signing key
+        #[arg(long)]
+        public_key: String,
+    },
 }
 
-#[derive(sqlx::FromRow)]
-struct ParamsRow {
-    server_seed: String,
-    server_seed_hash: String,
-    rtp_target: f64,
-    paytable_json: String,
-    nonce: i64,
+fn new_receipt_key() -> anyhow::Result<()> {
+    let secret = tigrinho_store::generate_seed();
+    let signer = ReceiptSigner::from_hex(&secret)?;
+    println!("Secret key (receipt_signing_key, keep private): {secret}");
+    println!(
+        "Public key: {} (key id {})",
+        signer.public_key(),
+        signer.key_id()
+    );
+    Ok(())
 }
 
-async fn get_pool(url: Option<String>) -> anyhow::Result<SqlitePool> {
-    let url = url.unwrap_or_else(|| "sqlite://tigrinho.db".into());
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(&url)
+fn verify_receipt(path: &str, public_key: &str) -> anyhow::Result<()> {
+    let resp: SpinResponse = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let key_id = tigrinho_core::key_id(public_key);
+    if resp.key_id != key_id {
+        anyhow::bail!(
+            "the receipt names key {:?}, but that public key has id {key_id}",
+            resp.key_id
+        );
+    }
+    let receipt = Receipt {
+        config_hash: &resp.config_hash,
+        server_seed_hash: &resp.server_seed_hash,
+        nonce: resp.nonce,
+        reels: &resp.reels,
+        payout: resp.payout,
+        cursor: resp.cursor,
+    };
+    tigrinho_core::verify_receipt(&receipt, public_key, &resp.signature)?;
+    println!(
+        "Receipt valid: nonce {} under seed hash {} paid {}",
+        resp.nonce, resp.server_seed_hash, resp.payout
+    );
+    Ok(())
+}
+
+fn new_seed_kek(id: &str) -> anyhow::Result<()> {
+    let entry = format!("{id}:{}", tigrinho_store::generate_seed());
+    SeedVault::parse(&entry)?;
+    println!("Seed keyring entry (keep private): {entry}");
+    println!(
+        "Put it first in seed_kek, keep the old keys after it, then run `tigrinho_cli rotate-kek`"
+    );
+    Ok(())
+}
+
+/// The seed keyring from `--seed-kek-file` or `SEED_KEK`.
+fn seed_vault(file: Option<&Path>) -> anyhow::Result<SeedVault> {
+    let keyring = match file {
+        Some(path) => {
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?
+        }
+        None => std::env::var("SEED_KEK")
+            .map_err(|_| anyhow::anyhow!("set SEED_KEK or --seed-kek-file to the seed keyring"))?,
+    };
+    SeedVault::parse(&keyring)
+}
+
+fn parse_role(s: &str) -> Result<AdminRole, String> {
+    AdminRole::parse(s).ok_or_else(|| "expected viewer, operator or owner".into())
+}
+
+/// Actor of the admin audit entries the CLI records.
+const CLI_ACTOR: &str = "cli";
+
+fn print_rotation(rotation: &SeedRotation) {
+    println!(
+        "Rotated server seed. Revealed old seed {} (hash {}). New hash: {}",
+        rotation.revealed_seed, rotation.previous_seed_hash, rotation.new_seed_hash
+    );
+}
+
+fn print_spin(s: &SpinLogEntry) {
+    println!(
+        "#{:>6} {} game={} seed={} nonce={} hash={} payout={}",
+        s.id,
+        s.ts.to_rfc3339(),
+        s.game,
+        s.client_seed,
+        s.nonce,
+        s.server_seed_hash,
+        s.payout
+    );
+}
+
+/// Replay a round's steps and compare them with its logged spins.
+async fn verify_round(
+    store: &dyn GameStore,
+    round_id: i64,
+    server_seed: Option<String>,
+) -> anyhow::Result<()> {
+    let round = store
+        .round(round_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("no such round: {round_id}"))?;
+    let q = SpinQuery {
+        round_id: Some(round_id),
+        ..Default::default()
+    };
+    // newest first from the store; replay wants the opening spin first
+    let mut spins = store
+        .spins(&q, None, tigrinho_core::round::MAX_FREE_SPINS + 1)
         .await?;
-    Ok(pool)
+    spins.sort_by_key(|s| s.cursor.unwrap_or(0));
+    let opening = spins
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("round {round_id} has no spins"))?;
+    let config_hash = opening
+        .config_hash
+        .as_deref()
+        .context("the round's opening spin has no config hash")?;
+    let def = store
+        .config(config_hash)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("unknown config {config_hash}"))?;
+    let server_seed = match server_seed {
+        Some(seed) => seed,
+        None => store
+            .seed_rotation_of(&round.game, &opening.server_seed_hash)
+            .await?
+            .map(|r| r.revealed_seed)
+            .context("the round's server seed is not revealed yet; pass --server-seed")?,
+    };
+    if tigrinho_core::derive_hash_hex(server_seed.as_bytes()) != opening.server_seed_hash {
+        anyhow::bail!(
+            "the server seed does not hash to {}",
+            opening.server_seed_hash
+        );
+    }
+    let actions = spins[1..]
+        .iter()
+        .map(|s| {
+            s.action
+                .as_deref()
+                .and_then(RoundAction::parse)
+                .with_context(|| format!("spin {} has no known action", s.id))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let steps = tigrinho_core::replay_round(
+        &def,
+        &server_seed,
+        &opening.client_seed,
+        opening.nonce as u64,
+        round.bet,
+        &actions,
+    )?;
+    for (step, spin) in steps.iter().zip(&spins) {
+        let reels: Vec<Vec<u8>> = step
+            .reel_window
+            .iter()
+            .map(|row| row.iter().map(|s| s.to_index()).collect())
+            .collect();
+        if reels != spin.result_reels || step.payout != spin.payout {
+            anyhow::bail!(
+                "step {} (spin {}) does not replay: logged {:?} paying {}, replayed {:?} paying {}",
+                step.cursor,
+                spin.id,
+                spin.result_reels,
+                spin.payout,
+                reels,
+                step.payout
+            );
+        }
+    }
+    let state = match steps.last().and_then(|s| s.state) {
+        Some(_) => "feature still in progress",
+        None => "complete",
+    };
+    println!(
+        "Round {round_id} replays: {} steps paying {}, {state}",
+        steps.len(),
+        steps.iter().map(|s| s.payout).sum::<f64>()
+    );
+    Ok(())
+}
+
+/// The commands that also run through a server's admin API.
+async fn run_remote(remote: &Remote, command: Commands) -> anyhow::Result<()> {
+    match command {
+        Commands::RotateSeed { game } => print_rotation(&remote.rotate_seed(&game).await?),
+        Commands::ViewLogs { n } => remote.spins(n).await?.iter().for_each(print_spin),
+        _ => anyhow::bail!("only rotate-seed and view-logs run against --server"),
+    }
+    Ok(())
+}
+
+/// Spins exported per store round trip.
+const EXPORT_BATCH: u32 = 1_000;
+
+async fn get_store(url: Option<String>) -> anyhow::Result<Arc<dyn GameStore>> {
+    let url = url.unwrap_or_else(|| "sqlite://tigrinho.db".into());
+    tigrinho_store::connect(&url, 5).await
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
-    let pool = get_pool(cli.database_url).await?;
+    // commands that never touch the database
+    match &cli.command {
+        Commands::NewReceiptKey => return new_receipt_key(),
+        Commands::VerifyReceipt { path, public_key } => return verify_receipt(path, public_key),
+        Commands::NewSeedKek { id } => return new_seed_kek(id),
+        _ => {}
+    }
+    if let Some(server) = &cli.server {
+        let admin_key = cli
+            .admin_key
+            .as_deref()
+            .context("--server needs --admin-key (or TIGRINHO_ADMIN_KEY)")?;
+        return run_remote(&Remote::new(server, admin_key)?, cli.command).await;
+    }
+    let store = get_store(cli.database_url).await?;
 
     match cli.command {
-        Commands::RotateSeed { new_seed } => {
-            let hash = {
-                let mut h = Sha256::new();
-                h.update(new_seed.as_bytes());
-                hex::encode(h.finalize())
-            };
-            sqlx::query(
-                "UPDATE params SET server_seed = ?, server_seed_hash = ?, nonce = 0 WHERE id = 1",
-            )
-            .bind(new_seed)
-            .bind(hash.clone())
-            .execute(&pool)
-            .await?;
-            println!("Rotated server seed. New hash: {}", hash);
+        Commands::RotateSeed { game } => {
+            let vault = seed_vault(cli.seed_kek_file.as_deref())?;
+            // seeds come from the OS CSPRNG, never from the operator
+            let change = AdminChange::new(CLI_ACTOR, "seeds.rotate");
+            let rotation = store
+                .rotate_seed(&game, RotationTrigger::Cli, None, &vault, Some(&change))
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no such game: {game}"))?;
+            print_rotation(&rotation);
         }
-        Commands::ViewLogs { n } => {
-            let rows = sqlx::query("SELECT id, ts, client_seed, nonce, server_seed_hash, payout FROM spins ORDER BY id DESC LIMIT ?")
-                .bind(n)
-                .fetch_all(&pool).await?;
-            for r in rows {
-                let id: i64 = r.get("id");
-                let ts: String = r.get("ts");
-                let client_seed: String = r.get("client_seed");
-                let nonce: i64 = r.get("nonce");
-                let server_seed_hash: String = r.get("server_seed_hash");
-                let payout: f64 = r.get("payout");
+        Commands::RotateKek => {
+            let vault = seed_vault(cli.seed_kek_file.as_deref())?;
+            let seeds = AdminChange::new(CLI_ACTOR, "seeds.rekey");
+            let resealed =
+                tigrinho_store::reseal_seeds(store.as_ref(), &vault, Some(&seeds)).await?;
+            let keys = AdminChange::new(CLI_ACTOR, "admin_keys.rekey");
+            let rewrapped =
+                tigrinho_store::reseal_admin_keys(store.as_ref(), &vault, Some(&keys)).await?;
+            println!(
+                "Resealed {resealed} server seeds and {rewrapped} admin signing keys; all are now under key {}",
+                vault.active_key_id()
+            );
+        }
+        Commands::CreateAdminKey {
+            name,
+            role,
+            expires_in_days,
+        } => {
+            let expires_at = expires_in_days.map(|days| Utc::now() + Duration::days(days));
+            let vault = seed_vault(cli.seed_kek_file.as_deref())?;
+            let (new, key) = tigrinho_store::generate_admin_key(&name, role, expires_at, &vault);
+            let change = AdminChange::new(CLI_ACTOR, "admin_keys.create");
+            store
+                .create_admin_key(&new, &change)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("an admin key named {name} already exists"))?;
+            println!(
+                "Admin key {name} ({}), shown only once: {key}",
+                role.as_str()
+            );
+        }
+        Commands::RevokeAdminKey { name } => {
+            let change = AdminChange::new(CLI_ACTOR, "admin_keys.revoke");
+            if !store.revoke_admin_key(&name, Utc::now(), &change).await? {
+                anyhow::bail!("no unrevoked admin key named {name}");
+            }
+            println!("Revoked admin key {name}");
+        }
+        Commands::ListAdminKeys => {
+            let now = Utc::now();
+            for k in store.admin_keys().await? {
+                let state = match (k.revoked_at, k.expires_at) {
+                    (Some(at), _) => format!("revoked {}", at.to_rfc3339()),
+                    (None, Some(at)) if at <= now => format!("expired {}", at.to_rfc3339()),
+                    (None, Some(at)) => format!("expires {}", at.to_rfc3339()),
+                    (None, None) => "active".into(),
+                };
                 println!(
-                    "#{:>6} {} seed={} nonce={} hash={} payout={}",
-                    id, ts, client_seed, nonce, server_seed_hash, payout
+                    "{:<20} {:<8} id={} created={} {state}",
+                    k.name,
+                    k.role.as_str(),
+                    k.key_id,
+                    k.created_at.to_rfc3339()
                 );
             }
         }
+        Commands::ViewLogs { n } => {
+            store
+                .spins(&SpinQuery::default(), None, n)
+                .await?
+                .iter()
+                .for_each(print_spin);
+        }
         Commands::ExportCsv { path } => {
             let mut wtr = csv::Writer::from_path(&path)?;
-            let rows = sqlx::query("SELECT id, ts, client_seed, nonce, server_seed_hash, result_reels_json, payout FROM spins ORDER BY id ASC")
-                .fetch_all(&pool).await?;
-            let total = rows.len();
-            for r in &rows {
-                use sqlx::Row;
-                wtr.write_record(&[
-                    r.get::<i64, _>("id").to_string(),
-                    r.get::<String, _>("ts"),
-                    r.get::<String, _>("client_seed"),
-                    r.get::<i64, _>("nonce").to_string(),
-                    r.get::<String, _>("server_seed_hash"),
-                    r.get::<String, _>("result_reels_json"),
-                    r.get::<f64, _>("payout").to_string(),
-                ])?;
+            let mut total = 0;
+            let mut after = 0;
+            loop {
+                let spins = store.spins_after(after, EXPORT_BATCH).await?;
+                let Some(last) = spins.last() else {
+                    break;
+                };
+                after = last.id;
+                for s in &spins {
+                    wtr.write_record(&[
+                        s.id.to_string(),
+                        s.ts.to_rfc3339(),
+                        s.client_seed.clone(),
+                        s.nonce.to_string(),
+                        s.server_seed_hash.clone(),
+                        serde_json::to_string(&s.result_reels)?,
+                        s.payout.to_string(),
+                        s.config_hash.clone().unwrap_or_default(),
+                        s.prev_hash.clone(),
+                        s.row_hash.clone(),
+                    ])?;
+                }
+                total += spins.len();
             }
             wtr.flush()?;
             println!("Exported {} rows to {}", total, path);
         }
+        Commands::VerifyChain { length, head } => {
+            let published = length.zip(head).map(|(length, head_hash)| ChainHead {
+                length,
+                last_spin_id: None,
+                head_hash,
+            });
+            let head =
+                tigrinho_store::verify_spin_chain(store.as_ref(), published.as_ref()).await?;
+            println!(
+                "Spin log intact: {} spins, head {}",
+                head.length, head.head_hash
+            );
+        }
+        Commands::VerifyRound {
+            round_id,
+            server_seed,
+        } => verify_round(store.as_ref(), round_id, server_seed).await?,
+        Commands::CommitRoot => match tigrinho_store::commit_merkle_root(store.as_ref()).await? {
+            Some(root) => println!(
+                "Committed Merkle root {} over spins {}..={} ({} spins): {}",
+                root.id, root.first_spin_id, root.last_spin_id, root.leaf_count, root.root
+            ),
+            None => println!("No spins since the last committed batch"),
+        },
+        Commands::NewReceiptKey | Commands::VerifyReceipt { .. } | Commands::NewSeedKek { .. } => {
+            unreachable!()
+        }
     }
 
     Ok(())
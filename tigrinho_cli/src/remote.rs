@@ -0,0 +1,83 @@
+use anyhow::{bail, Context};
+use chrono::Utc;
+use reqwest::{header::CONTENT_TYPE, Method, Url};
+use serde::de::DeserializeOwned;
+
+use tigrinho_shared::{AdminSignature, ApiErrorBody, SeedRotation, SpinLogEntry, SpinPage};
+
+/// A server's admin API, every request signed with an admin key so a captured
+/// request cannot be replayed.
+pub struct Remote {
+    base: Url,
+    admin_key: String,
+    http: reqwest::Client,
+}
+
+impl Remote {
+    pub fn new(server: &str, admin_key: &str) -> anyhow::Result<Self> {
+        // keep any path prefix the API is mounted under
+        let base = Url::parse(&format!("{}/", server.trim_end_matches('/')))
+            .with_context(|| format!("--server {server:?} is not a URL"))?;
+        Ok(Self {
+            base,
+            admin_key: admin_key.to_string(),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> anyhow::Result<T> {
+        let url = self.base.join(path.trim_start_matches('/'))?;
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let signed_path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        let signed = AdminSignature::sign(
+            &self.admin_key,
+            method.as_str(),
+            &signed_path,
+            body.as_bytes(),
+            Utc::now().timestamp(),
+            &tigrinho_store::generate_seed(),
+        )
+        .context("the admin key must look like <key id>.<secret>")?;
+
+        let mut req = self.http.request(method, url);
+        for (name, value) in signed.headers() {
+            req = req.header(name, value);
+        }
+        if !body.is_empty() {
+            req = req.header(CONTENT_TYPE, "application/json").body(body);
+        }
+        let resp = req.send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            match resp.json::<ApiErrorBody>().await {
+                Ok(err) => bail!("{status}: {} (request {})", err.message, err.request_id),
+                Err(_) => bail!("{status}"),
+            }
+        }
+        Ok(resp.json().await?)
+    }
+
+    pub async fn rotate_seed(&self, game: &str) -> anyhow::Result<SeedRotation> {
+        self.send(
+            Method::POST,
+            &format!("/admin/games/{game}/seeds/rotate"),
+            None,
+        )
+        .await
+    }
+
+    pub async fn spins(&self, limit: u32) -> anyhow::Result<Vec<SpinLogEntry>> {
+        let page: SpinPage = self
+            .send(Method::GET, &format!("/spins?limit={limit}"), None)
+            .await?;
+        Ok(page.spins)
+    }
+}
@@ -0,0 +1,259 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::rng::derive_hash_hex;
+
+// The spin log is a hash chain: every row stores the hash of the row before it
+// (`prev_hash`) and its own `row_hash` = SHA-256(prev_hash || "\n" || canonical
+// JSON of the row). Changing, dropping or moving a row breaks every later link,
+// and a published chain head pins the log up to that row.
+
+/// `prev_hash` of the first row of the log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// The columns of a spin log row that its hash covers, in canonical order.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainedSpin<'a> {
+    pub id: i64,
+    /// RFC 3339 in UTC with exactly six fractional digits, e.g.
+    /// `2026-10-18T12:00:00.123456Z`: the precision every store keeps.
+    pub ts: String,
+    pub game: &'a str,
+    pub player_id: Option<i64>,
+    pub client_seed: &'a str,
+    pub nonce: i64,
+    pub server_seed_hash: &'a str,
+    pub config_hash: Option<&'a str>,
+    pub result_reels: &'a [Vec<u8>],
+    pub payout: f64,
+    /// Left out when `None`, so rows from before rounds keep their hashes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round_id: Option<i64>,
+    /// Step of the round's feature and the action that played it; left out
+    /// for opening spins, like `round_id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<&'a str>,
+}
+
+impl ChainedSpin<'_> {
+    pub fn canonical_json(&self) -> String {
+        serde_json::to_string(self).expect("serializable row")
+    }
+
+    /// The `row_hash` of this row when it follows `prev_hash`.
+    pub fn row_hash(&self, prev_hash: &str) -> String {
+        derive_hash_hex(format!("{prev_hash}\n{}", self.canonical_json()).as_bytes())
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ChainError {
+    #[error("spin {0} does not match its row hash: the row was modified")]
+    Modified(i64),
+    #[error("spin {0} does not link to the row before it: rows were removed or reordered")]
+    Unlinked(i64),
+    #[error("row {0} of the log does not match the chain head for that length")]
+    HeadMismatch(u64),
+    #[error("the log has {found} chained rows but a chain head covers {expected}: rows were removed from the end")]
+    Truncated { found: u64, expected: u64 },
+}
+
+/// Checks a spin log fed to it row by row in id order.
+#[derive(Debug, Clone)]
+pub struct ChainVerifier {
+    head: String,
+    length: u64,
+    /// Chain heads the log must pass through, by length.
+    expected: BTreeMap<u64, String>,
+}
+
+impl Default for ChainVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainVerifier {
+    pub fn new() -> Self {
+        Self {
+            head: GENESIS_HASH.to_string(),
+            length: 0,
+            expected: BTreeMap::new(),
+        }
+    }
+
+    /// Also require the log to pass through a head recorded earlier, such as
+    /// the store's own or one published elsewhere.
+    pub fn expect_head(&mut self, length: u64, head_hash: &str) {
+        self.expected.insert(length, head_hash.to_string());
+    }
+
+    pub fn push(
+        &mut self,
+        spin: &ChainedSpin,
+        prev_hash: &str,
+        row_hash: &str,
+    ) -> Result<(), ChainError> {
+        if prev_hash != self.head {
+            return Err(ChainError::Unlinked(spin.id));
+        }
+        if spin.row_hash(prev_hash) != row_hash {
+            return Err(ChainError::Modified(spin.id));
+        }
+        self.head = row_hash.to_string();
+        self.length += 1;
+        if self
+            .expected
+            .get(&self.length)
+            .is_some_and(|h| *h != self.head)
+        {
+            return Err(ChainError::HeadMismatch(self.length));
+        }
+        Ok(())
+    }
+
+    /// Call after the last row; fails if an expected head lies beyond it.
+    pub fn finish(&self) -> Result<(), ChainError> {
+        if self.expected.get(&0).is_some_and(|h| h != GENESIS_HASH) {
+            return Err(ChainError::HeadMismatch(0));
+        }
+        match self.expected.keys().next_back() {
+            Some(&expected) if expected > self.length => Err(ChainError::Truncated {
+                found: self.length,
+                expected,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Hash of the last row pushed, or [`GENESIS_HASH`].
+    pub fn head(&self) -> &str {
+        &self.head
+    }
+
+    /// Rows pushed so far.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row {
+        id: i64,
+        payout: f64,
+        prev_hash: String,
+        row_hash: String,
+    }
+
+    const REELS: &[Vec<u8>] = &[];
+
+    fn chained(id: i64, payout: f64) -> ChainedSpin<'static> {
+        ChainedSpin {
+            id,
+            ts: "2026-10-18T12:00:00.000000Z".into(),
+            game: "tigrinho",
+            player_id: Some(1),
+            client_seed: "c",
+            nonce: id,
+            server_seed_hash: "h",
+            config_hash: None,
+            result_reels: REELS,
+            payout,
+            round_id: None,
+            cursor: None,
+            action: None,
+        }
+    }
+
+    fn log(n: i64) -> Vec<Row> {
+        let mut prev = GENESIS_HASH.to_string();
+        (1..=n)
+            .map(|id| {
+                let row_hash = chained(id, 1.0).row_hash(&prev);
+                Row {
+                    id,
+                    payout: 1.0,
+                    prev_hash: std::mem::replace(&mut prev, row_hash.clone()),
+                    row_hash,
+                }
+            })
+            .collect()
+    }
+
+    fn verify(rows: &[Row], head: Option<(u64, &str)>) -> Result<(), ChainError> {
+        let mut v = ChainVerifier::new();
+        if let Some((length, hash)) = head {
+            v.expect_head(length, hash);
+        }
+        for r in rows {
+            v.push(&chained(r.id, r.payout), &r.prev_hash, &r.row_hash)?;
+        }
+        v.finish()
+    }
+
+    #[test]
+    fn intact_log_verifies() {
+        let rows = log(4);
+        assert_eq!(verify(&rows, Some((4, &rows[3].row_hash))), Ok(()));
+        assert_eq!(verify(&[], Some((0, GENESIS_HASH))), Ok(()));
+    }
+
+    #[test]
+    fn round_id_is_hashed_only_when_present() {
+        let legacy = chained(1, 1.0);
+        assert!(!legacy.canonical_json().contains("round_id"));
+        let in_round = ChainedSpin {
+            round_id: Some(7),
+            ..legacy.clone()
+        };
+        assert_ne!(
+            legacy.row_hash(GENESIS_HASH),
+            in_round.row_hash(GENESIS_HASH)
+        );
+        assert!(!in_round.canonical_json().contains("cursor"));
+        let step = ChainedSpin {
+            cursor: Some(1),
+            action: Some("spin"),
+            ..in_round.clone()
+        };
+        assert_ne!(in_round.row_hash(GENESIS_HASH), step.row_hash(GENESIS_HASH));
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut rows = log(4);
+        rows[1].payout = 50.0;
+        assert_eq!(verify(&rows, None), Err(ChainError::Modified(2)));
+
+        let mut rows = log(4);
+        rows.remove(1);
+        assert_eq!(verify(&rows, None), Err(ChainError::Unlinked(3)));
+
+        let mut rows = log(4);
+        rows.swap(1, 2);
+        assert_eq!(verify(&rows, None), Err(ChainError::Unlinked(3)));
+
+        let rows = log(4);
+        let head = rows[3].row_hash.clone();
+        assert_eq!(
+            verify(&rows[..3], Some((4, &head))),
+            Err(ChainError::Truncated {
+                found: 3,
+                expected: 4
+            })
+        );
+        // a rewritten log that is internally consistent still misses the published head
+        let mut forged = log(4);
+        forged[3].row_hash = chained(4, 9.0).row_hash(&forged[3].prev_hash);
+        forged[3].payout = 9.0;
+        assert_eq!(
+            verify(&forged, Some((4, &head))),
+            Err(ChainError::HeadMismatch(4))
+        );
+    }
+}
@@ -4,6 +4,9 @@ use crate::{
     symbols::{ReelsConfig, Symbol},
 };
 
+/// Symbols per payline; only this many-of-a-kind wins are evaluated.
+pub const PAYLINE_LENGTH: usize = 3;
+
 #[derive(Debug, Clone)]
 pub struct EngineParams {
     pub reels: ReelsConfig,
@@ -21,25 +24,44 @@ pub fn compute_reel_window(rng: &ProvablyFairRng, reels: &ReelsConfig) -> Vec<Ve
     // For each reel, pick a starting index from RNG floats and take `rows` symbols circularly
     let cols = reels.reels.len();
     let floats = rng.next_floats(cols);
+    let stops: Vec<usize> = reels
+        .reels
+        .iter()
+        .enumerate()
+        .map(|(col, reel)| ((floats[col] * reel.len() as f64).floor() as usize) % reel.len())
+        .collect();
+    window_at_stops(reels, &stops)
+}
+
+/// The visible window when each reel `col` stops at index `stops[col]` of its strip.
+pub fn window_at_stops(reels: &ReelsConfig, stops: &[usize]) -> Vec<Vec<Symbol>> {
+    let cols = reels.reels.len();
     let mut window: Vec<Vec<Symbol>> = vec![vec![Symbol::A; cols]; reels.rows];
     for (col, reel) in reels.reels.iter().enumerate() {
-        let start = ((floats[col] * reel.len() as f64).floor() as usize) % reel.len();
-        for r in 0..reels.rows {
+        let start = stops[col];
+        for (r, row) in window.iter_mut().enumerate() {
             let idx = (start + r) % reel.len();
-            window[r][col] = reel[idx];
+            row[col] = reel[idx];
         }
     }
     window
 }
 
-fn evaluate_payout(window: &Vec<Vec<Symbol>>, paytable: &Paytable, bet: f64) -> f64 {
+/// The paylines evaluated for a window, each as `(row, col)` positions: every
+/// visible row, read across the first [`PAYLINE_LENGTH`] reels.
+pub fn paylines(reels: &ReelsConfig) -> Vec<Vec<(usize, usize)>> {
+    (0..reels.rows)
+        .map(|row| (0..PAYLINE_LENGTH).map(|col| (row, col)).collect())
+        .collect()
+}
+
+pub(crate) fn evaluate_payout(window: &[Vec<Symbol>], paytable: &Paytable, bet: f64) -> f64 {
     // Super-simple rule: pay only for row-wise 3-in-a-row of same symbol (Wild matches any)
-    let rows = window.len();
     let mut total = 0.0;
-    for r in 0..rows {
-        let a = window[r][0];
-        let b = window[r][1];
-        let c = window[r][2];
+    for row in window {
+        let a = row[0];
+        let b = row[1];
+        let c = row[2];
         // count matches treating wild as joker
         let all_same = match (a, b, c) {
             (Symbol::Wild, _, _) | (_, Symbol::Wild, _) | (_, _, Symbol::Wild) => true,
@@ -122,4 +144,20 @@ mod tests {
         assert_eq!(out1.payout, out2.payout);
         assert_eq!(out1.reel_window, out2.reel_window);
     }
+
+    #[test]
+    fn paylines_match_evaluation() {
+        let reels = ReelsConfig::default_3x3();
+        let lines = paylines(&reels);
+        assert_eq!(lines.len(), reels.rows);
+        let paytable = Paytable::simple_default();
+        // a window paying on exactly one row pays once per line that covers it
+        for (i, line) in lines.iter().enumerate() {
+            let mut window = vec![vec![Symbol::A, Symbol::B, Symbol::C]; reels.rows];
+            for &(row, col) in line {
+                window[row][col] = Symbol::D;
+            }
+            assert!(evaluate_payout(&window, &paytable, 1.0) > 0.0, "line {i}");
+        }
+    }
 }
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{
+    engine::{EngineParams, PAYLINE_LENGTH},
+    paytable::Paytable,
+    rng::derive_hash_hex,
+    round::{FreeSpins, MAX_FREE_SPINS},
+    symbols::{ReelsConfig, SYMBOL_COUNT},
+};
+
+/// Largest accepted reel strip / window, to keep exact RTP enumeration tractable.
+pub const MAX_REEL_LEN: usize = 256;
+pub const MAX_ROWS: usize = 10;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    #[error("the game needs at least {PAYLINE_LENGTH} reels, got {0}")]
+    TooFewReels(usize),
+    #[error("reel {0} is empty")]
+    EmptyReel(usize),
+    #[error("reel {0} is longer than {MAX_REEL_LEN} symbols")]
+    ReelTooLong(usize),
+    #[error("rows must be between 1 and {MAX_ROWS}, got {0}")]
+    InvalidRows(usize),
+    #[error("paytable is empty")]
+    EmptyPaytable,
+    #[error("paytable entry {0}: unknown symbol id {1}")]
+    UnknownSymbol(usize, u8),
+    #[error(
+        "paytable entry {0}: only {PAYLINE_LENGTH}-of-a-kind wins are supported, got count {1}"
+    )]
+    UnsupportedCount(usize, u8),
+    #[error("paytable entry {0}: payout multiplier must be a finite, non-negative number")]
+    InvalidMultiplier(usize),
+    #[error("paytable entry {0}: duplicate entry for symbol {1} x{2}")]
+    DuplicateEntry(usize, u8, u8),
+    #[error("rtp_target must be in (0, 1], got {0}")]
+    InvalidRtpTarget(f64),
+    #[error("free_spins: unknown trigger symbol id {0}")]
+    UnknownTriggerSymbol(u8),
+    #[error("free_spins: trigger_count must be between 1 and the window size {1}, got {0}")]
+    InvalidTriggerCount(u8, usize),
+    #[error("free_spins: spins must be between 1 and {MAX_FREE_SPINS}, got {0}")]
+    InvalidFreeSpinCount(u32),
+    #[error("free_spins: multiplier must be a finite, positive number")]
+    InvalidFreeSpinMultiplier,
+}
+
+/// Everything that determines how a spin is evaluated: reel strips, paytable,
+/// target RTP and the optional free spins feature.
+///
+/// Definitions are identified by the SHA-256 of their canonical JSON, so a spin
+/// recorded with a config hash can always be re-evaluated against the exact rules.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GameDefinition {
+    pub reels: ReelsConfig,
+    pub paytable: Paytable,
+    pub rtp_target: f64,
+    /// Left out of the canonical JSON when absent, so definitions from before
+    /// features existed keep their hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free_spins: Option<FreeSpins>,
+}
+
+impl GameDefinition {
+    pub fn default_game() -> Self {
+        Self {
+            reels: ReelsConfig::default_3x3(),
+            paytable: Paytable::simple_default(),
+            rtp_target: 0.95,
+            free_spins: None,
+        }
+    }
+
+    /// Canonical serialization the config hash is computed over.
+    pub fn canonical_json(&self) -> String {
+        serde_json::to_string(self).expect("serializable definition")
+    }
+
+    pub fn config_hash(&self) -> String {
+        derive_hash_hex(self.canonical_json().as_bytes())
+    }
+
+    /// Reject definitions the engine cannot evaluate meaningfully.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.reels.reels.len() < PAYLINE_LENGTH {
+            return Err(ValidationError::TooFewReels(self.reels.reels.len()));
+        }
+        for (i, reel) in self.reels.reels.iter().enumerate() {
+            if reel.is_empty() {
+                return Err(ValidationError::EmptyReel(i));
+            }
+            if reel.len() > MAX_REEL_LEN {
+                return Err(ValidationError::ReelTooLong(i));
+            }
+        }
+        if self.reels.rows == 0 || self.reels.rows > MAX_ROWS {
+            return Err(ValidationError::InvalidRows(self.reels.rows));
+        }
+        if self.paytable.0.is_empty() {
+            return Err(ValidationError::EmptyPaytable);
+        }
+        let mut seen = HashSet::new();
+        for (i, e) in self.paytable.0.iter().enumerate() {
+            if e.symbol as usize >= SYMBOL_COUNT {
+                return Err(ValidationError::UnknownSymbol(i, e.symbol));
+            }
+            if e.count as usize != PAYLINE_LENGTH {
+                return Err(ValidationError::UnsupportedCount(i, e.count));
+            }
+            if !e.payout_multiplier.is_finite() || e.payout_multiplier < 0.0 {
+                return Err(ValidationError::InvalidMultiplier(i));
+            }
+            if !seen.insert((e.symbol, e.count)) {
+                return Err(ValidationError::DuplicateEntry(i, e.symbol, e.count));
+            }
+        }
+        if !(self.rtp_target > 0.0 && self.rtp_target <= 1.0) {
+            return Err(ValidationError::InvalidRtpTarget(self.rtp_target));
+        }
+        if let Some(fs) = &self.free_spins {
+            if fs.trigger_symbol as usize >= SYMBOL_COUNT {
+                return Err(ValidationError::UnknownTriggerSymbol(fs.trigger_symbol));
+            }
+            let window = self.reels.rows * self.reels.reels.len();
+            if fs.trigger_count == 0 || fs.trigger_count as usize > window {
+                return Err(ValidationError::InvalidTriggerCount(
+                    fs.trigger_count,
+                    window,
+                ));
+            }
+            if fs.spins == 0 || fs.spins > MAX_FREE_SPINS {
+                return Err(ValidationError::InvalidFreeSpinCount(fs.spins));
+            }
+            if !fs.multiplier.is_finite() || fs.multiplier <= 0.0 {
+                return Err(ValidationError::InvalidFreeSpinMultiplier);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn engine_params(&self) -> EngineParams {
+        EngineParams {
+            reels: self.reels.clone(),
+            paytable: self.paytable.clone(),
+            rtp_target: self.rtp_target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_hash_is_content_addressed() {
+        let a = GameDefinition::default_game();
+        let b: GameDefinition = serde_json::from_str(&a.canonical_json()).unwrap();
+        assert_eq!(a.config_hash(), b.config_hash());
+
+        let mut c = a.clone();
+        c.paytable.0[0].payout_multiplier += 1.0;
+        assert_ne!(a.config_hash(), c.config_hash());
+
+        // no feature, no trace of it in the canonical JSON
+        assert!(!a.canonical_json().contains("free_spins"));
+        let mut d = a.clone();
+        d.free_spins = Some(FreeSpins {
+            trigger_symbol: 4,
+            trigger_count: 3,
+            spins: 5,
+            multiplier: 2.0,
+        });
+        assert_eq!(d.validate(), Ok(()));
+        assert_ne!(a.config_hash(), d.config_hash());
+        d.free_spins.as_mut().unwrap().trigger_count = 10;
+        assert_eq!(
+            d.validate(),
+            Err(ValidationError::InvalidTriggerCount(10, 9))
+        );
+    }
+
+    #[test]
+    fn validate_rejects_bad_paytables() {
+        assert_eq!(GameDefinition::default_game().validate(), Ok(()));
+
+        let mut def = GameDefinition::default_game();
+        def.paytable.0.clear();
+        assert_eq!(def.validate(), Err(ValidationError::EmptyPaytable));
+
+        let mut def = GameDefinition::default_game();
+        def.paytable.0[1].payout_multiplier = -2.0;
+        assert_eq!(def.validate(), Err(ValidationError::InvalidMultiplier(1)));
+
+        let mut def = GameDefinition::default_game();
+        def.paytable.0[2].symbol = 9;
+        assert_eq!(def.validate(), Err(ValidationError::UnknownSymbol(2, 9)));
+
+        let mut def = GameDefinition::default_game();
+        def.paytable.0[3].symbol = def.paytable.0[0].symbol;
+        assert!(matches!(
+            def.validate(),
+            Err(ValidationError::DuplicateEntry(3, _, 3))
+        ));
+    }
+}
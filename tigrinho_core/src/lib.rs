@@ -1,11 +1,29 @@
+pub mod chain;
 pub mod engine;
+pub mod game;
+pub mod math;
+pub mod merkle;
 pub mod paytable;
+pub mod receipt;
 pub mod rng;
+pub mod round;
 pub mod symbols;
 
+pub use crate::chain::{ChainError, ChainVerifier, ChainedSpin, GENESIS_HASH};
 pub use crate::engine::{
-    compute_reel_window, spin_once, spin_with_seeds, verify_reels, EngineParams, SpinOutcome,
+    compute_reel_window, paylines, spin_once, spin_with_seeds, verify_reels, window_at_stops,
+    EngineParams, SpinOutcome,
 };
+pub use crate::game::{GameDefinition, ValidationError};
+pub use crate::math::{exact_report, math_report, simulated_report, MathMethod, MathReport};
+pub use crate::merkle::{verify_inclusion, MerkleTree};
 pub use crate::paytable::{Paytable, PaytableEntry};
+pub use crate::receipt::{
+    key_id, verify_receipt, Receipt, ReceiptError, ReceiptSigner, RECEIPT_ALGORITHM,
+};
 pub use crate::rng::{derive_floats, derive_hash_hex, ProvablyFairRng};
+pub use crate::round::{
+    advance_round, open_round, opening_feature, replay_round, FeatureState, FreeSpins, RoundAction,
+    RoundError, RoundStep,
+};
 pub use crate::symbols::{ReelsConfig, Symbol};
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    engine::{evaluate_payout, window_at_stops},
+    game::GameDefinition,
+    round::{advance_round, open_round, FreeSpins, RoundAction},
+};
+
+/// Above this many reel-stop combinations the exact RTP is not enumerated.
+pub const EXACT_COMBINATION_LIMIT: u64 = 5_000_000;
+/// Rounds used when falling back to simulation.
+pub const DEFAULT_SIMULATION_ROUNDS: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MathMethod {
+    /// Every reel-stop combination enumerated; figures are the true values.
+    Exact,
+    /// Monte Carlo estimate; see `rtp_std_error` for its precision.
+    Simulated,
+}
+
+/// Per-unit-bet statistics of a game definition. A round includes the free
+/// spins its opening spin triggers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MathReport {
+    pub method: MathMethod,
+    /// Combinations enumerated or rounds simulated.
+    pub rounds: u64,
+    pub rtp: f64,
+    pub hit_frequency: f64,
+    /// Variance of the win multiplier of a single round.
+    pub variance: f64,
+    pub std_dev: f64,
+    pub max_multiplier: f64,
+    /// Standard error of `rtp`; zero for exact results.
+    pub rtp_std_error: f64,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    n: u64,
+    sum: f64,
+    sum_sq: f64,
+    hits: u64,
+    max: f64,
+}
+
+impl Accumulator {
+    fn push(&mut self, multiplier: f64) {
+        self.n += 1;
+        self.sum += multiplier;
+        self.sum_sq += multiplier * multiplier;
+        if multiplier > 0.0 {
+            self.hits += 1;
+        }
+        self.max = self.max.max(multiplier);
+    }
+
+    fn report(&self, method: MathMethod) -> MathReport {
+        let n = self.n.max(1) as f64;
+        let mean = self.sum / n;
+        let variance = (self.sum_sq / n - mean * mean).max(0.0);
+        let std_dev = variance.sqrt();
+        MathReport {
+            method,
+            rounds: self.n,
+            rtp: mean,
+            hit_frequency: self.hits as f64 / n,
+            variance,
+            std_dev,
+            max_multiplier: self.max,
+            rtp_std_error: match method {
+                MathMethod::Exact => 0.0,
+                MathMethod::Simulated => std_dev / n.sqrt(),
+            },
+        }
+    }
+}
+
+/// Number of equally likely reel-stop combinations, saturating on overflow.
+pub fn combinations(def: &GameDefinition) -> u64 {
+    def.reels
+        .reels
+        .iter()
+        .fold(1u64, |acc, reel| acc.saturating_mul(reel.len() as u64))
+}
+
+/// Exact figures of a round with free spins, from the single-spin figures of
+/// every window (`spins`) and of the windows that trigger (`triggered`). Free
+/// spins are independent spins paid at the multiplier, so with `n` of them at
+/// multiplier `m`, single-spin mean `mu` and variance `var`:
+///
+///   E[round]   = mu + P(trigger) * n * m * mu
+///   E[round^2] = E[X^2] + 2 * n * m * mu * E[X; trigger]
+///                + P(trigger) * m^2 * (n * var + n^2 * mu^2)
+fn free_spins_report(spins: &Accumulator, triggered: &Accumulator, fs: &FreeSpins) -> MathReport {
+    let total = spins.n.max(1) as f64;
+    let (n, m) = (fs.spins as f64, fs.multiplier);
+    let mu = spins.sum / total;
+    let var = (spins.sum_sq / total - mu * mu).max(0.0);
+    let p = triggered.n as f64 / total;
+
+    let rtp = mu + p * n * m * mu;
+    let second = spins.sum_sq / total
+        + 2.0 * n * m * mu * triggered.sum / total
+        + p * m * m * (n * var + n * n * mu * mu);
+    let variance = (second - rtp * rtp).max(0.0);
+    // a round misses when its opening spin misses and, if it triggered, so does every free spin
+    let miss = (spins.n - spins.hits) as f64 / total;
+    let triggered_miss = (triggered.n - triggered.hits) as f64 / total;
+    let plain_miss = miss - triggered_miss;
+    let hit_frequency =
+        1.0 - plain_miss - triggered_miss * (1.0 - spins.hits as f64 / total).powf(n);
+    let max_multiplier = if triggered.n > 0 {
+        spins.max.max(triggered.max + n * m * spins.max)
+    } else {
+        spins.max
+    };
+    MathReport {
+        method: MathMethod::Exact,
+        rounds: spins.n,
+        rtp,
+        hit_frequency,
+        variance,
+        std_dev: variance.sqrt(),
+        max_multiplier,
+        rtp_std_error: 0.0,
+    }
+}
+
+/// Exact figures by enumerating every reel-stop combination, or `None` if
+/// there are more than [`EXACT_COMBINATION_LIMIT`].
+pub fn exact_report(def: &GameDefinition) -> Option<MathReport> {
+    if combinations(def) > EXACT_COMBINATION_LIMIT {
+        return None;
+    }
+    let lens: Vec<usize> = def.reels.reels.iter().map(|r| r.len()).collect();
+    let mut stops = vec![0usize; lens.len()];
+    let mut acc = Accumulator::default();
+    let mut triggered = Accumulator::default();
+    loop {
+        let window = window_at_stops(&def.reels, &stops);
+        let multiplier = evaluate_payout(&window, &def.paytable, 1.0);
+        acc.push(multiplier);
+        if def
+            .free_spins
+            .as_ref()
+            .is_some_and(|fs| fs.triggered_by(&window))
+        {
+            triggered.push(multiplier);
+        }
+        // odometer-style increment over all stop combinations
+        let mut col = 0;
+        loop {
+            if col == stops.len() {
+                return Some(match &def.free_spins {
+                    Some(fs) => free_spins_report(&acc, &triggered, fs),
+                    None => acc.report(MathMethod::Exact),
+                });
+            }
+            stops[col] += 1;
+            if stops[col] < lens[col] {
+                break;
+            }
+            stops[col] = 0;
+            col += 1;
+        }
+    }
+}
+
+/// Monte Carlo estimate using the production RNG keyed by `seed`, so runs are
+/// reproducible. Each round is played to the end, free spins included.
+pub fn simulated_report(def: &GameDefinition, rounds: u64, seed: &str) -> MathReport {
+    let mut acc = Accumulator::default();
+    for nonce in 0..rounds {
+        let mut step = open_round(def, seed, "rtp-simulation", nonce, 1.0);
+        let mut multiplier = step.payout;
+        while let Some(state) = step.state {
+            step = advance_round(
+                def,
+                seed,
+                "rtp-simulation",
+                nonce,
+                1.0,
+                &state,
+                step.cursor + 1,
+                RoundAction::Spin,
+            )
+            .expect("the round's own feature");
+            multiplier += step.payout;
+        }
+        acc.push(multiplier);
+    }
+    acc.report(MathMethod::Simulated)
+}
+
+/// Exact figures when tractable, otherwise a default-sized simulation.
+pub fn math_report(def: &GameDefinition) -> MathReport {
+    exact_report(def)
+        .unwrap_or_else(|| simulated_report(def, DEFAULT_SIMULATION_ROUNDS, &def.config_hash()))
+}
@@ -0,0 +1,158 @@
+use sha2::{Digest, Sha256};
+
+// Batches of the spin log are committed as Merkle trees over their row hashes:
+// leaf = SHA-256(0x00 || row_hash), node = SHA-256(0x01 || left || right), and
+// an odd node at the end of a level moves up unchanged. The prefixes keep a
+// leaf from ever being passed off as an inner node.
+
+type Hash = [u8; 32];
+
+fn leaf_hash(row_hash: &str) -> Hash {
+    Sha256::new()
+        .chain_update([0u8])
+        .chain_update(row_hash.as_bytes())
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    Sha256::new()
+        .chain_update([1u8])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// A Merkle tree over the `row_hash`es of a batch of spins, in id order.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// Leaves first, the root alone last.
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new<S: AsRef<str>>(row_hashes: &[S]) -> Self {
+        let mut levels = vec![row_hashes
+            .iter()
+            .map(|h| leaf_hash(h.as_ref()))
+            .collect::<Vec<_>>()];
+        while let Some(level) = levels.last().filter(|l| l.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [odd] => *odd,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hex root; `None` for an empty batch.
+    pub fn root(&self) -> Option<String> {
+        self.levels.last()?.first().map(hex::encode)
+    }
+
+    /// Sibling hashes from the leaf at `index` up to the root, skipping the
+    /// levels where the path node has no sibling.
+    pub fn proof(&self, index: usize) -> Option<Vec<String>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut index = index;
+        let mut siblings = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                siblings.push(hex::encode(sibling));
+            }
+            index /= 2;
+        }
+        Some(siblings)
+    }
+}
+
+/// Whether `siblings` (from [`MerkleTree::proof`]) lead from the spin with
+/// `row_hash`, leaf `index` of a batch of `leaf_count`, to `root`. Take
+/// `leaf_count` and `root` from the published batch, not from the proof.
+pub fn verify_inclusion(
+    row_hash: &str,
+    index: u64,
+    leaf_count: u64,
+    siblings: &[String],
+    root: &str,
+) -> bool {
+    if index >= leaf_count {
+        return false;
+    }
+    let mut hash = leaf_hash(row_hash);
+    let mut siblings = siblings.iter();
+    let (mut index, mut size) = (index, leaf_count);
+    while size > 1 {
+        if index % 2 == 1 || index + 1 < size {
+            let Some(sibling) = siblings.next().and_then(|s| decode(s)) else {
+                return false;
+            };
+            hash = if index % 2 == 1 {
+                node_hash(&sibling, &hash)
+            } else {
+                node_hash(&hash, &sibling)
+            };
+        }
+        index /= 2;
+        size = size.div_ceil(2);
+    }
+    siblings.next().is_none() && hex::encode(hash) == root
+}
+
+fn decode(hex_hash: &str) -> Option<Hash> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("row-{i}")).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_against_the_root() {
+        for n in 1..=9 {
+            let rows = rows(n);
+            let tree = MerkleTree::new(&rows);
+            let root = tree.root().unwrap();
+            for (i, row) in rows.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert!(verify_inclusion(row, i as u64, n as u64, &proof, &root));
+            }
+            assert!(tree.proof(n).is_none());
+        }
+        assert!(MerkleTree::new::<String>(&[]).root().is_none());
+    }
+
+    #[test]
+    fn forged_proofs_fail() {
+        let rows = rows(5);
+        let tree = MerkleTree::new(&rows);
+        let root = tree.root().unwrap();
+        let proof = tree.proof(2).unwrap();
+        assert!(!verify_inclusion("row-x", 2, 5, &proof, &root));
+        assert!(!verify_inclusion(&rows[2], 3, 5, &proof, &root));
+        assert!(!verify_inclusion(&rows[2], 2, 5, &proof[1..], &root));
+        let mut extended = proof.clone();
+        extended.push(root.clone());
+        assert!(!verify_inclusion(&rows[2], 2, 5, &extended, &root));
+    }
+}
@@ -1,14 +1,14 @@
 use crate::symbols::Symbol;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PaytableEntry {
     pub symbol: u8, // Symbol index
     pub count: u8,
     pub payout_multiplier: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Paytable(pub Vec<PaytableEntry>);
 
 impl Paytable {
@@ -0,0 +1,162 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::Serialize;
+
+use crate::rng::derive_hash_hex;
+
+// A receipt is the server's Ed25519 signature over the outcome it returned for
+// a spin. The signed message is a fixed prefix followed by the canonical JSON
+// of `Receipt`, so a receipt cannot be confused with any other signed payload.
+
+/// Name of the signature scheme, as published next to each key.
+pub const RECEIPT_ALGORITHM: &str = "ed25519";
+
+const MESSAGE_PREFIX: &[u8] = b"tigrinho-receipt-v1\n";
+
+/// The fields of a spin response that its receipt covers, in canonical order.
+#[derive(Debug, Clone, Serialize)]
+pub struct Receipt<'a> {
+    pub config_hash: &'a str,
+    pub server_seed_hash: &'a str,
+    pub nonce: u64,
+    pub reels: &'a [Vec<u8>],
+    pub payout: f64,
+    /// Step of the round's feature; absent for the spin that opens a round, so
+    /// those sign exactly as before features existed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u64>,
+}
+
+impl Receipt<'_> {
+    /// The exact bytes that are signed.
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = MESSAGE_PREFIX.to_vec();
+        serde_json::to_writer(&mut message, self).expect("serializable receipt");
+        message
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ReceiptError {
+    #[error("key must be 32 bytes of hex")]
+    InvalidKey,
+    #[error("signature must be 64 bytes of hex")]
+    MalformedSignature,
+    #[error("signature does not match the receipt and key")]
+    BadSignature,
+}
+
+fn decode<const N: usize>(hex_str: &str) -> Option<[u8; N]> {
+    hex::decode(hex_str).ok()?.try_into().ok()
+}
+
+/// Short identifier of a public key: the first 16 hex digits of its SHA-256.
+pub fn key_id(public_key_hex: &str) -> String {
+    derive_hash_hex(public_key_hex.as_bytes())[..16].to_string()
+}
+
+/// The server side: signs receipts with one secret key.
+#[derive(Clone)]
+pub struct ReceiptSigner {
+    key: SigningKey,
+    public_key: String,
+    key_id: String,
+}
+
+impl ReceiptSigner {
+    /// From the 32-byte secret key in hex.
+    pub fn from_hex(secret_hex: &str) -> Result<Self, ReceiptError> {
+        let key = SigningKey::from_bytes(&decode(secret_hex).ok_or(ReceiptError::InvalidKey)?);
+        let public_key = hex::encode(key.verifying_key().as_bytes());
+        Ok(Self {
+            key_id: key_id(&public_key),
+            key,
+            public_key,
+        })
+    }
+
+    /// Public key in hex.
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Hex signature over `receipt`.
+    pub fn sign(&self, receipt: &Receipt) -> String {
+        hex::encode(self.key.sign(&receipt.message()).to_bytes())
+    }
+}
+
+/// Check a hex `signature` over `receipt` against a hex public key.
+pub fn verify_receipt(
+    receipt: &Receipt,
+    public_key_hex: &str,
+    signature_hex: &str,
+) -> Result<(), ReceiptError> {
+    let key = decode(public_key_hex)
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or(ReceiptError::InvalidKey)?;
+    let signature =
+        Signature::from_bytes(&decode(signature_hex).ok_or(ReceiptError::MalformedSignature)?);
+    key.verify(&receipt.message(), &signature)
+        .map_err(|_| ReceiptError::BadSignature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+    fn receipt(reels: &[Vec<u8>], payout: f64) -> Receipt<'_> {
+        Receipt {
+            config_hash: "c",
+            server_seed_hash: "h",
+            nonce: 7,
+            reels,
+            payout,
+            cursor: None,
+        }
+    }
+
+    #[test]
+    fn receipts_verify_only_unchanged() {
+        let signer = ReceiptSigner::from_hex(SECRET).unwrap();
+        // RFC 8032 test vector 1
+        assert_eq!(
+            signer.public_key(),
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+        );
+        let reels = vec![vec![0, 1, 2]];
+        let signature = signer.sign(&receipt(&reels, 2.5));
+        assert_eq!(
+            verify_receipt(&receipt(&reels, 2.5), signer.public_key(), &signature),
+            Ok(())
+        );
+        assert_eq!(
+            verify_receipt(&receipt(&reels, 25.0), signer.public_key(), &signature),
+            Err(ReceiptError::BadSignature)
+        );
+        let other = ReceiptSigner::from_hex(&"11".repeat(32)).unwrap();
+        assert_eq!(
+            verify_receipt(&receipt(&reels, 2.5), other.public_key(), &signature),
+            Err(ReceiptError::BadSignature)
+        );
+        assert_eq!(
+            verify_receipt(&receipt(&reels, 2.5), signer.public_key(), "00"),
+            Err(ReceiptError::MalformedSignature)
+        );
+        assert!(ReceiptSigner::from_hex("short").is_err());
+
+        let step = Receipt {
+            cursor: Some(1),
+            ..receipt(&reels, 2.5)
+        };
+        assert_eq!(
+            verify_receipt(&step, signer.public_key(), &signature),
+            Err(ReceiptError::BadSignature)
+        );
+    }
+}
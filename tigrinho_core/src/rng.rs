@@ -3,6 +3,10 @@ use sha2::Sha256;
 
 // Deterministic RNG using provably-fair HMAC construction
 // server_seed (secret) + client_seed + nonce -> HMAC-SHA256 -> bytes -> floats in [0,1)
+// Later steps of a round (free spins and the like) sign
+// "client_seed:nonce:cursor:step" instead; step 0 is the plain spin. Plain
+// messages always end in the nonce's digits, so no client seed can make a spin
+// draw the same numbers as another round's step.
 
 pub type HmacSha256 = Hmac<Sha256>;
 
@@ -39,6 +43,8 @@ pub struct ProvablyFairRng {
     pub server_seed: String, // secret
     pub client_seed: String,
     pub nonce: u64,
+    /// Step within the round; 0 for the spin that opens it.
+    pub cursor: u64,
 }
 
 impl ProvablyFairRng {
@@ -47,6 +53,20 @@ impl ProvablyFairRng {
             server_seed: server_seed.into(),
             client_seed: client_seed.into(),
             nonce,
+            cursor: 0,
+        }
+    }
+
+    /// The RNG of step `cursor` of the round opened with `nonce`.
+    pub fn with_cursor(
+        server_seed: impl Into<String>,
+        client_seed: impl Into<String>,
+        nonce: u64,
+        cursor: u64,
+    ) -> Self {
+        Self {
+            cursor,
+            ..Self::new(server_seed, client_seed, nonce)
         }
     }
 
@@ -56,7 +76,10 @@ impl ProvablyFairRng {
 
     pub fn hmac_bytes(&self) -> [u8; 32] {
         let mut mac = HmacSha256::new_from_slice(self.server_seed.as_bytes()).expect("HMAC key");
-        let msg = format!("{}:{}", self.client_seed, self.nonce);
+        let msg = match self.cursor {
+            0 => format!("{}:{}", self.client_seed, self.nonce),
+            cursor => format!("{}:{}:{cursor}:step", self.client_seed, self.nonce),
+        };
         mac.update(msg.as_bytes());
         let res = mac.finalize().into_bytes();
         let mut out = [0u8; 32];
@@ -82,4 +105,24 @@ mod tests {
         assert_eq!(rng1.hmac_bytes().to_vec(), rng2.hmac_bytes().to_vec());
         assert_eq!(rng1.next_floats(5), rng2.next_floats(5));
     }
+
+    #[test]
+    fn cursors_draw_apart_from_the_opening_spin() {
+        let open = ProvablyFairRng::new("server", "client", 1);
+        assert_eq!(
+            ProvablyFairRng::with_cursor("server", "client", 1, 0).hmac_bytes(),
+            open.hmac_bytes()
+        );
+        let step = ProvablyFairRng::with_cursor("server", "client", 1, 1);
+        assert_ne!(step.hmac_bytes(), open.hmac_bytes());
+        assert_ne!(
+            step.hmac_bytes(),
+            ProvablyFairRng::with_cursor("server", "client", 1, 2).hmac_bytes()
+        );
+        // a client seed cannot replay a step as a plain spin
+        assert_ne!(
+            ProvablyFairRng::new("server", "client:1", 1).hmac_bytes(),
+            step.hmac_bytes()
+        );
+    }
 }
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    engine::{compute_reel_window, evaluate_payout},
+    game::GameDefinition,
+    rng::ProvablyFairRng,
+    symbols::Symbol,
+};
+
+// A round is its opening spin plus, when that spin triggers the game's
+// feature, the feature's steps. Step `cursor` draws from the round's seed pair
+// and nonce with `ProvablyFairRng::with_cursor`, so a whole round replays from
+// the seeds, the nonce and the actions the player took.
+
+/// Most free spins a trigger may award.
+pub const MAX_FREE_SPINS: u32 = 100;
+
+/// Free spins: at least `trigger_count` of `trigger_symbol` anywhere in the
+/// opening window award `spins` more spins, each paid at `multiplier` times
+/// the paytable. Free spins do not retrigger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FreeSpins {
+    pub trigger_symbol: u8,
+    pub trigger_count: u8,
+    pub spins: u32,
+    pub multiplier: f64,
+}
+
+impl FreeSpins {
+    pub fn triggered_by(&self, window: &[Vec<Symbol>]) -> bool {
+        let count = window
+            .iter()
+            .flatten()
+            .filter(|s| s.to_index() == self.trigger_symbol)
+            .count();
+        count >= self.trigger_count as usize
+    }
+}
+
+/// Where an in-progress feature stands between two steps.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "feature", rename_all = "snake_case")]
+pub enum FeatureState {
+    FreeSpins { remaining: u32, played: u32 },
+}
+
+/// What the player asks an in-progress feature to do next.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundAction {
+    /// Play the next free spin.
+    Spin,
+}
+
+impl RoundAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoundAction::Spin => "spin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "spin" => Some(RoundAction::Spin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum RoundError {
+    #[error("the round is complete; step {0} does not exist")]
+    Complete(u64),
+    #[error("the game has no feature in progress to continue")]
+    NoFeature,
+}
+
+/// One step of a round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundStep {
+    /// 0 for the opening spin.
+    pub cursor: u64,
+    pub reel_window: Vec<Vec<Symbol>>,
+    pub payout: f64,
+    /// The feature's state after this step; `None` once the round is complete.
+    pub state: Option<FeatureState>,
+}
+
+/// The spin that opens a round: an ordinary spin, which may trigger the game's
+/// feature.
+pub fn open_round(
+    def: &GameDefinition,
+    server_seed: &str,
+    client_seed: &str,
+    nonce: u64,
+    bet: f64,
+) -> RoundStep {
+    let rng = ProvablyFairRng::new(server_seed, client_seed, nonce);
+    let window = compute_reel_window(&rng, &def.reels);
+    let payout = evaluate_payout(&window, &def.paytable, bet);
+    let state = opening_feature(def, &window);
+    RoundStep {
+        cursor: 0,
+        reel_window: window,
+        payout,
+        state,
+    }
+}
+
+/// The feature an opening spin showing `window` leaves its round in.
+pub fn opening_feature(def: &GameDefinition, window: &[Vec<Symbol>]) -> Option<FeatureState> {
+    def.free_spins
+        .as_ref()
+        .filter(|fs| fs.triggered_by(window))
+        .map(|fs| FeatureState::FreeSpins {
+            remaining: fs.spins,
+            played: 0,
+        })
+}
+
+/// Step `cursor` (from 1) of a round whose feature stands at `state`.
+#[allow(clippy::too_many_arguments)]
+pub fn advance_round(
+    def: &GameDefinition,
+    server_seed: &str,
+    client_seed: &str,
+    nonce: u64,
+    bet: f64,
+    state: &FeatureState,
+    cursor: u64,
+    action: RoundAction,
+) -> Result<RoundStep, RoundError> {
+    match (*state, action) {
+        (FeatureState::FreeSpins { remaining, played }, RoundAction::Spin) => {
+            let fs = def.free_spins.as_ref().ok_or(RoundError::NoFeature)?;
+            if remaining == 0 {
+                return Err(RoundError::Complete(cursor));
+            }
+            let rng = ProvablyFairRng::with_cursor(server_seed, client_seed, nonce, cursor);
+            let window = compute_reel_window(&rng, &def.reels);
+            let payout = evaluate_payout(&window, &def.paytable, bet) * fs.multiplier;
+            let state = (remaining > 1).then_some(FeatureState::FreeSpins {
+                remaining: remaining - 1,
+                played: played + 1,
+            });
+            Ok(RoundStep {
+                cursor,
+                reel_window: window,
+                payout,
+                state,
+            })
+        }
+    }
+}
+
+/// Every step of a round, replayed from its seeds and the actions the player
+/// took after the opening spin. Fails on an action after the round completed;
+/// the round is complete when the last step's `state` is `None`.
+pub fn replay_round(
+    def: &GameDefinition,
+    server_seed: &str,
+    client_seed: &str,
+    nonce: u64,
+    bet: f64,
+    actions: &[RoundAction],
+) -> Result<Vec<RoundStep>, RoundError> {
+    let mut steps = vec![open_round(def, server_seed, client_seed, nonce, bet)];
+    for (cursor, &action) in (1u64..).zip(actions) {
+        let state = steps
+            .last()
+            .and_then(|step| step.state)
+            .ok_or(RoundError::Complete(cursor))?;
+        let step = advance_round(
+            def,
+            server_seed,
+            client_seed,
+            nonce,
+            bet,
+            &state,
+            cursor,
+            action,
+        )?;
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free_spins_game() -> GameDefinition {
+        GameDefinition {
+            // any A in the window triggers
+            free_spins: Some(FreeSpins {
+                trigger_symbol: Symbol::A.to_index(),
+                trigger_count: 1,
+                spins: 3,
+                multiplier: 2.0,
+            }),
+            ..GameDefinition::default_game()
+        }
+    }
+
+    #[test]
+    fn rounds_replay_from_seeds_and_actions() {
+        let def = free_spins_game();
+        let nonce = (0..)
+            .find(|&n| open_round(&def, "server", "client", n, 1.0).state.is_some())
+            .unwrap();
+        let steps = replay_round(
+            &def,
+            "server",
+            "client",
+            nonce,
+            1.0,
+            &[RoundAction::Spin; 3],
+        )
+        .unwrap();
+        assert_eq!(steps.len(), 4);
+        assert_eq!(
+            steps[1].state,
+            Some(FeatureState::FreeSpins {
+                remaining: 2,
+                played: 1
+            })
+        );
+        assert_eq!(steps[3].state, None);
+        for (cursor, step) in steps.iter().enumerate() {
+            assert_eq!(step.cursor, cursor as u64);
+        }
+        // the opening spin is the plain spin of the nonce
+        let plain = crate::spin_with_seeds("server", "client", nonce, &def.engine_params(), 1.0, 1);
+        assert_eq!(steps[0].reel_window, plain.reel_window);
+        assert_eq!(steps[0].payout, plain.payout);
+        // steps are independent draws, paid at the multiplier
+        let rng = ProvablyFairRng::with_cursor("server", "client", nonce, 2);
+        let window = compute_reel_window(&rng, &def.reels);
+        assert_eq!(steps[2].reel_window, window);
+        assert_eq!(
+            steps[2].payout,
+            2.0 * evaluate_payout(&window, &def.paytable, 1.0)
+        );
+
+        assert_eq!(
+            replay_round(
+                &def,
+                "server",
+                "client",
+                nonce,
+                1.0,
+                &[RoundAction::Spin; 4]
+            ),
+            Err(RoundError::Complete(4))
+        );
+    }
+
+    #[test]
+    fn games_without_a_feature_open_complete_rounds() {
+        let def = GameDefinition::default_game();
+        for nonce in 0..50 {
+            assert_eq!(open_round(&def, "server", "client", nonce, 1.0).state, None);
+        }
+        assert_eq!(
+            replay_round(&def, "server", "client", 0, 1.0, &[RoundAction::Spin]),
+            Err(RoundError::Complete(1))
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+/// Number of distinct symbols; valid symbol indices are `0..SYMBOL_COUNT`.
+pub const SYMBOL_COUNT: usize = 5;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Symbol {
     A,
@@ -10,6 +13,10 @@ pub enum Symbol {
 }
 
 impl Symbol {
+    /// Every symbol, in index order.
+    pub const ALL: [Symbol; SYMBOL_COUNT] =
+        [Symbol::A, Symbol::B, Symbol::C, Symbol::D, Symbol::Wild];
+
     pub fn from_index(i: u8) -> Self {
         match i % 5 {
             0 => Symbol::A,
@@ -29,9 +36,24 @@ impl Symbol {
             Symbol::Wild => 4,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Symbol::A => "A",
+            Symbol::B => "B",
+            Symbol::C => "C",
+            Symbol::D => "D",
+            Symbol::Wild => "Wild",
+        }
+    }
+
+    /// Wild substitutes for any symbol on a payline.
+    pub fn is_wild(self) -> bool {
+        self == Symbol::Wild
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ReelsConfig {
     pub reels: Vec<Vec<Symbol>>, // each reel strips
     pub rows: usize,             // visible rows
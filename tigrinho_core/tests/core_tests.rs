@@ -36,5 +36,36 @@ fn rtp_simulation_smoke() {
     }
     let rtp = total_payout / total_bet;
     // very loose bounds since default table is arbitrary
-    assert!(rtp >= 0.0 && rtp <= 10.0);
+    assert!((0.0..=10.0).contains(&rtp));
+}
+
+#[test]
+fn exact_rtp_matches_simulation() {
+    let def = tigrinho_core::GameDefinition::default_game();
+    let exact = tigrinho_core::exact_report(&def).expect("small game is enumerable");
+    assert_eq!(exact.rounds, 9 * 9 * 9);
+    let sim = tigrinho_core::simulated_report(&def, 20_000, "seed");
+    // within 5 standard errors of the exact value
+    assert!((sim.rtp - exact.rtp).abs() < 5.0 * sim.rtp_std_error);
+}
+
+#[test]
+fn exact_rtp_with_free_spins_matches_simulation() {
+    let mut def = tigrinho_core::GameDefinition::default_game();
+    def.free_spins = Some(tigrinho_core::FreeSpins {
+        trigger_symbol: 4,
+        trigger_count: 2,
+        spins: 4,
+        multiplier: 3.0,
+    });
+    let plain =
+        tigrinho_core::exact_report(&tigrinho_core::GameDefinition::default_game()).unwrap();
+    let exact = tigrinho_core::exact_report(&def).expect("small game is enumerable");
+    assert!(exact.rtp > plain.rtp);
+    assert!(exact.hit_frequency >= plain.hit_frequency);
+    let sim = tigrinho_core::simulated_report(&def, 50_000, "seed");
+    assert!((sim.rtp - exact.rtp).abs() < 5.0 * sim.rtp_std_error);
+    // the variance formula, checked against the sample variance
+    assert!((sim.variance - exact.variance).abs() < 0.1 * exact.variance);
+    assert!((sim.hit_frequency - exact.hit_frequency).abs() < 0.01);
 }
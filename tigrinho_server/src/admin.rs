@@ -0,0 +1,170 @@
+use axum::extract::State;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::info;
+
+use tigrinho_core::{FreeSpins, GameDefinition, MathReport, Paytable, PaytableEntry};
+use tigrinho_shared::{
+    AdminParamsPreviewRequest, AdminParamsPreviewResponse, AdminRole, AdminSetParamsRequest,
+    AdminSetParamsResponse, ApiError,
+};
+
+use crate::auth::AdminAuth;
+use crate::configs;
+use crate::error::{ApiJson, AppResult};
+use crate::games::GameSlug;
+use crate::AppState;
+
+/// Largest simulation an admin may request in a preview.
+const MAX_PREVIEW_SIMULATION_ROUNDS: u64 = 10_000_000;
+/// How long a preview can be applied for.
+const PREVIEW_TTL_MINUTES: i64 = 30;
+/// Warn when the computed RTP is further than this from the target.
+const RTP_WARNING_TOLERANCE: f64 = 0.01;
+
+/// Build and validate the definition an admin proposes: the active reels with
+/// the given paytable, target and free spins.
+async fn proposed_definition(
+    state: &AppState,
+    slug: &str,
+    rtp_target: f64,
+    paytable: &[tigrinho_shared::PaytableEntry],
+    free_spins: Option<&tigrinho_shared::FreeSpins>,
+) -> AppResult<GameDefinition> {
+    let (_, mut def) = configs::active_config(state.store.as_ref(), slug).await?;
+    def.rtp_target = rtp_target;
+    def.paytable = Paytable(
+        paytable
+            .iter()
+            .map(|e| PaytableEntry {
+                symbol: e.symbol,
+                count: e.count,
+                payout_multiplier: e.payout_multiplier,
+            })
+            .collect(),
+    );
+    def.free_spins = free_spins.map(|fs| FreeSpins {
+        trigger_symbol: fs.trigger_symbol,
+        trigger_count: fs.trigger_count,
+        spins: fs.spins,
+        multiplier: fs.multiplier,
+    });
+    def.validate()
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+    Ok(def)
+}
+
+/// What a preview token vouches for: this config, previewed for this game.
+fn previewed(slug: &str, config_hash: &str) -> String {
+    format!("{slug}:{config_hash}")
+}
+
+pub(crate) fn to_shared_report(r: MathReport) -> tigrinho_shared::MathReport {
+    tigrinho_shared::MathReport {
+        method: match r.method {
+            tigrinho_core::MathMethod::Exact => tigrinho_shared::MathMethod::Exact,
+            tigrinho_core::MathMethod::Simulated => tigrinho_shared::MathMethod::Simulated,
+        },
+        rounds: r.rounds,
+        rtp: r.rtp,
+        hit_frequency: r.hit_frequency,
+        variance: r.variance,
+        std_dev: r.std_dev,
+        max_multiplier: r.max_multiplier,
+        rtp_std_error: r.rtp_std_error,
+    }
+}
+
+/// Dry run: validate a proposed paytable and report its math without applying it.
+pub async fn route_admin_preview_params(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    GameSlug(slug): GameSlug,
+    ApiJson(req): ApiJson<AdminParamsPreviewRequest>,
+) -> AppResult<ApiJson<AdminParamsPreviewResponse>> {
+    admin.require(AdminRole::Operator)?;
+    if let Some(rounds) = req.simulate_rounds {
+        if rounds == 0 || rounds > MAX_PREVIEW_SIMULATION_ROUNDS {
+            return Err(ApiError::Validation(format!(
+                "simulate_rounds must be between 1 and {MAX_PREVIEW_SIMULATION_ROUNDS}"
+            ))
+            .into());
+        }
+    }
+    let def = proposed_definition(
+        &state,
+        &slug,
+        req.rtp_target,
+        &req.paytable,
+        req.free_spins.as_ref(),
+    )
+    .await?;
+    let preview_hash = def.config_hash();
+    let simulate_rounds = req.simulate_rounds;
+    // enumeration/simulation is CPU bound; keep it off the async workers
+    let report = tokio::task::spawn_blocking(move || match simulate_rounds {
+        Some(rounds) => tigrinho_core::simulated_report(&def, rounds, &def.config_hash()),
+        None => tigrinho_core::math_report(&def),
+    })
+    .await
+    .map_err(anyhow::Error::from)?;
+
+    let mut warnings = Vec::new();
+    if (report.rtp - req.rtp_target).abs() > RTP_WARNING_TOLERANCE {
+        warnings.push(format!(
+            "computed RTP {:.4} differs from rtp_target {:.4}",
+            report.rtp, req.rtp_target
+        ));
+    }
+    if report.rtp > 1.0 {
+        warnings.push("computed RTP exceeds 100%: the game pays out more than it takes".into());
+    }
+    let preview_expires_at = Utc::now() + Duration::minutes(PREVIEW_TTL_MINUTES);
+    let preview_token = state
+        .previews
+        .sign(&previewed(&slug, &preview_hash), preview_expires_at);
+    Ok(ApiJson(AdminParamsPreviewResponse {
+        preview_hash,
+        preview_token,
+        preview_expires_at,
+        rtp_target: req.rtp_target,
+        report: to_shared_report(report),
+        warnings,
+    }))
+}
+
+/// Apply a previewed proposal as a new immutable config version.
+pub async fn route_admin_set_params(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    GameSlug(slug): GameSlug,
+    ApiJson(req): ApiJson<AdminSetParamsRequest>,
+) -> AppResult<ApiJson<AdminSetParamsResponse>> {
+    admin.require(AdminRole::Owner)?;
+    let def = proposed_definition(
+        &state,
+        &slug,
+        req.rtp_target,
+        &req.paytable,
+        req.free_spins.as_ref(),
+    )
+    .await?;
+    let vouched = state.previews.verify(&req.preview_token, Utc::now());
+    if vouched.as_deref() != Some(previewed(&slug, &def.config_hash()).as_str()) {
+        return Err(ApiError::Validation(format!(
+            "preview_token is not a live preview of this configuration for game {slug}; preview it again before applying"
+        ))
+        .into());
+    }
+    let config_hash = state.store.insert_config(&def).await?;
+    let change = admin.change("params.set");
+    if !state
+        .store
+        .set_active_config(&slug, &config_hash, Some(&change))
+        .await?
+    {
+        return Err(ApiError::NotFound(format!("game {slug}")).into());
+    }
+    info!("activated config {config_hash} for game {slug}");
+    Ok(ApiJson(AdminSetParamsResponse { config_hash }))
+}
@@ -0,0 +1,76 @@
+use axum::extract::State;
+use std::sync::Arc;
+use tracing::{error, info};
+
+use tigrinho_shared::{
+    AdminAuditEntry, AdminRole, ApiError, ChainHead, MerkleRoot, ReceiptKey, SpinProof,
+};
+
+use crate::auth::AdminAuth;
+use crate::error::{ApiJson, ApiPath, AppResult};
+use crate::AppState;
+
+/// Listed by `GET /audit/roots`.
+const ROOT_LIST_LIMIT: u32 = 100;
+/// Listed by `GET /admin/audit`.
+const ADMIN_AUDIT_LIMIT: u32 = 100;
+
+/// `GET /audit/chain-head`: the last link of the spin log hash chain, for
+/// operators and players to record outside the server.
+pub async fn route_chain_head(State(state): State<Arc<AppState>>) -> AppResult<ApiJson<ChainHead>> {
+    Ok(ApiJson(state.store.chain_head().await?))
+}
+
+/// `GET /keys`: the public keys spin receipts are signed with.
+pub async fn route_receipt_keys(State(state): State<Arc<AppState>>) -> ApiJson<Vec<ReceiptKey>> {
+    ApiJson(state.receipt_keys.clone())
+}
+
+/// Background task committing a Merkle root over the spins logged since the
+/// previous one, every `merkle_root_every_secs`.
+pub fn spawn_merkle_commits(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(state.merkle_root_every);
+        // the first tick is immediate; let a freshly started server take spins first
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            match tigrinho_store::commit_merkle_root(state.store.as_ref()).await {
+                Ok(Some(root)) => info!(
+                    "committed Merkle root {} over spins {}..={}: {}",
+                    root.id, root.first_spin_id, root.last_spin_id, root.root
+                ),
+                Ok(None) => {}
+                Err(e) => error!("Merkle root commit failed: {e:#}"),
+            }
+        }
+    });
+}
+
+/// Most recent Merkle roots, newest first.
+pub async fn route_merkle_roots(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<ApiJson<Vec<MerkleRoot>>> {
+    Ok(ApiJson(state.store.merkle_roots(ROOT_LIST_LIMIT).await?))
+}
+
+/// Inclusion proof of a spin in its committed batch. Spins logged since the
+/// last commit have none yet.
+pub async fn route_spin_proof(
+    State(state): State<Arc<AppState>>,
+    ApiPath(id): ApiPath<i64>,
+) -> AppResult<ApiJson<SpinProof>> {
+    let proof = tigrinho_store::spin_proof(state.store.as_ref(), id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("committed spin {id}")))?;
+    Ok(ApiJson(proof))
+}
+
+/// `GET /admin/audit`: the most recent admin actions, newest first.
+pub async fn route_admin_audit(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+) -> AppResult<ApiJson<Vec<AdminAuditEntry>>> {
+    admin.require(AdminRole::Owner)?;
+    Ok(ApiJson(state.store.admin_audit(ADMIN_AUDIT_LIMIT).await?))
+}
@@ -0,0 +1,470 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, SaltString};
+use argon2::{Argon2, PasswordVerifier};
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{FromRequestParts, OriginalUri, Request, State},
+    http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::headers::{authorization::Bearer, Authorization};
+use axum_extra::TypedHeader;
+use chrono::{DateTime, Utc};
+use hmac::Mac;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+use tigrinho_core::rng::HmacSha256;
+use tigrinho_shared::signing::{
+    verify_admin_request, ADMIN_KEY_ID_HEADER, ADMIN_NONCE_HEADER, ADMIN_SIGNATURE_HEADER,
+    ADMIN_TIMESTAMP_HEADER,
+};
+use tigrinho_shared::{
+    AdminRole, ApiError, LoginRequest, PlayerInfo, RegisterRequest, SessionResponse,
+    SetClientSeedRequest,
+};
+use tigrinho_store::{AdminChange, AdminKey, NewSession};
+
+use crate::error::{ApiJson, AppError, AppResult};
+use crate::AppState;
+
+/// Longest client seed a player may keep.
+const MAX_CLIENT_SEED_LEN: usize = 64;
+
+// Session tokens look like `<session_id>.<expires_unix>.<hex hmac>`.
+// The HMAC binds the id to its expiry so neither can be altered client-side;
+// the `sessions` row is still consulted on every request so tokens can be revoked.
+
+#[derive(Clone)]
+pub struct SessionSigner {
+    key: Vec<u8>,
+}
+
+impl SessionSigner {
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self { key: key.into() }
+    }
+
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC key");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// A signer under a key derived from this one for `purpose`, so the tokens
+    /// it signs are never accepted as session tokens.
+    pub fn derive(&self, purpose: &str) -> Self {
+        Self::new(self.mac(purpose).finalize().into_bytes().to_vec())
+    }
+
+    pub fn sign(&self, session_id: &str, expires_at: DateTime<Utc>) -> String {
+        let payload = format!("{}.{}", session_id, expires_at.timestamp());
+        let sig = hex::encode(self.mac(&payload).finalize().into_bytes());
+        format!("{}.{}", payload, sig)
+    }
+
+    /// Check the signature and expiry of a token, returning its session id.
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Option<String> {
+        let (payload, sig) = token.rsplit_once('.')?;
+        let (session_id, exp) = payload.split_once('.')?;
+        let sig = hex::decode(sig).ok()?;
+        // verify_slice compares in constant time
+        self.mac(payload).verify_slice(&sig).ok()?;
+        let exp: i64 = exp.parse().ok()?;
+        if now.timestamp() >= exp {
+            return None;
+        }
+        Some(session_id.to_string())
+    }
+}
+
+/// The player behind a valid, unrevoked session token.
+#[derive(Debug, Clone)]
+pub struct AuthPlayer {
+    pub player_id: i64,
+    pub session_id: String,
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AuthPlayer {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        // already resolved by the rate limiter
+        if let Some(player) = parts.extensions.get::<AuthPlayer>() {
+            return Ok(player.clone());
+        }
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| ApiError::Unauthorized)?;
+        authenticate(state, bearer.token()).await
+    }
+}
+
+/// Resolve a raw session token, as sent in the `Authorization` header.
+pub async fn authenticate(state: &AppState, token: &str) -> AppResult<AuthPlayer> {
+    let session_id = state
+        .sessions
+        .verify(token, Utc::now())
+        .ok_or(ApiError::Unauthorized)?;
+    live_session(state, &session_id).await
+}
+
+/// The player of a stored session that is neither revoked nor expired.
+pub async fn live_session(state: &AppState, session_id: &str) -> AppResult<AuthPlayer> {
+    let session = state
+        .store
+        .session(session_id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    if Utc::now() >= session.expires_at {
+        return Err(ApiError::Unauthorized.into());
+    }
+    Ok(AuthPlayer {
+        player_id: session.player_id,
+        session_id: session_id.to_string(),
+    })
+}
+
+/// Actor recorded for requests made with the configured `api_key`.
+pub const CONFIG_ADMIN: &str = "api_key";
+/// Largest body of a signed admin request, which is read whole to check it.
+const MAX_SIGNED_BODY: usize = 2 * 1024 * 1024;
+
+/// Caller presented a valid admin key: the configured `api_key`, which is an
+/// owner, or a live key from the store, sent as a bearer token or used to sign
+/// the request (see [`verify_signed_admin`]).
+#[derive(Debug, Clone)]
+pub struct AdminAuth {
+    /// Name of the key, as recorded in the admin audit trail.
+    pub actor: String,
+    pub role: AdminRole,
+    /// Id of the stored key; `None` for the configured `api_key`.
+    pub key_id: Option<String>,
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(admin) = parts.extensions.get::<AdminAuth>() {
+            return Ok(admin.clone());
+        }
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| ApiError::Unauthorized)?;
+        AdminAuth::check(state, bearer.token()).await
+    }
+}
+
+/// Equality that takes the same time wherever the inputs differ.
+fn digests_match(a: &str, b: &str) -> bool {
+    let (a, b) = (Sha256::digest(a.as_bytes()), Sha256::digest(b.as_bytes()));
+    a.ct_eq(&b).into()
+}
+
+impl AdminAuth {
+    /// Resolve an admin key, `<key_id>.<secret>` for stored keys.
+    pub async fn check(state: &AppState, key: &str) -> AppResult<AdminAuth> {
+        if !state.api_key.is_empty() && digests_match(key, &state.api_key) {
+            return Ok(AdminAuth {
+                actor: CONFIG_ADMIN.into(),
+                role: AdminRole::Owner,
+                key_id: None,
+            });
+        }
+        let (key_id, secret) = key.split_once('.').ok_or(ApiError::Unauthorized)?;
+        let stored = live_admin_key(state, key_id).await?;
+        let hash = tigrinho_store::admin_secret_hash(secret);
+        if !bool::from(hash.as_bytes().ct_eq(stored.secret_hash.as_bytes())) {
+            return Err(ApiError::Unauthorized.into());
+        }
+        Ok(AdminAuth {
+            actor: stored.name,
+            role: stored.role,
+            key_id: Some(stored.key_id),
+        })
+    }
+
+    /// A change by this key, which the store audits as `action`.
+    pub fn change(&self, action: &str) -> AdminChange {
+        AdminChange::new(&self.actor, action)
+    }
+
+    /// Refuse the request unless the key's role includes `role`.
+    pub fn require(&self, role: AdminRole) -> AppResult<()> {
+        if self.role < role {
+            return Err(ApiError::Forbidden(format!(
+                "admin key {} has role {}, this needs {}",
+                self.actor,
+                self.role.as_str(),
+                role.as_str()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// A stored admin key that is neither revoked nor expired.
+pub async fn live_admin_key(state: &AppState, key_id: &str) -> AppResult<AdminKey> {
+    let key = state
+        .store
+        .admin_key(key_id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    if key.revoked_at.is_some() || key.expires_at.is_some_and(|at| Utc::now() >= at) {
+        return Err(ApiError::Unauthorized.into());
+    }
+    Ok(key)
+}
+
+/// Middleware: authenticate admin requests signed with
+/// [`tigrinho_shared::AdminSignature`] and hand the key to [`AdminAuth`].
+/// Requests without a signature pass through untouched.
+pub async fn verify_signed_admin(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if !req.headers().contains_key(ADMIN_SIGNATURE_HEADER) {
+        return next.run(req).await;
+    }
+    match check_signed_admin(&state, req).await {
+        Ok(req) => next.run(req).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn check_signed_admin(state: &AppState, req: Request) -> AppResult<Request> {
+    // read in a block: a borrow of the request must not live across an await
+    let (key_id, nonce, signature, timestamp, method, path) = {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+                .ok_or(ApiError::Unauthorized)
+        };
+        let timestamp: i64 = header(ADMIN_TIMESTAMP_HEADER)?
+            .parse()
+            .map_err(|_| ApiError::Unauthorized)?;
+        // signed as the client addressed it, before any nesting stripped a prefix
+        let uri = req
+            .extensions()
+            .get::<OriginalUri>()
+            .map_or(req.uri(), |original| &original.0);
+        (
+            header(ADMIN_KEY_ID_HEADER)?,
+            header(ADMIN_NONCE_HEADER)?,
+            header(ADMIN_SIGNATURE_HEADER)?,
+            timestamp,
+            req.method().as_str().to_string(),
+            uri.path_and_query().map_or("/", |p| p.as_str()).to_string(),
+        )
+    };
+    let signed_at = DateTime::from_timestamp(timestamp, 0).ok_or(ApiError::Unauthorized)?;
+    // stale or from the future: either way outside the window
+    if (Utc::now() - signed_at).abs() > state.admin_signature_window {
+        return Err(ApiError::Unauthorized.into());
+    }
+
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY)
+        .await
+        .map_err(|_| ApiError::Invalid("request body is too large".into()))?;
+    let key = live_admin_key(state, &key_id).await?;
+    let signing_key =
+        tigrinho_store::open_signing_key(&state.seeds, &key)?.ok_or(ApiError::Unauthorized)?;
+    if !verify_admin_request(
+        &signing_key,
+        &method,
+        &path,
+        timestamp,
+        &nonce,
+        &body,
+        &signature,
+    ) {
+        return Err(ApiError::Unauthorized.into());
+    }
+    // claimed only once verified, so forgeries cannot fill the table
+    if !state
+        .store
+        .claim_admin_signature(
+            &key_id,
+            &signature,
+            signed_at + state.admin_signature_window,
+        )
+        .await?
+    {
+        return Err(ApiError::Unauthorized.into());
+    }
+
+    let mut req = Request::from_parts(parts, Body::from(body));
+    req.extensions_mut().insert(AdminAuth {
+        actor: key.name,
+        role: key.role,
+        key_id: Some(key.key_id),
+    });
+    Ok(req)
+}
+
+fn hash_password(password: &str) -> anyhow::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("hash password: {e}"))?;
+    Ok(hash.to_string())
+}
+
+fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+fn new_session_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub async fn route_register(
+    State(state): State<Arc<AppState>>,
+    ApiJson(req): ApiJson<RegisterRequest>,
+) -> AppResult<(StatusCode, ApiJson<PlayerInfo>)> {
+    let username = req.username.trim();
+    if username.is_empty() {
+        return Err(ApiError::Validation("username must not be empty".into()).into());
+    }
+    if req.password.len() < 8 {
+        return Err(ApiError::Validation("password must be at least 8 characters".into()).into());
+    }
+    let password_hash = hash_password(&req.password)?;
+    let player = state
+        .store
+        .create_player(username, &password_hash)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("username already taken".into()))?;
+    Ok((StatusCode::CREATED, ApiJson(player)))
+}
+
+pub async fn route_login(
+    State(state): State<Arc<AppState>>,
+    ApiJson(req): ApiJson<LoginRequest>,
+) -> AppResult<ApiJson<SessionResponse>> {
+    let (player_id, password_hash) = state
+        .store
+        .player_credentials(req.username.trim())
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    if !verify_password(&req.password, &password_hash) {
+        return Err(ApiError::Unauthorized.into());
+    }
+    let issued_at = Utc::now();
+    let session = NewSession {
+        id: new_session_id(),
+        player_id,
+        issued_at,
+        expires_at: issued_at + state.session_ttl,
+    };
+    state.store.create_session(&session).await?;
+    Ok(ApiJson(SessionResponse {
+        token: state.sessions.sign(&session.id, session.expires_at),
+        expires_at: session.expires_at,
+    }))
+}
+
+/// Revoke the session used to make this request.
+pub async fn route_logout(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+) -> AppResult<StatusCode> {
+    state.store.revoke_session(&player.session_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revoke every live session of the player, including the current one.
+pub async fn route_revoke_sessions(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+) -> AppResult<StatusCode> {
+    state.store.revoke_player_sessions(player.player_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn route_me(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+) -> AppResult<ApiJson<PlayerInfo>> {
+    let info = state
+        .store
+        .player(player.player_id)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    Ok(ApiJson(info))
+}
+
+/// `POST /me/client-seed`: the client seed the player's spins use from now on
+/// when they name none. Spins already played keep the seed they were drawn with.
+pub async fn route_set_client_seed(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+    ApiJson(req): ApiJson<SetClientSeedRequest>,
+) -> AppResult<ApiJson<PlayerInfo>> {
+    let client_seed = req.client_seed.trim();
+    if client_seed.is_empty() || client_seed.len() > MAX_CLIENT_SEED_LEN {
+        return Err(ApiError::Validation(format!(
+            "client_seed must be between 1 and {MAX_CLIENT_SEED_LEN} characters"
+        ))
+        .into());
+    }
+    let info = state
+        .store
+        .set_client_seed(player.player_id, client_seed)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+    Ok(ApiJson(info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn token_roundtrip_and_expiry() {
+        let signer = SessionSigner::new("secret");
+        let now = Utc::now();
+        let token = signer.sign("abc", now + Duration::minutes(5));
+        assert_eq!(signer.verify(&token, now).as_deref(), Some("abc"));
+        assert_eq!(signer.verify(&token, now + Duration::minutes(6)), None);
+    }
+
+    #[test]
+    fn token_tampering_rejected() {
+        let signer = SessionSigner::new("secret");
+        let now = Utc::now();
+        let token = signer.sign("abc", now + Duration::minutes(5));
+        let forged = token.replacen("abc", "abd", 1);
+        assert_eq!(signer.verify(&forged, now), None);
+        let other = SessionSigner::new("other");
+        assert_eq!(other.verify(&token, now), None);
+    }
+}
@@ -0,0 +1,541 @@
+use anyhow::{bail, Context};
+use clap::Parser;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use tigrinho_core::ReceiptSigner;
+use tigrinho_store::{GameStore, SeedVault};
+
+use crate::monitor::MonitorPolicy;
+use crate::ratelimit::{BucketConfig, RateLimits};
+
+/// Development credentials; only accepted with `--dev`.
+pub const DEV_API_KEY: &str = "dev-key";
+pub const DEV_SESSION_SECRET: &str = "dev-session-secret";
+/// SHA-256 of `dev-receipt-key`.
+pub const DEV_RECEIPT_SIGNING_KEY: &str =
+    "e47010292594e86fa8f831489d2c59506f83b34b9fed78ef032cd1b9534dd9ae";
+/// `dev:` and the SHA-256 of `dev-seed-kek`.
+pub const DEV_SEED_KEK: &str =
+    "dev:c09395a2974dbc4ea08899fc7785b75cef2a3c9a112541e89eed3c0b2c4cfcd0";
+pub use tigrinho_store::DEV_SERVER_SEED;
+
+const DEFAULT_CONFIG_FILE: &str = "tigrinho.toml";
+const MIN_SECRET_LEN: usize = 16;
+
+#[derive(Debug, Parser)]
+#[command(about = "Tigrinho HTTP server")]
+pub struct Args {
+    /// TOML settings file; `tigrinho.toml` is read when present
+    #[arg(long, env = "TIGRINHO_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Allow the development API key, session secret, signing keys and server seed
+    #[arg(long)]
+    pub dev: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("expected text or json"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSettings {
+    pub format: LogFormat,
+    /// `tracing` env-filter directive, e.g. `info,tigrinho_server=debug`.
+    pub filter: String,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            filter: "info".into(),
+        }
+    }
+}
+
+/// Token buckets for `/spin` as `"<rate>:<burst>"` or `"off"`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitSettings {
+    pub player: String,
+    pub ip: String,
+    pub global: String,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            player: "5:10".into(),
+            ip: "20:40".into(),
+            global: "500:1000".into(),
+        }
+    }
+}
+
+impl RateLimitSettings {
+    pub fn limits(&self) -> anyhow::Result<RateLimits> {
+        let parse = |name: &str, v: &str| {
+            BucketConfig::parse(v).with_context(|| {
+                format!("rate_limits.{name} = {v:?}: expected <rate>:<burst> or off")
+            })
+        };
+        Ok(RateLimits {
+            per_player: parse("player", &self.player)?,
+            per_ip: parse("ip", &self.ip)?,
+            global: parse("global", &self.global)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeedRotationSettings {
+    pub every_spins: Option<i64>,
+    pub every_hours: Option<f64>,
+}
+
+/// Everything the server reads at startup: a TOML file, overridden by
+/// environment variables, overridden by `--dev`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: String,
+    /// `sqlite:` or `postgres:` URL; the scheme selects the store backend.
+    pub database_url: String,
+    pub pool_size: u32,
+    /// Bearer key acting as an owner on the admin endpoints, alongside the
+    /// keys made with `tigrinho_cli create-admin-key`. Optional; leave it
+    /// unset once stored keys are in place.
+    pub api_key: String,
+    /// HMAC key of player session tokens. Required outside `--dev`.
+    pub session_secret: String,
+    pub session_ttl_secs: i64,
+    pub idempotency_ttl_secs: i64,
+    /// How far the timestamp of a signed admin request may be from the
+    /// server's clock; a signature is accepted once within it.
+    pub admin_signature_window_secs: i64,
+    /// Ed25519 secret key (32 bytes of hex) signing spin receipts. Required
+    /// outside `--dev`; `tigrinho_cli new-receipt-key` makes one.
+    pub receipt_signing_key: String,
+    /// Public keys (hex) of earlier signing keys, still published at `GET /keys`.
+    pub retired_receipt_keys: Vec<String>,
+    /// Keyring sealing the server seeds at rest: `<id>:<64 hex digits>` entries
+    /// separated by commas or newlines, the active key first. Required outside
+    /// `--dev`; `tigrinho_cli new-seed-kek` makes a key.
+    pub seed_kek: String,
+    /// File to read `seed_kek` from instead.
+    pub seed_kek_file: Option<PathBuf>,
+    /// Origins allowed to call the API from a browser; `"*"` allows any.
+    pub cors_origins: Vec<String>,
+    pub big_win_multiplier: f64,
+    /// How often a Merkle root is committed over the spins logged since the last one.
+    pub merkle_root_every_secs: u64,
+    pub log: LogSettings,
+    pub rate_limits: RateLimitSettings,
+    pub seed_rotation: SeedRotationSettings,
+    pub rtp_monitor: MonitorPolicy,
+    #[serde(skip)]
+    pub dev: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:8080".into(),
+            database_url: "sqlite://tigrinho.db".into(),
+            pool_size: 5,
+            api_key: String::new(),
+            session_secret: String::new(),
+            session_ttl_secs: 24 * 60 * 60,
+            idempotency_ttl_secs: 24 * 60 * 60,
+            admin_signature_window_secs: 5 * 60,
+            receipt_signing_key: String::new(),
+            retired_receipt_keys: Vec::new(),
+            seed_kek: String::new(),
+            seed_kek_file: None,
+            cors_origins: Vec::new(),
+            big_win_multiplier: 10.0,
+            merkle_root_every_secs: 60 * 60,
+            log: LogSettings::default(),
+            rate_limits: RateLimitSettings::default(),
+            seed_rotation: SeedRotationSettings::default(),
+            rtp_monitor: MonitorPolicy::default(),
+            dev: false,
+        }
+    }
+}
+
+/// Parse an override, naming the variable when it is malformed.
+fn parse_var<T: FromStr>(name: &str, value: &str) -> anyhow::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{name}={value:?}: {e}"))
+}
+
+impl ServerConfig {
+    pub fn load(args: &Args) -> anyhow::Result<Self> {
+        let path = match &args.config {
+            Some(path) => Some(path.clone()),
+            None => Some(PathBuf::from(DEFAULT_CONFIG_FILE)).filter(|p| p.exists()),
+        };
+        let mut config = match path {
+            Some(path) => {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("reading {}", path.display()))?;
+                toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))?
+            }
+            None => ServerConfig::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        config.dev = args.dev;
+        config.finish()?;
+        Ok(config)
+    }
+
+    /// Override settings from environment variables (looked up through `var`).
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> anyhow::Result<()> {
+        if let Some(v) = var("BIND") {
+            self.bind = v;
+        }
+        if let Some(v) = var("DATABASE_URL") {
+            self.database_url = v;
+        }
+        if let Some(v) = var("DB_POOL_SIZE") {
+            self.pool_size = parse_var("DB_POOL_SIZE", &v)?;
+        }
+        if let Some(v) = var("API_KEY") {
+            self.api_key = v;
+        }
+        if let Some(v) = var("SESSION_SECRET") {
+            self.session_secret = v;
+        }
+        if let Some(v) = var("SESSION_TTL_SECS") {
+            self.session_ttl_secs = parse_var("SESSION_TTL_SECS", &v)?;
+        }
+        if let Some(v) = var("IDEMPOTENCY_TTL_SECS") {
+            self.idempotency_ttl_secs = parse_var("IDEMPOTENCY_TTL_SECS", &v)?;
+        }
+        if let Some(v) = var("ADMIN_SIGNATURE_WINDOW_SECS") {
+            self.admin_signature_window_secs = parse_var("ADMIN_SIGNATURE_WINDOW_SECS", &v)?;
+        }
+        if let Some(v) = var("RECEIPT_SIGNING_KEY") {
+            self.receipt_signing_key = v;
+        }
+        if let Some(v) = var("SEED_KEK") {
+            self.seed_kek = v;
+        }
+        if let Some(v) = var("SEED_KEK_FILE") {
+            self.seed_kek_file = Some(v.into());
+        }
+        if let Some(v) = var("CORS_ORIGINS") {
+            self.cors_origins = v
+                .split(',')
+                .map(str::trim)
+                .filter(|o| !o.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(v) = var("BIG_WIN_MULTIPLIER") {
+            self.big_win_multiplier = parse_var("BIG_WIN_MULTIPLIER", &v)?;
+        }
+        if let Some(v) = var("MERKLE_ROOT_EVERY_SECS") {
+            self.merkle_root_every_secs = parse_var("MERKLE_ROOT_EVERY_SECS", &v)?;
+        }
+        if let Some(v) = var("LOG_FORMAT") {
+            self.log.format = parse_var("LOG_FORMAT", &v)?;
+        }
+        if let Some(v) = var("LOG_FILTER") {
+            self.log.filter = v;
+        }
+        if let Some(v) = var("RATE_LIMIT_PLAYER") {
+            self.rate_limits.player = v;
+        }
+        if let Some(v) = var("RATE_LIMIT_IP") {
+            self.rate_limits.ip = v;
+        }
+        if let Some(v) = var("RATE_LIMIT_GLOBAL") {
+            self.rate_limits.global = v;
+        }
+        if let Some(v) = var("SEED_ROTATE_EVERY_SPINS") {
+            self.seed_rotation.every_spins = Some(parse_var("SEED_ROTATE_EVERY_SPINS", &v)?);
+        }
+        if let Some(v) = var("SEED_ROTATE_EVERY_HOURS") {
+            self.seed_rotation.every_hours = Some(parse_var("SEED_ROTATE_EVERY_HOURS", &v)?);
+        }
+        if let Some(v) = var("RTP_ALERT_Z") {
+            self.rtp_monitor.z_threshold = parse_var("RTP_ALERT_Z", &v)?;
+        }
+        if let Some(v) = var("RTP_ALERT_MIN_SPINS") {
+            self.rtp_monitor.min_spins = parse_var("RTP_ALERT_MIN_SPINS", &v)?;
+        }
+        if let Some(v) = var("RTP_AUTO_SUSPEND") {
+            self.rtp_monitor.auto_suspend = parse_var("RTP_AUTO_SUSPEND", &v)?;
+        }
+        Ok(())
+    }
+
+    /// Fill in development defaults under `--dev`, then reject anything unusable
+    /// or insecure.
+    pub fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(path) = &self.seed_kek_file {
+            if !self.seed_kek.is_empty() {
+                bail!("set seed_kek or seed_kek_file, not both");
+            }
+            self.seed_kek = std::fs::read_to_string(path)
+                .with_context(|| format!("reading seed_kek_file {}", path.display()))?;
+        }
+        if self.dev {
+            if self.api_key.is_empty() {
+                self.api_key = DEV_API_KEY.into();
+            }
+            if self.session_secret.is_empty() {
+                self.session_secret = DEV_SESSION_SECRET.into();
+            }
+            if self.cors_origins.is_empty() {
+                self.cors_origins = vec!["*".into()];
+            }
+            if self.receipt_signing_key.is_empty() {
+                self.receipt_signing_key = DEV_RECEIPT_SIGNING_KEY.into();
+            }
+            if self.seed_kek.is_empty() {
+                self.seed_kek = DEV_SEED_KEK.into();
+            }
+        } else {
+            if self.api_key == DEV_API_KEY {
+                bail!("set api_key (or API_KEY) to a real secret, leave it unset, or start with --dev");
+            }
+            if self.session_secret.is_empty() || self.session_secret == DEV_SESSION_SECRET {
+                bail!(
+                    "set session_secret (or SESSION_SECRET) to a real secret, or start with --dev"
+                );
+            }
+            if (!self.api_key.is_empty() && self.api_key.len() < MIN_SECRET_LEN)
+                || self.session_secret.len() < MIN_SECRET_LEN
+            {
+                bail!("api_key and session_secret must be at least {MIN_SECRET_LEN} characters");
+            }
+            if self.receipt_signing_key.is_empty()
+                || self.receipt_signing_key == DEV_RECEIPT_SIGNING_KEY
+            {
+                bail!(
+                    "set receipt_signing_key (or RECEIPT_SIGNING_KEY), e.g. from `tigrinho_cli new-receipt-key`, or start with --dev"
+                );
+            }
+            let (_, dev_key) = DEV_SEED_KEK.split_once(':').expect("id:key");
+            if self.seed_kek.is_empty() || self.seed_kek.contains(dev_key) {
+                bail!(
+                    "set seed_kek (or SEED_KEK, or a seed_kek_file), e.g. from `tigrinho_cli new-seed-kek`, or start with --dev"
+                );
+            }
+        }
+        SeedVault::parse(&self.seed_kek).context("seed_kek")?;
+        ReceiptSigner::from_hex(&self.receipt_signing_key).context("receipt_signing_key")?;
+        for key in &self.retired_receipt_keys {
+            verify_receipt_key(key).with_context(|| format!("retired_receipt_keys: {key:?}"))?;
+        }
+        if self.pool_size == 0 {
+            bail!("pool_size must be at least 1");
+        }
+        if self.session_ttl_secs <= 0
+            || self.idempotency_ttl_secs <= 0
+            || self.admin_signature_window_secs <= 0
+        {
+            bail!("session_ttl_secs, idempotency_ttl_secs and admin_signature_window_secs must be positive");
+        }
+        if !(self.big_win_multiplier.is_finite() && self.big_win_multiplier > 0.0) {
+            bail!("big_win_multiplier must be positive");
+        }
+        if self.merkle_root_every_secs == 0 {
+            bail!("merkle_root_every_secs must be positive");
+        }
+        if self.seed_rotation.every_spins.is_some_and(|n| n <= 0)
+            || self
+                .seed_rotation
+                .every_hours
+                .is_some_and(|h| !(h.is_finite() && h > 0.0))
+        {
+            bail!("seed_rotation limits must be positive when set");
+        }
+        if !(self.rtp_monitor.z_threshold.is_finite() && self.rtp_monitor.z_threshold > 0.0) {
+            bail!("rtp_monitor.z_threshold must be positive");
+        }
+        for origin in &self.cors_origins {
+            if origin != "*" {
+                http::HeaderValue::from_str(origin)
+                    .with_context(|| format!("invalid CORS origin {origin:?}"))?;
+            }
+        }
+        self.rate_limits.limits()?;
+        Ok(())
+    }
+}
+
+/// A public key must be 32 bytes of hex.
+fn verify_receipt_key(key: &str) -> anyhow::Result<()> {
+    match hex::decode(key) {
+        Ok(bytes) if bytes.len() == 32 => Ok(()),
+        _ => bail!("expected 32 bytes of hex"),
+    }
+}
+
+/// Outside `--dev`, refuse to serve games still running the development seed.
+pub async fn refuse_dev_seed(store: &dyn GameStore, dev: bool) -> anyhow::Result<()> {
+    if dev {
+        return Ok(());
+    }
+    let games = store.list_games().await?;
+    let dev_hash = tigrinho_core::derive_hash_hex(DEV_SERVER_SEED.as_bytes());
+    if let Some(game) = games.iter().find(|g| g.server_seed_hash == dev_hash) {
+        bail!(
+            "game {} still uses the development seed; run `tigrinho_cli rotate-seed --game {}` or start with --dev",
+            game.slug,
+            game.slug
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn file_then_env_overrides() {
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            bind = "0.0.0.0:9000"
+            pool_size = 8
+            api_key = "file-key-0123456789"
+            session_secret = "file-secret-0123456789"
+            receipt_signing_key = "1111111111111111111111111111111111111111111111111111111111111111"
+            seed_kek = "k1:2222222222222222222222222222222222222222222222222222222222222222"
+            cors_origins = ["https://example.com"]
+
+            [rate_limits]
+            player = "off"
+
+            [seed_rotation]
+            every_spins = 5000
+            "#,
+        )
+        .unwrap();
+        config
+            .apply_env(env(&[("DB_POOL_SIZE", "12"), ("LOG_FORMAT", "json")]))
+            .unwrap();
+        config.finish().unwrap();
+        assert_eq!(config.bind, "0.0.0.0:9000");
+        assert_eq!(config.pool_size, 12);
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert_eq!(config.seed_rotation.every_spins, Some(5000));
+        assert_eq!(config.rate_limits.limits().unwrap().per_player, None);
+    }
+
+    #[test]
+    fn dev_credentials_need_dev_mode() {
+        let mut config = ServerConfig::default();
+        assert!(config.finish().is_err());
+
+        let mut config = ServerConfig::default();
+        config
+            .apply_env(env(&[
+                ("API_KEY", DEV_API_KEY),
+                ("SESSION_SECRET", "a-long-enough-secret"),
+            ]))
+            .unwrap();
+        assert!(config.finish().is_err());
+
+        let mut config = ServerConfig::default();
+        config
+            .apply_env(env(&[
+                ("API_KEY", "a-long-enough-api-key"),
+                ("SESSION_SECRET", "a-long-enough-secret"),
+                ("RECEIPT_SIGNING_KEY", DEV_RECEIPT_SIGNING_KEY),
+            ]))
+            .unwrap();
+        assert!(config.finish().is_err());
+
+        let mut config = ServerConfig::default();
+        config
+            .apply_env(env(&[
+                ("API_KEY", "a-long-enough-api-key"),
+                ("SESSION_SECRET", "a-long-enough-secret"),
+                ("RECEIPT_SIGNING_KEY", &"11".repeat(32)),
+                ("SEED_KEK", DEV_SEED_KEK),
+            ]))
+            .unwrap();
+        assert!(config.finish().is_err());
+
+        // stored admin keys can stand in for the configured one
+        let mut config = ServerConfig::default();
+        config
+            .apply_env(env(&[
+                ("SESSION_SECRET", "a-long-enough-secret"),
+                ("RECEIPT_SIGNING_KEY", &"11".repeat(32)),
+                ("SEED_KEK", &format!("k1:{}", "22".repeat(32))),
+            ]))
+            .unwrap();
+        config.finish().unwrap();
+        assert!(config.api_key.is_empty());
+
+        let mut config = ServerConfig {
+            dev: true,
+            ..Default::default()
+        };
+        config.finish().unwrap();
+        assert_eq!(config.api_key, DEV_API_KEY);
+        assert_eq!(config.seed_kek, DEV_SEED_KEK);
+        assert_eq!(config.cors_origins, vec!["*".to_string()]);
+    }
+
+    #[test]
+    fn malformed_values_are_errors() {
+        let mut config = ServerConfig::default();
+        assert!(config.apply_env(env(&[("DB_POOL_SIZE", "many")])).is_err());
+        assert!(toml::from_str::<ServerConfig>("unknown_setting = 1").is_err());
+
+        let mut config = ServerConfig {
+            dev: true,
+            ..Default::default()
+        };
+        config.rate_limits.ip = "fast".into();
+        assert!(config.finish().is_err());
+
+        let mut config = ServerConfig {
+            dev: true,
+            retired_receipt_keys: vec!["abc".into()],
+            ..Default::default()
+        };
+        assert!(config.finish().is_err());
+    }
+}
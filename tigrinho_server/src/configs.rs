@@ -0,0 +1,66 @@
+use axum::extract::State;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tigrinho_core::GameDefinition;
+use tigrinho_shared::{ApiError, MathReport};
+use tigrinho_store::GameStore;
+
+use crate::admin::to_shared_report;
+use crate::error::{ApiJson, ApiPath, AppResult};
+use crate::AppState;
+
+/// The active configuration of a game and its hash, or a typed 404.
+pub async fn active_config(
+    store: &dyn GameStore,
+    slug: &str,
+) -> AppResult<(String, GameDefinition)> {
+    store
+        .active_config(slug)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("game {slug}")).into())
+}
+
+/// A config that is known to exist (it is referenced by a game or spin).
+pub async fn config_by_hash(store: &dyn GameStore, hash: &str) -> anyhow::Result<GameDefinition> {
+    store
+        .config(hash)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("config {hash} is missing"))
+}
+
+/// Theoretical math per config hash. Configs are immutable, so entries never go stale.
+pub type MathCache = Arc<Mutex<HashMap<String, MathReport>>>;
+
+/// The theoretical report of a config, computed once off the async runtime.
+pub async fn theoretical_report(
+    cache: &MathCache,
+    hash: &str,
+    def: &GameDefinition,
+) -> anyhow::Result<MathReport> {
+    if let Some(report) = cache.lock().unwrap().get(hash) {
+        return Ok(report.clone());
+    }
+    let def = def.clone();
+    let report = to_shared_report(
+        tokio::task::spawn_blocking(move || tigrinho_core::math_report(&def)).await?,
+    );
+    cache
+        .lock()
+        .unwrap()
+        .insert(hash.to_string(), report.clone());
+    Ok(report)
+}
+
+/// Any config version ever made active, so historical spins can be re-verified.
+pub async fn route_get_config(
+    State(state): State<Arc<AppState>>,
+    ApiPath(hash): ApiPath<String>,
+) -> AppResult<ApiJson<GameDefinition>> {
+    let def = state
+        .store
+        .config(&hash)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("config {hash}")))?;
+    Ok(ApiJson(def))
+}
@@ -0,0 +1,151 @@
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        ws::{rejection::WebSocketUpgradeRejection, WebSocketUpgrade},
+        FromRequest, FromRequestParts, Request,
+    },
+    http::{HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use rand::RngCore;
+use tracing::error;
+
+use tigrinho_shared::{ApiError, ApiErrorBody};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Server-side wrapper so `ApiError` (defined in `tigrinho_shared`) can be an axum response.
+#[derive(Debug)]
+pub struct AppError(pub ApiError);
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl From<ApiError> for AppError {
+    fn from(e: ApiError) -> Self {
+        AppError(e)
+    }
+}
+
+// Anything that is not an explicit ApiError is an internal failure: log the
+// cause, but never leak it to the client.
+impl From<anyhow::Error> for AppError {
+    fn from(e: anyhow::Error) -> Self {
+        error!("internal error: {e:#}");
+        AppError(ApiError::Internal)
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError(ApiError::Invalid(rejection.body_text()))
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        AppError(ApiError::Invalid(rejection.body_text()))
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError(ApiError::Invalid(rejection.body_text()))
+    }
+}
+
+impl From<WebSocketUpgradeRejection> for AppError {
+    fn from(rejection: WebSocketUpgradeRejection) -> Self {
+        AppError(ApiError::Invalid(rejection.body_text()))
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.0.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = ApiErrorBody {
+            code: self.0.code(),
+            message: self.0.to_string(),
+            request_id: current_request_id(),
+        };
+        let mut resp = (status, Json(body)).into_response();
+        if let ApiError::RateLimited { retry_after_secs } = self.0 {
+            resp.headers_mut().insert(
+                http::header::RETRY_AFTER,
+                HeaderValue::from(retry_after_secs),
+            );
+        }
+        resp
+    }
+}
+
+/// `Json` extractor whose rejections use the error envelope instead of axum's plain text.
+#[derive(FromRequest)]
+#[from_request(via(Json), rejection(AppError))]
+pub struct ApiJson<T>(pub T);
+
+impl<T: serde::Serialize> IntoResponse for ApiJson<T> {
+    fn into_response(self) -> Response {
+        Json(self.0).into_response()
+    }
+}
+
+/// `Path` extractor with enveloped rejections.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct ApiPath<T>(pub T);
+
+/// `Query` extractor with enveloped rejections.
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct ApiQuery<T>(pub T);
+
+/// `WebSocketUpgrade` extractor with enveloped rejections, for requests that
+/// are not a WebSocket handshake.
+#[derive(FromRequestParts)]
+#[from_request(rejection(AppError))]
+pub struct ApiWs(pub WebSocketUpgrade);
+
+fn current_request_id() -> String {
+    REQUEST_ID
+        .try_with(|id| id.clone())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn new_request_id() -> String {
+    let mut bytes = [0u8; 8];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Middleware: adopt the caller's `x-request-id` (or mint one), expose it to
+/// error responses and echo it back on every response.
+pub async fn request_id(req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 64)
+        .map(str::to_string)
+        .unwrap_or_else(new_request_id);
+    let mut resp = REQUEST_ID.scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        resp.headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    resp
+}
+
+pub async fn route_not_found() -> AppError {
+    AppError(ApiError::NotFound("no such route".into()))
+}
+
+pub async fn route_method_not_allowed() -> AppError {
+    AppError(ApiError::MethodNotAllowed)
+}
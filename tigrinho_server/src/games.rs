@@ -0,0 +1,228 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, RawPathParams, State},
+    http::{request::Parts, StatusCode},
+};
+use std::sync::Arc;
+use tracing::info;
+
+use tigrinho_core::{GameDefinition, Symbol};
+use tigrinho_shared::{
+    AdminRole, ApiError, CreateGameRequest, FreeSpins, GameConfigResponse, GameLimitsRequest,
+    GameSummary, PaytableEntry, SymbolInfo,
+};
+use tigrinho_store::{GameRecord, GameStore, NewGame};
+
+use crate::auth::AdminAuth;
+use crate::configs;
+use crate::error::{ApiJson, AppError, AppResult};
+use crate::AppState;
+
+/// Game served by the unprefixed legacy routes (`/spin`, `/verify`, ...).
+pub use tigrinho_store::DEFAULT_GAME;
+
+const MAX_SLUG_LEN: usize = 32;
+
+/// The game or a typed 404.
+pub async fn require_game(store: &dyn GameStore, slug: &str) -> AppResult<GameRecord> {
+    store
+        .game(slug)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("game {slug}")).into())
+}
+
+fn validate_slug(slug: &str) -> Result<(), ApiError> {
+    let ok = !slug.is_empty()
+        && slug.len() <= MAX_SLUG_LEN
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if ok {
+        Ok(())
+    } else {
+        Err(ApiError::Validation(format!(
+            "slug must be 1 to {MAX_SLUG_LEN} lowercase letters, digits or '-'"
+        )))
+    }
+}
+
+fn validate_limits(min_bet: f64, max_bet: f64) -> Result<(), ApiError> {
+    if !(min_bet.is_finite() && max_bet.is_finite() && min_bet > 0.0 && min_bet <= max_bet) {
+        return Err(ApiError::Validation(
+            "bet limits must satisfy 0 < min_bet <= max_bet".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Slug of the game a request addresses: the `:slug` path segment, or
+/// [`DEFAULT_GAME`] on the legacy routes that have none.
+#[derive(Debug, Clone)]
+pub struct GameSlug(pub String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for GameSlug {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(|e| ApiError::Invalid(e.body_text()))?;
+        let slug = params
+            .iter()
+            .find(|(name, _)| *name == "slug")
+            .map(|(_, value)| value.to_string())
+            .unwrap_or_else(|| DEFAULT_GAME.to_string());
+        Ok(GameSlug(slug))
+    }
+}
+
+pub async fn route_list_games(
+    State(state): State<Arc<AppState>>,
+) -> AppResult<ApiJson<Vec<GameSummary>>> {
+    let games = state.store.list_games().await?;
+    Ok(ApiJson(games.iter().map(GameRecord::summary).collect()))
+}
+
+pub async fn route_game_summary(
+    State(state): State<Arc<AppState>>,
+    GameSlug(slug): GameSlug,
+) -> AppResult<ApiJson<GameSummary>> {
+    Ok(ApiJson(
+        require_game(state.store.as_ref(), &slug).await?.summary(),
+    ))
+}
+
+/// The active rules of a game with their theoretical math.
+pub async fn route_game_config(
+    State(state): State<Arc<AppState>>,
+    GameSlug(slug): GameSlug,
+) -> AppResult<ApiJson<GameConfigResponse>> {
+    let game = require_game(state.store.as_ref(), &slug).await?;
+    let config_hash = game
+        .active_config_hash
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("game {slug} has no active config"))?;
+    let def = configs::config_by_hash(state.store.as_ref(), &config_hash).await?;
+    let math = configs::theoretical_report(&state.math_cache, &config_hash, &def).await?;
+    Ok(ApiJson(GameConfigResponse {
+        slug: game.slug,
+        name: game.name,
+        config_hash,
+        server_seed_hash: game.server_seed_hash,
+        min_bet: game.min_bet,
+        max_bet: game.max_bet,
+        symbols: Symbol::ALL
+            .iter()
+            .map(|s| SymbolInfo {
+                id: s.to_index(),
+                name: s.name().to_string(),
+                wild: s.is_wild(),
+            })
+            .collect(),
+        reels: def
+            .reels
+            .reels
+            .iter()
+            .map(|reel| reel.iter().map(|s| s.to_index()).collect())
+            .collect(),
+        rows: def.reels.rows,
+        paylines: tigrinho_core::paylines(&def.reels)
+            .into_iter()
+            .map(|line| line.into_iter().map(|(r, c)| [r, c]).collect())
+            .collect(),
+        paytable: def
+            .paytable
+            .0
+            .iter()
+            .map(|e| PaytableEntry {
+                symbol: e.symbol,
+                count: e.count,
+                payout_multiplier: e.payout_multiplier,
+            })
+            .collect(),
+        rtp_target: def.rtp_target,
+        free_spins: def.free_spins.as_ref().map(|fs| FreeSpins {
+            trigger_symbol: fs.trigger_symbol,
+            trigger_count: fs.trigger_count,
+            spins: fs.spins,
+            multiplier: fs.multiplier,
+        }),
+        math,
+    }))
+}
+
+/// Create a game running the default definition under a fresh seed.
+pub async fn route_admin_create_game(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    ApiJson(req): ApiJson<CreateGameRequest>,
+) -> AppResult<(StatusCode, ApiJson<GameSummary>)> {
+    admin.require(AdminRole::Owner)?;
+    validate_slug(&req.slug)?;
+    validate_limits(req.min_bet, req.max_bet)?;
+    if req.name.trim().is_empty() {
+        return Err(ApiError::Validation("name must not be empty".into()).into());
+    }
+    let config_hash = state
+        .store
+        .insert_config(&GameDefinition::default_game())
+        .await?;
+    let created = state
+        .store
+        .create_game(
+            &NewGame {
+                slug: req.slug.clone(),
+                name: req.name.trim().to_string(),
+                config_hash,
+                min_bet: req.min_bet,
+                max_bet: req.max_bet,
+            },
+            &state.seeds,
+            &admin.change("games.create"),
+        )
+        .await?;
+    if !created {
+        return Err(ApiError::Conflict(format!("game {} already exists", req.slug)).into());
+    }
+    info!("created game {}", req.slug);
+    let game = require_game(state.store.as_ref(), &req.slug)
+        .await?
+        .summary();
+    Ok((StatusCode::CREATED, ApiJson(game)))
+}
+
+pub async fn route_admin_set_limits(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    GameSlug(slug): GameSlug,
+    ApiJson(req): ApiJson<GameLimitsRequest>,
+) -> AppResult<ApiJson<GameSummary>> {
+    admin.require(AdminRole::Operator)?;
+    validate_limits(req.min_bet, req.max_bet)?;
+    let change = admin.change("games.set_limits");
+    if !state
+        .store
+        .set_limits(&slug, req.min_bet, req.max_bet, &change)
+        .await?
+    {
+        return Err(ApiError::NotFound(format!("game {slug}")).into());
+    }
+    let after = require_game(state.store.as_ref(), &slug).await?.summary();
+    Ok(ApiJson(after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slug_rules() {
+        assert!(validate_slug("tigrinho").is_ok());
+        assert!(validate_slug("fortune-ox-2").is_ok());
+        assert!(validate_slug("").is_err());
+        assert!(validate_slug("Tigrinho").is_err());
+        assert!(validate_slug("a/b").is_err());
+        assert!(validate_slug(&"x".repeat(33)).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use axum::http::HeaderMap;
+use chrono::Duration;
+use serde::{de::DeserializeOwned, Serialize};
+
+use tigrinho_shared::ApiError;
+use tigrinho_store::GameStore;
+
+use crate::error::AppResult;
+
+// Idempotency keys are scoped per player. The first request with a key reserves
+// a row (response NULL), performs the work and stores its response; later
+// requests with the same key and body get that response replayed. The spin
+// itself points the row at the spin in its own transaction, so a request that
+// recorded its spin but never stored the response is answered from the spin.
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const MAX_KEY_LEN: usize = 255;
+
+pub enum Reservation<T> {
+    /// First time this key is seen: do the work, then `complete` it.
+    Fresh,
+    /// Same key and body as an earlier, finished request.
+    Replay(T),
+    /// Same key and body as an earlier request that recorded this spin but
+    /// never stored its response: rebuild it, then `complete` the key.
+    Recorded(i64),
+}
+
+/// Read the `Idempotency-Key` header, if the client sent one.
+pub fn key_from_headers(headers: &HeaderMap) -> AppResult<Option<String>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| ApiError::Invalid("Idempotency-Key must be visible ASCII".into()))?
+        .trim();
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(ApiError::Invalid(format!(
+            "Idempotency-Key must be 1 to {MAX_KEY_LEN} characters"
+        ))
+        .into());
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Hash of the request body that a key is bound to.
+pub fn request_hash<R: Serialize>(req: &R) -> String {
+    let body = serde_json::to_vec(req).expect("serializable request");
+    tigrinho_core::derive_hash_hex(&body)
+}
+
+/// Reserve `key` for this request or find the stored outcome of an earlier one.
+///
+/// Fails with a conflict while the original request is still in flight or when
+/// the key was already used with a different body.
+pub async fn reserve<T: DeserializeOwned>(
+    store: &dyn GameStore,
+    player_id: i64,
+    key: &str,
+    request_hash: &str,
+    ttl: Duration,
+) -> AppResult<Reservation<T>> {
+    let Some(earlier) = store
+        .claim_idempotency_key(player_id, key, request_hash, ttl)
+        .await?
+    else {
+        return Ok(Reservation::Fresh);
+    };
+    if earlier.request_hash != request_hash {
+        return Err(ApiError::Conflict(
+            "Idempotency-Key was already used with a different request body".into(),
+        )
+        .into());
+    }
+    match (earlier.response_json, earlier.spin_id) {
+        (Some(json), _) => Ok(Reservation::Replay(
+            serde_json::from_str(&json).map_err(anyhow::Error::from)?,
+        )),
+        (None, Some(spin_id)) => Ok(Reservation::Recorded(spin_id)),
+        (None, None) => Err(ApiError::Conflict(
+            "a request with this Idempotency-Key is still in progress".into(),
+        )
+        .into()),
+    }
+}
+
+/// Store the response of a reserved request so duplicates can replay it.
+pub async fn complete<T: Serialize>(
+    store: &dyn GameStore,
+    player_id: i64,
+    key: &str,
+    response: &T,
+) -> anyhow::Result<()> {
+    let json = serde_json::to_string(response)?;
+    store.complete_idempotency_key(player_id, key, &json).await
+}
+
+/// Drop a reservation whose request failed before recording a spin, so the
+/// client can retry with it.
+pub async fn release(store: &dyn GameStore, player_id: i64, key: &str) -> anyhow::Result<()> {
+    store.release_idempotency_key(player_id, key).await
+}
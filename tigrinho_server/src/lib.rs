@@ -0,0 +1,426 @@
+use axum::http::{HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
+use axum::{
+    extract::State,
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use tigrinho_core::{FeatureState, Receipt, ReceiptSigner, Symbol};
+use tigrinho_shared::{
+    ApiError, ReceiptKey, SpinLogEntry, SpinRequest, SpinResponse, VerifyResponse,
+};
+use tigrinho_store::{GameStore, NewSpin, SeedVault};
+
+mod admin;
+mod audit;
+mod auth;
+pub mod config;
+mod configs;
+mod error;
+mod games;
+mod idempotency;
+mod live;
+mod metrics;
+mod monitor;
+mod ratelimit;
+mod rounds;
+mod seeds;
+mod spins;
+
+use auth::{AuthPlayer, SessionSigner};
+use config::ServerConfig;
+use error::{ApiJson, AppResult};
+use games::GameSlug;
+use idempotency::Reservation;
+
+/// Everything the handlers share: the store plus the policies and in-process
+/// services built from a [`ServerConfig`].
+#[derive(Clone)]
+pub struct AppState {
+    store: Arc<dyn GameStore>,
+    api_key: String,
+    sessions: SessionSigner,
+    /// Signs the tokens `POST /admin/params/preview` issues.
+    previews: SessionSigner,
+    session_ttl: chrono::Duration,
+    idempotency_ttl: chrono::Duration,
+    admin_signature_window: chrono::Duration,
+    receipts: ReceiptSigner,
+    /// Published at `GET /keys`: the signing key, then the retired ones.
+    receipt_keys: Vec<ReceiptKey>,
+    /// Opens the server seeds; nothing else in the server sees them.
+    seeds: SeedVault,
+    rotation: seeds::RotationPolicy,
+    merkle_root_every: std::time::Duration,
+    math_cache: configs::MathCache,
+    metrics: Arc<metrics::Metrics>,
+    monitor: Arc<monitor::RtpMonitor>,
+    rate_limiter: Arc<ratelimit::RateLimiter>,
+    live: Arc<live::LiveHub>,
+}
+
+impl AppState {
+    pub fn new(cfg: &ServerConfig, store: Arc<dyn GameStore>) -> anyhow::Result<Self> {
+        let receipts = ReceiptSigner::from_hex(&cfg.receipt_signing_key)?;
+        let receipt_keys = std::iter::once((receipts.public_key(), true))
+            .chain(cfg.retired_receipt_keys.iter().map(|k| (k.as_str(), false)))
+            .map(|(public_key, active)| ReceiptKey {
+                key_id: tigrinho_core::key_id(public_key),
+                algorithm: tigrinho_core::RECEIPT_ALGORITHM.into(),
+                public_key: public_key.to_string(),
+                active,
+            })
+            .collect();
+        let sessions = SessionSigner::new(cfg.session_secret.clone());
+        Ok(Self {
+            store,
+            api_key: cfg.api_key.clone(),
+            previews: sessions.derive("tigrinho-params-preview-v1"),
+            sessions,
+            session_ttl: chrono::Duration::seconds(cfg.session_ttl_secs),
+            idempotency_ttl: chrono::Duration::seconds(cfg.idempotency_ttl_secs),
+            admin_signature_window: chrono::Duration::seconds(cfg.admin_signature_window_secs),
+            receipts,
+            receipt_keys,
+            seeds: SeedVault::parse(&cfg.seed_kek)?,
+            rotation: seeds::RotationPolicy::from_config(&cfg.seed_rotation),
+            merkle_root_every: std::time::Duration::from_secs(cfg.merkle_root_every_secs),
+            math_cache: Default::default(),
+            metrics: Default::default(),
+            monitor: Arc::new(monitor::RtpMonitor::new(cfg.rtp_monitor.clone())),
+            rate_limiter: Arc::new(ratelimit::RateLimiter::new(cfg.rate_limits.limits()?)),
+            live: Arc::new(live::LiveHub::new(cfg.big_win_multiplier)),
+        })
+    }
+}
+
+// DB schema is defined in migrations (see tigrinho_store/migrations and migrations_pg)
+
+async fn route_verify(
+    State(state): State<Arc<AppState>>,
+    GameSlug(slug): GameSlug,
+) -> AppResult<ApiJson<VerifyResponse>> {
+    let game = games::require_game(state.store.as_ref(), &slug).await?;
+    Ok(ApiJson(VerifyResponse {
+        server_seed_hash: game.server_seed_hash,
+        config_hash: game.active_config_hash.unwrap_or_default(),
+    }))
+}
+
+async fn route_spin(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+    GameSlug(slug): GameSlug,
+    headers: HeaderMap,
+    ApiJson(req): ApiJson<SpinRequest>,
+) -> AppResult<Response> {
+    if req.bet <= 0.0 || !req.bet.is_finite() {
+        return Err(ApiError::Validation("bet must be a positive number".into()).into());
+    }
+    if req.lines == 0 {
+        return Err(ApiError::Validation("lines must be at least 1".into()).into());
+    }
+    let Some(key) = idempotency::key_from_headers(&headers)? else {
+        check_bet(&state, &slug, &req).await?;
+        let resp = perform_spin(&state, &player, &slug, &req, None).await?;
+        return Ok(ApiJson(resp).into_response());
+    };
+
+    // the key is bound to the game as well as the body
+    let request_hash = idempotency::request_hash(&(&slug, &req));
+    match idempotency::reserve::<SpinResponse>(
+        state.store.as_ref(),
+        player.player_id,
+        &key,
+        &request_hash,
+        state.idempotency_ttl,
+    )
+    .await?
+    {
+        Reservation::Replay(resp) => {
+            Ok(([(idempotency::REPLAYED_HEADER, "true")], ApiJson(resp)).into_response())
+        }
+        Reservation::Recorded(spin_id) => {
+            let resp = recorded_spin_response(&state, spin_id).await?;
+            idempotency::complete(state.store.as_ref(), player.player_id, &key, &resp).await?;
+            Ok(([(idempotency::REPLAYED_HEADER, "true")], ApiJson(resp)).into_response())
+        }
+        Reservation::Fresh => {
+            let spun = async {
+                check_bet(&state, &slug, &req).await?;
+                perform_spin(&state, &player, &slug, &req, Some(&key)).await
+            };
+            match spun.await {
+                Ok(resp) => {
+                    idempotency::complete(state.store.as_ref(), player.player_id, &key, &resp)
+                        .await?;
+                    Ok(ApiJson(resp).into_response())
+                }
+                Err(e) => {
+                    let _ =
+                        idempotency::release(state.store.as_ref(), player.player_id, &key).await;
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
+/// Refuse a bet the game does not take right now. Only new spins are checked:
+/// a replayed one was accepted when it was made, whatever the game says since.
+async fn check_bet(state: &AppState, slug: &str, req: &SpinRequest) -> AppResult<()> {
+    let game = games::require_game(state.store.as_ref(), slug).await?;
+    if game.suspended_at.is_some() {
+        return Err(ApiError::GameSuspended(slug.to_string()).into());
+    }
+    if req.bet < game.min_bet || req.bet > game.max_bet {
+        return Err(ApiError::Validation(format!(
+            "bet must be between {} and {} for game {slug}",
+            game.min_bet, game.max_bet
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+async fn perform_spin(
+    state: &Arc<AppState>,
+    player: &AuthPlayer,
+    slug: &str,
+    req: &SpinRequest,
+    idempotency_key: Option<&str>,
+) -> AppResult<SpinResponse> {
+    let client_seed = match &req.client_seed {
+        Some(seed) => seed.clone(),
+        None => state
+            .store
+            .player(player.player_id)
+            .await?
+            .and_then(|p| p.client_seed)
+            .ok_or_else(|| {
+                ApiError::Validation(
+                    "client_seed is required until one is set with POST /me/client-seed".into(),
+                )
+            })?,
+    };
+    let p = state
+        .store
+        .reserve_nonce(slug)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("game {slug}")))?;
+    let config_hash = p
+        .active_config_hash
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("game {slug} has no active config"))?;
+    let def = configs::config_by_hash(state.store.as_ref(), &config_hash).await?;
+    let server_seed = state.seeds.open(slug, &p.sealed_seed)?;
+    let step = tigrinho_core::open_round(&def, &server_seed, &client_seed, p.nonce as u64, req.bet);
+
+    // log spin; a triggered feature keeps the round open in a game session
+    let session = step
+        .state
+        .map(|feature| rounds::session_step(0, None, Some(feature)))
+        .transpose()?;
+    let entry = state
+        .store
+        .record_spin(&NewSpin {
+            ts: chrono::Utc::now(),
+            game: slug.to_string(),
+            player_id: player.player_id,
+            client_seed,
+            nonce: p.nonce,
+            server_seed_hash: p.server_seed_hash.clone(),
+            config_hash: config_hash.clone(),
+            result_reels: rounds::reel_indices(&step.reel_window),
+            bet: req.bet,
+            payout: step.payout,
+            round_id: None,
+            settle: step.state.is_none(),
+            session,
+            idempotency_key: idempotency_key.map(str::to_string),
+        })
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("a spin opening a round is always recorded"))?;
+
+    state.live.publish_spin(&entry, req.bet);
+    state.metrics.record_spin(slug, req.bet, step.payout);
+    // a feature round is observed once it settles
+    if step.state.is_none() {
+        monitor::after_spin(state, slug, &config_hash, &def, req.bet, step.payout);
+    }
+    seeds::after_spin(state, slug, &p.server_seed_hash, p.nonce);
+    Ok(spin_response(state, &entry, step.state))
+}
+
+/// The response of a spin that opened its round, rebuilt from the spin log.
+async fn recorded_spin_response(state: &AppState, spin_id: i64) -> AppResult<SpinResponse> {
+    let entry = state
+        .store
+        .spin(spin_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("spin {spin_id} is missing from the log"))?;
+    let config_hash = entry.config_hash.as_deref().unwrap_or_default();
+    let def = configs::config_by_hash(state.store.as_ref(), config_hash).await?;
+    let window: Vec<Vec<Symbol>> = entry
+        .result_reels
+        .iter()
+        .map(|row| row.iter().map(|&i| Symbol::from_index(i)).collect())
+        .collect();
+    let feature = tigrinho_core::opening_feature(&def, &window);
+    Ok(spin_response(state, &entry, feature))
+}
+
+/// The signed response for a recorded spin, with the feature it left its
+/// round in.
+fn spin_response(
+    state: &AppState,
+    entry: &SpinLogEntry,
+    feature: Option<FeatureState>,
+) -> SpinResponse {
+    let config_hash = entry.config_hash.clone().unwrap_or_default();
+    let cursor = entry.cursor.map(|c| c as u64);
+    let signature = state.receipts.sign(&Receipt {
+        config_hash: &config_hash,
+        server_seed_hash: &entry.server_seed_hash,
+        nonce: entry.nonce as u64,
+        reels: &entry.result_reels,
+        payout: entry.payout,
+        cursor,
+    });
+    SpinResponse {
+        server_seed_hash: entry.server_seed_hash.clone(),
+        config_hash,
+        nonce: entry.nonce as u64,
+        reels: entry.result_reels.clone(),
+        payout: entry.payout,
+        key_id: state.receipts.key_id().to_string(),
+        signature,
+        round_id: entry.round_id,
+        cursor,
+        feature: feature.map(rounds::feature_info),
+    }
+}
+
+/// `*` allows any origin; otherwise only the listed ones.
+fn cors_origins(origins: &[String]) -> anyhow::Result<AllowOrigin> {
+    if origins.iter().any(|o| o == "*") {
+        return Ok(AllowOrigin::any());
+    }
+    let origins = origins
+        .iter()
+        .map(|o| HeaderValue::from_str(o))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(AllowOrigin::list(origins))
+}
+
+/// The full game API over a migrated store (see [`GameStore::migrate`]). Starts
+/// the seed rotation and Merkle root schedulers, so it must be called from
+/// within a Tokio runtime.
+pub fn build_router(cfg: &ServerConfig, store: Arc<dyn GameStore>) -> anyhow::Result<Router> {
+    let state = Arc::new(AppState::new(cfg, store)?);
+    seeds::spawn_scheduler(state.clone());
+    audit::spawn_merkle_commits(state.clone());
+    let cors = cors_origins(&cfg.cors_origins)?;
+
+    let spin_limit = ratelimit::RateLimitLayer::new(state.clone());
+    // per-game routes; the legacy unprefixed routes address games::DEFAULT_GAME
+    Ok(Router::new()
+        .route("/games", get(games::route_list_games))
+        .route("/games/:slug", get(games::route_game_summary))
+        .route("/games/:slug/config", get(games::route_game_config))
+        .route("/games/:slug/verify", get(route_verify))
+        .route(
+            "/games/:slug/spin",
+            post(route_spin).layer(spin_limit.clone()),
+        )
+        .route(
+            "/games/:slug/seeds/revealed",
+            get(seeds::route_revealed_seeds),
+        )
+        .route("/verify", get(route_verify))
+        .route("/spin", post(route_spin).layer(spin_limit.clone()))
+        .route("/seeds/revealed", get(seeds::route_revealed_seeds))
+        .route("/configs/:hash", get(configs::route_get_config))
+        .route("/audit/chain-head", get(audit::route_chain_head))
+        .route("/audit/roots", get(audit::route_merkle_roots))
+        .route("/keys", get(audit::route_receipt_keys))
+        .route("/spins/:id/proof", get(audit::route_spin_proof))
+        .route("/auth/register", post(auth::route_register))
+        .route("/auth/login", post(auth::route_login))
+        .route("/auth/logout", post(auth::route_logout))
+        .route("/auth/sessions/revoke", post(auth::route_revoke_sessions))
+        .route("/me", get(auth::route_me))
+        .route("/me/client-seed", post(auth::route_set_client_seed))
+        .route("/me/spins", get(spins::route_my_spins))
+        .route("/me/sessions", get(rounds::route_my_sessions))
+        .route("/me/live-ticket", post(live::route_player_ticket))
+        .route(
+            "/rounds/:id/action",
+            post(rounds::route_round_action).layer(spin_limit),
+        )
+        .route("/spins", get(spins::route_admin_spins))
+        .route("/admin/games", post(games::route_admin_create_game))
+        .route(
+            "/admin/games/:slug/limits",
+            post(games::route_admin_set_limits),
+        )
+        .route(
+            "/admin/games/:slug/resume",
+            post(monitor::route_admin_resume_game),
+        )
+        .route("/admin/rtp-alerts", get(monitor::route_admin_rtp_alerts))
+        .route("/admin/audit", get(audit::route_admin_audit))
+        .route("/admin/live-ticket", post(live::route_admin_ticket))
+        .route("/admin/rounds", get(rounds::route_admin_rounds))
+        .route("/admin/rounds/:id", get(rounds::route_admin_round))
+        .route(
+            "/admin/rounds/:id/void",
+            post(rounds::route_admin_void_round),
+        )
+        .route(
+            "/admin/games/:slug/report",
+            get(rounds::route_admin_game_report),
+        )
+        .route(
+            "/admin/games/:slug/params/preview",
+            post(admin::route_admin_preview_params),
+        )
+        .route(
+            "/admin/games/:slug/params",
+            post(admin::route_admin_set_params),
+        )
+        .route(
+            "/admin/games/:slug/seeds/rotate",
+            post(seeds::route_admin_rotate_seed),
+        )
+        .route(
+            "/admin/params/preview",
+            post(admin::route_admin_preview_params),
+        )
+        .route("/admin/set-params", post(admin::route_admin_set_params))
+        .route("/admin/seeds/rotate", post(seeds::route_admin_rotate_seed))
+        .route("/metrics", get(metrics::route_metrics))
+        .route("/ws", get(live::route_ws))
+        .method_not_allowed_fallback(error::route_method_not_allowed)
+        .fallback(error::route_not_found)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::verify_signed_admin,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            metrics::track_requests,
+        ))
+        .with_state(state)
+        .layer(middleware::from_fn(error::request_id))
+        .layer(
+            CorsLayer::new()
+                .allow_origin(cors)
+                .allow_methods(Any)
+                .allow_headers(Any),
+        ))
+}
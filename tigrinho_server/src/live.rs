@@ -0,0 +1,371 @@
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket},
+        State,
+    },
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::debug;
+
+use tigrinho_shared::{
+    AdminRole, ApiError, BigWin, LiveChannel, LiveEvent, LiveQuery, LiveTicket, RtpAlert,
+    SpinLogEntry,
+};
+
+use crate::auth::{self, AdminAuth, AuthPlayer};
+use crate::error::{ApiJson, ApiQuery, ApiWs, AppResult};
+use crate::AppState;
+
+/// Events buffered per subscriber; a slower client skips what it missed.
+const CHANNEL_CAPACITY: usize = 1024;
+/// How long a ticket can wait to be redeemed: only for the client to connect.
+const TICKET_TTL_SECS: i64 = 30;
+/// How often an open feed checks that what it was opened with still holds.
+const RECHECK_SECS: u64 = 15;
+
+/// An event as fanned out to every subscriber, serialized once.
+#[derive(Debug)]
+struct Published {
+    /// Player whose spin this is, for the per-player channel.
+    player_id: Option<i64>,
+    kind: Kind,
+    json: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Spin,
+    BigWin,
+    RtpAlert,
+}
+
+/// Who is listening on a socket, after authentication.
+#[derive(Debug, Clone, Copy)]
+enum Subscriber {
+    Player(i64),
+    Public,
+    Admin,
+}
+
+impl Subscriber {
+    fn channel(self) -> LiveChannel {
+        match self {
+            Subscriber::Player(_) => LiveChannel::Player,
+            Subscriber::Public => LiveChannel::Public,
+            Subscriber::Admin => LiveChannel::Admin,
+        }
+    }
+
+    fn sees(self, event: &Published) -> bool {
+        match self {
+            Subscriber::Player(id) => event.kind == Kind::Spin && event.player_id == Some(id),
+            Subscriber::Public => event.kind == Kind::BigWin,
+            Subscriber::Admin => event.kind != Kind::BigWin,
+        }
+    }
+}
+
+/// The session or stored admin key a ticket was issued for. The feed it opens
+/// closes once that is revoked or expires; the public feed and the configured
+/// `api_key` have none.
+#[derive(Debug, Clone, PartialEq)]
+enum Credential {
+    Session(String),
+    AdminKey(String),
+}
+
+impl Credential {
+    async fn holds(&self, state: &AppState) -> bool {
+        match self {
+            Credential::Session(id) => auth::live_session(state, id).await.is_ok(),
+            Credential::AdminKey(id) => auth::live_admin_key(state, id).await.is_ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Ticket {
+    subscriber: Subscriber,
+    credential: Option<Credential>,
+    expires_at: DateTime<Utc>,
+}
+
+/// In-process fan-out of spin and alert events to WebSocket subscribers.
+pub struct LiveHub {
+    tx: broadcast::Sender<Arc<Published>>,
+    /// Payout multiple of the bet from which a spin counts as a big win.
+    big_win_multiplier: f64,
+    /// Outstanding tickets by their secret.
+    tickets: Mutex<HashMap<String, Ticket>>,
+}
+
+impl LiveHub {
+    pub fn new(big_win_multiplier: f64) -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            big_win_multiplier,
+            tickets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn issue_ticket(
+        &self,
+        subscriber: Subscriber,
+        credential: Option<Credential>,
+        now: DateTime<Utc>,
+    ) -> LiveTicket {
+        let mut bytes = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let ticket = LiveTicket {
+            ticket: hex::encode(bytes),
+            channel: subscriber.channel(),
+            expires_at: now + Duration::seconds(TICKET_TTL_SECS),
+        };
+        let mut tickets = self.tickets.lock().unwrap();
+        tickets.retain(|_, t| now < t.expires_at);
+        tickets.insert(
+            ticket.ticket.clone(),
+            Ticket {
+                subscriber,
+                credential,
+                expires_at: ticket.expires_at,
+            },
+        );
+        ticket
+    }
+
+    /// Use up a ticket; it must be for `channel` and still valid.
+    fn redeem_ticket(
+        &self,
+        ticket: &str,
+        channel: LiveChannel,
+        now: DateTime<Utc>,
+    ) -> Option<Ticket> {
+        let ticket = self.tickets.lock().unwrap().remove(ticket)?;
+        (now < ticket.expires_at && ticket.subscriber.channel() == channel).then_some(ticket)
+    }
+
+    fn publish(&self, player_id: Option<i64>, kind: Kind, event: &LiveEvent) {
+        // nobody listening is not an error
+        let json = serde_json::to_string(event).expect("serializable event");
+        let _ = self.tx.send(Arc::new(Published {
+            player_id,
+            kind,
+            json,
+        }));
+    }
+
+    pub fn publish_spin(&self, spin: &SpinLogEntry, bet: f64) {
+        let multiplier = spin.payout / bet;
+        if multiplier >= self.big_win_multiplier {
+            let win = BigWin {
+                game: spin.game.clone(),
+                ts: spin.ts,
+                bet,
+                payout: spin.payout,
+                multiplier,
+            };
+            self.publish(None, Kind::BigWin, &LiveEvent::BigWin(win));
+        }
+        self.publish(spin.player_id, Kind::Spin, &LiveEvent::Spin(spin.clone()));
+    }
+
+    pub fn publish_alert(&self, alert: &RtpAlert) {
+        self.publish(None, Kind::RtpAlert, &LiveEvent::RtpAlert(alert.clone()));
+    }
+}
+
+/// `POST /me/live-ticket`: a ticket to the caller's own spins.
+pub async fn route_player_ticket(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+) -> AppResult<ApiJson<LiveTicket>> {
+    let subscriber = Subscriber::Player(player.player_id);
+    let credential = Credential::Session(player.session_id);
+    Ok(ApiJson(state.live.issue_ticket(
+        subscriber,
+        Some(credential),
+        Utc::now(),
+    )))
+}
+
+/// `POST /admin/live-ticket`: a ticket to the admin feed.
+pub async fn route_admin_ticket(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+) -> AppResult<ApiJson<LiveTicket>> {
+    admin.require(AdminRole::Viewer)?;
+    let credential = admin.key_id.map(Credential::AdminKey);
+    Ok(ApiJson(state.live.issue_ticket(
+        Subscriber::Admin,
+        credential,
+        Utc::now(),
+    )))
+}
+
+/// `GET /ws?channel=player|public|admin&ticket=...`: redeem the ticket, then upgrade.
+pub async fn route_ws(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(q): ApiQuery<LiveQuery>,
+    ApiWs(ws): ApiWs,
+) -> AppResult<Response> {
+    let (subscriber, credential) = match q.channel {
+        LiveChannel::Public => (Subscriber::Public, None),
+        channel => {
+            let ticket = q.ticket.as_deref().ok_or(ApiError::Unauthorized)?;
+            let ticket = state
+                .live
+                .redeem_ticket(ticket, channel, Utc::now())
+                .ok_or(ApiError::Unauthorized)?;
+            (ticket.subscriber, ticket.credential)
+        }
+    };
+    let rx = state.live.tx.subscribe();
+    Ok(ws.on_upgrade(move |socket| stream(state, socket, rx, subscriber, credential)))
+}
+
+async fn stream(
+    state: Arc<AppState>,
+    mut socket: WebSocket,
+    mut rx: broadcast::Receiver<Arc<Published>>,
+    subscriber: Subscriber,
+    credential: Option<Credential>,
+) {
+    let every = std::time::Duration::from_secs(RECHECK_SECS);
+    let mut recheck = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if subscriber.sees(&event)
+                        && socket.send(Message::Text(event.json.clone())).await.is_err()
+                    {
+                        return;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("live subscriber lagged, skipped {skipped} events");
+                }
+                Err(RecvError::Closed) => return,
+            },
+            // the feed is one-way; anything but a close from the client is ignored
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            _ = recheck.tick(), if credential.is_some() => {
+                let Some(credential) = &credential else { continue };
+                if !credential.holds(&state).await {
+                    let frame = CloseFrame {
+                        code: close_code::POLICY,
+                        reason: "credentials revoked or expired".into(),
+                    };
+                    let _ = socket.send(Message::Close(Some(frame))).await;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin(player_id: i64, payout: f64) -> SpinLogEntry {
+        SpinLogEntry {
+            id: 1,
+            ts: Utc::now(),
+            game: "tigrinho".into(),
+            player_id: Some(player_id),
+            client_seed: "c".into(),
+            nonce: 1,
+            server_seed_hash: "h".into(),
+            config_hash: None,
+            result_reels: vec![vec![0, 0, 0]],
+            payout,
+            prev_hash: "p".into(),
+            row_hash: "r".into(),
+            round_id: Some(1),
+            cursor: None,
+            action: None,
+        }
+    }
+
+    fn drain(rx: &mut broadcast::Receiver<Arc<Published>>, who: Subscriber) -> Vec<Kind> {
+        let mut seen = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if who.sees(&event) {
+                seen.push(event.kind);
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn channels_filter_events() {
+        let hub = LiveHub::new(10.0);
+        let mut rx = [
+            hub.tx.subscribe(),
+            hub.tx.subscribe(),
+            hub.tx.subscribe(),
+            hub.tx.subscribe(),
+        ];
+        hub.publish_spin(&spin(1, 2.0), 1.0);
+        hub.publish_spin(&spin(2, 15.0), 1.0);
+
+        assert_eq!(drain(&mut rx[0], Subscriber::Player(1)), vec![Kind::Spin]);
+        assert_eq!(drain(&mut rx[1], Subscriber::Player(3)), vec![]);
+        assert_eq!(drain(&mut rx[2], Subscriber::Public), vec![Kind::BigWin]);
+        assert_eq!(
+            drain(&mut rx[3], Subscriber::Admin),
+            vec![Kind::Spin, Kind::Spin]
+        );
+    }
+
+    #[test]
+    fn tickets_are_single_use_per_channel_and_expire() {
+        let hub = LiveHub::new(10.0);
+        let now = Utc::now();
+        let session = Some(Credential::Session("s".into()));
+        let ticket = hub
+            .issue_ticket(Subscriber::Player(4), session.clone(), now)
+            .ticket;
+        assert!(hub
+            .redeem_ticket(&ticket, LiveChannel::Player, now)
+            .is_some_and(
+                |t| matches!(t.subscriber, Subscriber::Player(4)) && t.credential == session
+            ));
+        assert!(hub
+            .redeem_ticket(&ticket, LiveChannel::Player, now)
+            .is_none());
+
+        let ticket = hub.issue_ticket(Subscriber::Player(4), session, now).ticket;
+        assert!(hub
+            .redeem_ticket(&ticket, LiveChannel::Admin, now)
+            .is_none());
+
+        let ticket = hub.issue_ticket(Subscriber::Admin, None, now).ticket;
+        let late = now + Duration::seconds(TICKET_TTL_SECS);
+        assert!(hub
+            .redeem_ticket(&ticket, LiveChannel::Admin, late)
+            .is_none());
+    }
+
+    #[test]
+    fn public_feed_is_anonymous() {
+        let hub = LiveHub::new(10.0);
+        let mut rx = hub.tx.subscribe();
+        hub.publish_spin(&spin(7, 20.0), 1.0);
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.kind, Kind::BigWin);
+        assert!(!event.json.contains("player_id"));
+        assert!(!event.json.contains("client_seed"));
+    }
+}
@@ -0,0 +1,313 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use prometheus::{
+    CounterVec, Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tigrinho_shared::{AdminRole, Round};
+
+use crate::auth::AdminAuth;
+use crate::configs;
+use crate::error::AppResult;
+use crate::AppState;
+
+/// Sizes, in spins, of the rolling windows the observed RTP is reported over.
+pub const RTP_WINDOWS: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Running sums over the most recent spins of one game.
+#[derive(Default)]
+struct RollingRtp {
+    recent: VecDeque<(f64, f64)>,
+    // (wagered, paid) over the last RTP_WINDOWS[i] spins
+    sums: [(f64, f64); RTP_WINDOWS.len()],
+}
+
+impl RollingRtp {
+    fn push(&mut self, bet: f64, payout: f64) {
+        self.recent.push_front((bet, payout));
+        for (i, &size) in RTP_WINDOWS.iter().enumerate() {
+            self.sums[i].0 += bet;
+            self.sums[i].1 += payout;
+            if let Some(&(old_bet, old_payout)) = self.recent.get(size) {
+                self.sums[i].0 -= old_bet;
+                self.sums[i].1 -= old_payout;
+            }
+        }
+        self.recent.truncate(RTP_WINDOWS[RTP_WINDOWS.len() - 1]);
+    }
+
+    /// Observed RTP per window, `None` until the window has any wager.
+    fn rtp(&self) -> [Option<f64>; RTP_WINDOWS.len()] {
+        self.sums
+            .map(|(wagered, paid)| (wagered > 0.0).then(|| paid / wagered))
+    }
+}
+
+/// Process-wide Prometheus metrics. Counters start from zero on every restart.
+pub struct Metrics {
+    registry: Registry,
+    spins: IntCounterVec,
+    wagered: CounterVec,
+    paid: CounterVec,
+    observed_rtp: GaugeVec,
+    theoretical_rtp: GaugeVec,
+    rtp_alerts: IntCounterVec,
+    voided_rounds: IntCounterVec,
+    refunded: CounterVec,
+    reversed: CounterVec,
+    rate_limited: IntCounterVec,
+    seed_age: GaugeVec,
+    request_duration: HistogramVec,
+    pool_size: IntGauge,
+    pool_idle: IntGauge,
+    pool_max: IntGauge,
+    rolling: Mutex<HashMap<String, RollingRtp>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("tigrinho".into()), None).expect("registry");
+        let spins =
+            IntCounterVec::new(Opts::new("spins_total", "Spins played"), &["game"]).unwrap();
+        let wagered =
+            CounterVec::new(Opts::new("wagered_total", "Total amount bet"), &["game"]).unwrap();
+        let paid =
+            CounterVec::new(Opts::new("paid_total", "Total amount paid out"), &["game"]).unwrap();
+        let observed_rtp = GaugeVec::new(
+            Opts::new(
+                "observed_rtp",
+                "Paid over wagered across the last `window` spins",
+            ),
+            &["game", "window"],
+        )
+        .unwrap();
+        let theoretical_rtp = GaugeVec::new(
+            Opts::new(
+                "theoretical_rtp",
+                "RTP of the active config computed by tigrinho_core",
+            ),
+            &["game"],
+        )
+        .unwrap();
+        let rtp_alerts = IntCounterVec::new(
+            Opts::new("rtp_alerts_total", "RTP deviation alerts raised"),
+            &["game"],
+        )
+        .unwrap();
+        let voided_rounds = IntCounterVec::new(
+            Opts::new("rounds_voided_total", "Rounds voided by an admin"),
+            &["game"],
+        )
+        .unwrap();
+        // wagered_total and paid_total keep what voided rounds moved; these say how much
+        let refunded = CounterVec::new(
+            Opts::new("refunded_total", "Wagers refunded by voided rounds"),
+            &["game"],
+        )
+        .unwrap();
+        let reversed = CounterVec::new(
+            Opts::new("reversed_total", "Payouts taken back by voided rounds"),
+            &["game"],
+        )
+        .unwrap();
+        let rate_limited = IntCounterVec::new(
+            Opts::new(
+                "rate_limited_total",
+                "Requests refused by a rate limit, by the scope that refused",
+            ),
+            &["scope"],
+        )
+        .unwrap();
+        let seed_age = GaugeVec::new(
+            Opts::new(
+                "seed_age_seconds",
+                "Seconds since the active server seed was committed",
+            ),
+            &["game"],
+        )
+        .unwrap();
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "Request latency"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let pool_size = IntGauge::new("db_pool_connections", "Open database connections").unwrap();
+        let pool_idle =
+            IntGauge::new("db_pool_idle_connections", "Idle database connections").unwrap();
+        let pool_max = IntGauge::new("db_pool_max_connections", "Database pool capacity").unwrap();
+
+        registry.register(Box::new(spins.clone())).unwrap();
+        registry.register(Box::new(wagered.clone())).unwrap();
+        registry.register(Box::new(paid.clone())).unwrap();
+        registry.register(Box::new(observed_rtp.clone())).unwrap();
+        registry
+            .register(Box::new(theoretical_rtp.clone()))
+            .unwrap();
+        registry.register(Box::new(rtp_alerts.clone())).unwrap();
+        registry.register(Box::new(voided_rounds.clone())).unwrap();
+        registry.register(Box::new(refunded.clone())).unwrap();
+        registry.register(Box::new(reversed.clone())).unwrap();
+        registry.register(Box::new(rate_limited.clone())).unwrap();
+        registry.register(Box::new(seed_age.clone())).unwrap();
+        registry
+            .register(Box::new(request_duration.clone()))
+            .unwrap();
+        registry.register(Box::new(pool_size.clone())).unwrap();
+        registry.register(Box::new(pool_idle.clone())).unwrap();
+        registry.register(Box::new(pool_max.clone())).unwrap();
+
+        Self {
+            registry,
+            spins,
+            wagered,
+            paid,
+            observed_rtp,
+            theoretical_rtp,
+            rtp_alerts,
+            voided_rounds,
+            refunded,
+            reversed,
+            rate_limited,
+            seed_age,
+            request_duration,
+            pool_size,
+            pool_idle,
+            pool_max,
+            rolling: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_spin(&self, game: &str, bet: f64, payout: f64) {
+        self.spins.with_label_values(&[game]).inc();
+        self.wagered.with_label_values(&[game]).inc_by(bet);
+        self.paid.with_label_values(&[game]).inc_by(payout);
+        let mut rolling = self.rolling.lock().unwrap();
+        let window = rolling.entry(game.to_string()).or_default();
+        window.push(bet, payout);
+        for (size, rtp) in RTP_WINDOWS.iter().zip(window.rtp()) {
+            if let Some(rtp) = rtp {
+                self.observed_rtp
+                    .with_label_values(&[game, &size.to_string()])
+                    .set(rtp);
+            }
+        }
+    }
+
+    pub fn record_rtp_alert(&self, game: &str) {
+        self.rtp_alerts.with_label_values(&[game]).inc();
+    }
+
+    pub fn record_void(&self, round: &Round) {
+        let game = round.game.as_str();
+        self.voided_rounds.with_label_values(&[game]).inc();
+        self.refunded.with_label_values(&[game]).inc_by(round.bet);
+        self.reversed
+            .with_label_values(&[game])
+            .inc_by(round.payout);
+    }
+
+    pub fn record_rate_limited(&self, scope: &str) {
+        self.rate_limited.with_label_values(&[scope]).inc();
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Middleware: time every request, labelled by its route template rather than
+/// the raw path so slugs and hashes do not explode the label set.
+pub async fn track_requests(
+    State(state): State<Arc<AppState>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = req.method().to_string();
+    let start = Instant::now();
+    let resp = next.run(req).await;
+    state
+        .metrics
+        .request_duration
+        .with_label_values(&[&method, &route, resp.status().as_str()])
+        .observe(start.elapsed().as_secs_f64());
+    resp
+}
+
+/// Refresh the gauges that are read from the database or pool at scrape time.
+async fn refresh(state: &AppState) -> anyhow::Result<()> {
+    let m = &state.metrics;
+    if let Some(pool) = state.store.pool_stats() {
+        m.pool_size.set(pool.size as i64);
+        m.pool_idle.set(pool.idle as i64);
+        m.pool_max.set(pool.max as i64);
+    }
+
+    let now = Utc::now();
+    for game in state.store.list_games().await? {
+        if let Some(committed) = game.seed_committed_at {
+            m.seed_age
+                .with_label_values(&[&game.slug])
+                .set((now - committed).num_milliseconds() as f64 / 1000.0);
+        }
+        let Some(hash) = game.active_config_hash else {
+            continue;
+        };
+        let def = configs::config_by_hash(state.store.as_ref(), &hash).await?;
+        let report = configs::theoretical_report(&state.math_cache, &hash, &def).await?;
+        m.theoretical_rtp
+            .with_label_values(&[&game.slug])
+            .set(report.rtp);
+    }
+    Ok(())
+}
+
+/// Per-game money totals are for staff only: scrape with a viewer admin key
+/// as the bearer token.
+pub async fn route_metrics(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+) -> AppResult<Response> {
+    admin.require(AdminRole::Viewer)?;
+    refresh(&state).await?;
+    let mut buf = Vec::new();
+    TextEncoder::new()
+        .encode(&state.metrics.registry.gather(), &mut buf)
+        .map_err(anyhow::Error::from)?;
+    Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], buf).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolling_windows_forget_old_spins() {
+        let mut r = RollingRtp::default();
+        for _ in 0..RTP_WINDOWS[0] {
+            r.push(1.0, 0.0);
+        }
+        for _ in 0..RTP_WINDOWS[0] {
+            r.push(1.0, 2.0);
+        }
+        let rtp = r.rtp();
+        assert_eq!(rtp[0], Some(2.0));
+        assert_eq!(rtp[1], Some(1.0));
+        assert!(r.recent.len() <= RTP_WINDOWS[RTP_WINDOWS.len() - 1]);
+    }
+}
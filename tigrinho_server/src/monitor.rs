@@ -0,0 +1,271 @@
+use axum::extract::State;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+use tigrinho_core::GameDefinition;
+use tigrinho_shared::{AdminRole, ApiError, GameSummary, MathReport, RtpAlert};
+use tigrinho_store::NewAlert;
+
+use crate::auth::AdminAuth;
+use crate::configs;
+use crate::error::{ApiJson, AppResult};
+use crate::games::{self, GameSlug};
+use crate::AppState;
+
+const ALERT_LIST_LIMIT: u32 = 100;
+
+/// When the monitor alerts (`[rtp_monitor]` in the server config).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MonitorPolicy {
+    /// |z| above which the deviation is significant. The test is repeated after
+    /// every spin, so this is deliberately far above the usual 1.96 to keep the
+    /// false alarm rate over many looks negligible.
+    pub z_threshold: f64,
+    /// Spins observed under a config before it is tested at all.
+    pub min_spins: u64,
+    /// Suspend the game when an alert fires.
+    pub auto_suspend: bool,
+}
+
+impl Default for MonitorPolicy {
+    fn default() -> Self {
+        Self {
+            z_threshold: 5.0,
+            min_spins: 1_000,
+            auto_suspend: true,
+        }
+    }
+}
+
+/// Per-unit-bet returns observed under one config of one game.
+#[derive(Debug, Default)]
+struct Tracker {
+    config_hash: String,
+    spins: u64,
+    returned: f64,
+    alerted: bool,
+}
+
+impl Tracker {
+    fn observed_rtp(&self) -> f64 {
+        self.returned / self.spins as f64
+    }
+
+    /// Standard score of the observed mean return against the theoretical one.
+    fn z_score(&self, theoretical: &MathReport) -> f64 {
+        let diff = self.observed_rtp() - theoretical.rtp;
+        let std_error = theoretical.std_dev / (self.spins as f64).sqrt();
+        if std_error > 0.0 {
+            diff / std_error
+        } else if diff.abs() > 1e-9 {
+            // a zero-variance game can only ever return exactly its RTP
+            diff.signum() * f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Tests each game's observed RTP against the theoretical RTP of the config it
+/// runs. Observations live in memory and restart from zero when the process
+/// restarts, the active config changes or the game is resumed.
+pub struct RtpMonitor {
+    policy: MonitorPolicy,
+    trackers: Mutex<HashMap<String, Tracker>>,
+}
+
+struct Deviation {
+    spins: u64,
+    observed_rtp: f64,
+    z_score: f64,
+}
+
+impl RtpMonitor {
+    pub fn new(policy: MonitorPolicy) -> Self {
+        Self {
+            policy,
+            trackers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Add one spin; returns the deviation the first time it becomes significant.
+    fn observe(
+        &self,
+        game: &str,
+        config_hash: &str,
+        theoretical: &MathReport,
+        bet: f64,
+        payout: f64,
+    ) -> Option<Deviation> {
+        let mut trackers = self.trackers.lock().unwrap();
+        let t = trackers.entry(game.to_string()).or_default();
+        if t.config_hash != config_hash {
+            *t = Tracker {
+                config_hash: config_hash.to_string(),
+                ..Default::default()
+            };
+        }
+        t.spins += 1;
+        t.returned += payout / bet;
+        if t.alerted || t.spins < self.policy.min_spins {
+            return None;
+        }
+        let z_score = t.z_score(theoretical);
+        if z_score.abs() <= self.policy.z_threshold {
+            return None;
+        }
+        t.alerted = true;
+        Some(Deviation {
+            spins: t.spins,
+            observed_rtp: t.observed_rtp(),
+            z_score,
+        })
+    }
+
+    fn reset(&self, game: &str) {
+        self.trackers.lock().unwrap().remove(game);
+    }
+}
+
+/// Feed a settled spin to the monitor, alerting and suspending off the request path.
+pub fn after_spin(
+    state: &Arc<AppState>,
+    game: &str,
+    config_hash: &str,
+    def: &GameDefinition,
+    bet: f64,
+    payout: f64,
+) {
+    let state = state.clone();
+    let game = game.to_string();
+    let config_hash = config_hash.to_string();
+    let def = def.clone();
+    tokio::spawn(async move {
+        let theoretical =
+            match configs::theoretical_report(&state.math_cache, &config_hash, &def).await {
+                Ok(report) => report,
+                Err(e) => {
+                    error!("rtp monitor: no theoretical report for {config_hash}: {e:#}");
+                    return;
+                }
+            };
+        let Some(d) = state
+            .monitor
+            .observe(&game, &config_hash, &theoretical, bet, payout)
+        else {
+            return;
+        };
+        let suspend = state.monitor.policy.auto_suspend;
+        state.metrics.record_rtp_alert(&game);
+        warn!(
+            "rtp alert on {game}: observed {:.4} vs theoretical {:.4} after {} spins (z = {:.2}){}",
+            d.observed_rtp,
+            theoretical.rtp,
+            d.spins,
+            d.z_score,
+            if suspend { ", suspending" } else { "" }
+        );
+        let alert = NewAlert {
+            game,
+            config_hash,
+            spins: d.spins as i64,
+            observed_rtp: d.observed_rtp,
+            theoretical_rtp: theoretical.rtp,
+            z_score: d.z_score,
+        };
+        match state.store.raise_alert(&alert, suspend).await {
+            Ok(alert) => state.live.publish_alert(&alert),
+            Err(e) => error!("rtp monitor: recording alert failed: {e:#}"),
+        }
+    });
+}
+
+/// Clear a suspension and restart observation of the game from zero.
+pub async fn route_admin_resume_game(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    GameSlug(slug): GameSlug,
+) -> AppResult<ApiJson<GameSummary>> {
+    admin.require(AdminRole::Operator)?;
+    let game = games::require_game(state.store.as_ref(), &slug).await?;
+    if game.suspended_at.is_none() {
+        return Err(ApiError::Conflict(format!("game {slug} is not suspended")).into());
+    }
+    state
+        .store
+        .resume_game(&slug, &admin.change("games.resume"))
+        .await?;
+    state.monitor.reset(&slug);
+    let after = games::require_game(state.store.as_ref(), &slug)
+        .await?
+        .summary();
+    Ok(ApiJson(after))
+}
+
+/// The latest alerts across all games, newest first.
+pub async fn route_admin_rtp_alerts(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+) -> AppResult<ApiJson<Vec<RtpAlert>>> {
+    admin.require(AdminRole::Viewer)?;
+    Ok(ApiJson(state.store.rtp_alerts(ALERT_LIST_LIMIT).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tigrinho_shared::MathMethod;
+
+    fn report(rtp: f64, std_dev: f64) -> MathReport {
+        MathReport {
+            method: MathMethod::Exact,
+            rounds: 0,
+            rtp,
+            hit_frequency: 0.5,
+            variance: std_dev * std_dev,
+            std_dev,
+            max_multiplier: 2.0,
+            rtp_std_error: 0.0,
+        }
+    }
+
+    fn monitor() -> RtpMonitor {
+        RtpMonitor::new(MonitorPolicy {
+            z_threshold: 5.0,
+            min_spins: 100,
+            auto_suspend: true,
+        })
+    }
+
+    #[test]
+    fn consistent_returns_do_not_alert() {
+        let m = monitor();
+        // alternating 0x / 2x has mean 1 and std dev 1
+        let theoretical = report(1.0, 1.0);
+        for i in 0..10_000 {
+            let payout = if i % 2 == 0 { 0.0 } else { 2.0 };
+            assert!(m.observe("g", "h", &theoretical, 1.0, payout).is_none());
+        }
+    }
+
+    #[test]
+    fn deviation_alerts_once_and_config_change_resets() {
+        let m = monitor();
+        let theoretical = report(1.0, 1.0);
+        let mut alerts = 0;
+        for _ in 0..1_000 {
+            if let Some(d) = m.observe("g", "h", &theoretical, 1.0, 0.0) {
+                assert!(d.z_score < -5.0);
+                // nothing is tested before min_spins
+                assert_eq!(d.spins, 100);
+                alerts += 1;
+            }
+        }
+        assert_eq!(alerts, 1);
+        // a new config starts a fresh observation window
+        assert!(m.observe("g", "h2", &theoretical, 1.0, 0.0).is_none());
+    }
+}
@@ -0,0 +1,285 @@
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::header::AUTHORIZATION,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tower::{Layer, Service};
+
+use tigrinho_shared::ApiError;
+
+use crate::auth::authenticate;
+use crate::error::AppError;
+use crate::AppState;
+
+/// Buckets idle long enough to be full again are dropped once the map grows past this.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// A token bucket: `burst` tokens, refilled at `rate` tokens per second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketConfig {
+    pub rate: f64,
+    pub burst: f64,
+}
+
+impl BucketConfig {
+    /// `"<rate>:<burst>"`, e.g. `"5:10"`; `"off"` disables the limit.
+    pub fn parse(s: &str) -> Option<Option<Self>> {
+        if s == "off" {
+            return Some(None);
+        }
+        let (rate, burst) = s.split_once(':')?;
+        let rate: f64 = rate.trim().parse().ok()?;
+        let burst: f64 = burst.trim().parse().ok()?;
+        (rate > 0.0 && burst >= 1.0).then_some(Some(Self { rate, burst }))
+    }
+}
+
+/// Limits applied to `/spin`; `None` disables a scope.
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub per_player: Option<BucketConfig>,
+    pub per_ip: Option<BucketConfig>,
+    pub global: Option<BucketConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum BucketKey {
+    Player(i64),
+    Ip(IpAddr),
+    Global,
+}
+
+impl BucketKey {
+    fn scope(&self) -> &'static str {
+        match self {
+            BucketKey::Player(_) => "player",
+            BucketKey::Ip(_) => "ip",
+            BucketKey::Global => "global",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, cfg: BucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * cfg.rate).min(cfg.burst);
+        self.updated = now;
+    }
+}
+
+pub struct RateLimiter {
+    limits: RateLimits,
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn config(&self, key: &BucketKey) -> Option<BucketConfig> {
+        match key {
+            BucketKey::Player(_) => self.limits.per_player,
+            BucketKey::Ip(_) => self.limits.per_ip,
+            BucketKey::Global => self.limits.global,
+        }
+    }
+
+    /// Take one token from every applicable bucket, or none at all. On refusal
+    /// returns the scope that refused and the seconds until it has a token.
+    fn acquire(&self, keys: &[BucketKey], now: Instant) -> Result<(), (&'static str, u64)> {
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|key, b| match self.config(key) {
+                Some(cfg) => {
+                    let mut b = *b;
+                    b.refill(cfg, now);
+                    b.tokens < cfg.burst
+                }
+                None => false,
+            });
+        }
+        let mut refused: Option<(&'static str, u64)> = None;
+        for key in keys {
+            let Some(cfg) = self.config(key) else {
+                continue;
+            };
+            let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+                tokens: cfg.burst,
+                updated: now,
+            });
+            bucket.refill(cfg, now);
+            if bucket.tokens < 1.0 {
+                let wait = ((1.0 - bucket.tokens) / cfg.rate).ceil().max(1.0) as u64;
+                if refused.is_none_or(|(_, w)| wait > w) {
+                    refused = Some((key.scope(), wait));
+                }
+            }
+        }
+        if let Some(refused) = refused {
+            return Err(refused);
+        }
+        for key in keys {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tower layer enforcing [`RateLimits`] on the routes it wraps.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    state: Arc<AppState>,
+}
+
+impl RateLimitLayer {
+    pub fn new(state: Arc<AppState>) -> Self {
+        Self { state }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    state: Arc<AppState>,
+}
+
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // take the service that was polled ready, leave a fresh clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+        Box::pin(async move {
+            let mut keys = vec![BucketKey::Global];
+            if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
+                keys.push(BucketKey::Ip(addr.ip()));
+            }
+            if state.rate_limiter.limits.per_player.is_some() {
+                let token = req
+                    .headers()
+                    .get(AUTHORIZATION)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.strip_prefix("Bearer "))
+                    .map(str::to_string);
+                // requests without a live session are still limited per IP and
+                // globally; the handler rejects them anyway
+                if let Some(token) = token {
+                    if let Ok(player) = authenticate(&state, &token).await {
+                        keys.push(BucketKey::Player(player.player_id));
+                        req.extensions_mut().insert(player);
+                    }
+                }
+            }
+            if let Err((scope, retry_after_secs)) =
+                state.rate_limiter.acquire(&keys, Instant::now())
+            {
+                state.metrics.record_rate_limited(scope);
+                return Ok(AppError(ApiError::RateLimited { retry_after_secs }).into_response());
+            }
+            inner.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimits {
+            per_player: Some(BucketConfig {
+                rate: 1.0,
+                burst: 2.0,
+            }),
+            per_ip: None,
+            global: Some(BucketConfig {
+                rate: 100.0,
+                burst: 100.0,
+            }),
+        })
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let l = limiter();
+        let keys = [BucketKey::Global, BucketKey::Player(1)];
+        let t0 = Instant::now();
+        assert!(l.acquire(&keys, t0).is_ok());
+        assert!(l.acquire(&keys, t0).is_ok());
+        assert_eq!(l.acquire(&keys, t0), Err(("player", 1)));
+        // other players have their own bucket
+        assert!(l.acquire(&[BucketKey::Player(2)], t0).is_ok());
+        assert!(l.acquire(&keys, t0 + Duration::from_millis(1100)).is_ok());
+    }
+
+    #[test]
+    fn refusal_consumes_nothing() {
+        let l = limiter();
+        let t0 = Instant::now();
+        let keys = [BucketKey::Global, BucketKey::Player(1)];
+        for _ in 0..2 {
+            l.acquire(&keys, t0).unwrap();
+        }
+        let before = l.buckets.lock().unwrap()[&BucketKey::Global].tokens;
+        assert!(l.acquire(&keys, t0).is_err());
+        let after = l.buckets.lock().unwrap()[&BucketKey::Global].tokens;
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn parse_bucket_config() {
+        assert_eq!(
+            BucketConfig::parse("5:10"),
+            Some(Some(BucketConfig {
+                rate: 5.0,
+                burst: 10.0
+            }))
+        );
+        assert_eq!(BucketConfig::parse("off"), Some(None));
+        assert_eq!(BucketConfig::parse("5"), None);
+        assert_eq!(BucketConfig::parse("0:10"), None);
+    }
+}
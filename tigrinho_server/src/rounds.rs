@@ -0,0 +1,272 @@
+use anyhow::Context;
+use axum::extract::State;
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::info;
+
+use tigrinho_core::{FeatureState, RoundAction, Symbol};
+use tigrinho_shared::{
+    AdminRole, ApiError, GameSessionInfo, ReportQuery, Round, RoundActionRequest, RoundQuery,
+    RoundReport, RoundStatus, SpinResponse, VoidRoundRequest,
+};
+use tigrinho_store::{GameSession, GameStore, NewSpin, SessionStep};
+
+use crate::auth::{AdminAuth, AuthPlayer};
+use crate::error::{ApiJson, ApiPath, ApiQuery, AppResult};
+use crate::games::{self, GameSlug};
+use crate::{configs, monitor, AppState};
+
+const DEFAULT_LIST_LIMIT: u32 = 50;
+const MAX_LIST_LIMIT: u32 = 500;
+const MAX_VOID_REASON_LEN: usize = 500;
+
+async fn require_round(store: &dyn GameStore, id: i64) -> AppResult<Round> {
+    store
+        .round(id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("round {id}")).into())
+}
+
+/// Symbol ids of a reel window, as spins are logged and returned.
+pub fn reel_indices(window: &[Vec<Symbol>]) -> Vec<Vec<u8>> {
+    window
+        .iter()
+        .map(|row| row.iter().map(|s| s.to_index()).collect())
+        .collect()
+}
+
+pub fn feature_info(state: FeatureState) -> tigrinho_shared::FeatureState {
+    match state {
+        FeatureState::FreeSpins { remaining, played } => {
+            tigrinho_shared::FeatureState::FreeSpins { remaining, played }
+        }
+    }
+}
+
+/// How a step moves its round's game session.
+pub fn session_step(
+    cursor: u64,
+    action: Option<RoundAction>,
+    state: Option<FeatureState>,
+) -> anyhow::Result<SessionStep> {
+    Ok(SessionStep {
+        cursor: cursor as i64,
+        action: action.map(|a| a.as_str().to_string()),
+        state: state.map(serde_json::to_value).transpose()?,
+    })
+}
+
+fn stored_feature(round_id: i64, state: serde_json::Value) -> anyhow::Result<FeatureState> {
+    serde_json::from_value(state)
+        .with_context(|| format!("round {round_id} has an unreadable feature state"))
+}
+
+fn round_action(action: tigrinho_shared::RoundAction) -> RoundAction {
+    match action {
+        tigrinho_shared::RoundAction::Spin => RoundAction::Spin,
+    }
+}
+
+/// The server seed a round draws from: its game's current seed or, once that
+/// was rotated out mid-round, the seed its rotation revealed.
+async fn round_seed(state: &AppState, session: &GameSession) -> AppResult<String> {
+    let game = games::require_game(state.store.as_ref(), &session.game).await?;
+    if game.server_seed_hash == session.server_seed_hash {
+        return Ok(state.seeds.open(&game.slug, &game.sealed_seed)?);
+    }
+    let rotation = state
+        .store
+        .seed_rotation_of(&session.game, &session.server_seed_hash)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "the seed of round {} is neither current nor revealed",
+                session.round_id
+            )
+        })?;
+    Ok(rotation.revealed_seed)
+}
+
+/// `GET /me/sessions`: the player's rounds with a feature in progress, to pick
+/// up after a reconnect.
+pub async fn route_my_sessions(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+) -> AppResult<ApiJson<Vec<GameSessionInfo>>> {
+    let mut sessions = Vec::new();
+    for session in state.store.game_sessions(player.player_id).await? {
+        let Some(feature) = session.state else {
+            continue;
+        };
+        let round = require_round(state.store.as_ref(), session.round_id).await?;
+        sessions.push(GameSessionInfo {
+            round_id: session.round_id,
+            game: session.game,
+            bet: session.bet,
+            payout: round.payout,
+            cursor: session.cursor as u64,
+            feature: feature_info(stored_feature(session.round_id, feature)?),
+            updated_at: session.updated_at,
+        });
+    }
+    Ok(ApiJson(sessions))
+}
+
+/// `POST /rounds/{id}/action`: play the next step of the player's round. The
+/// step is drawn from the round's own seed pair and nonce at the session's
+/// cursor, so it is the same whenever and however often it is asked for.
+pub async fn route_round_action(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(req): ApiJson<RoundActionRequest>,
+) -> AppResult<ApiJson<SpinResponse>> {
+    // another player's round is as good as unknown
+    let session = state
+        .store
+        .game_session(id)
+        .await?
+        .filter(|s| s.player_id == player.player_id)
+        .ok_or_else(|| ApiError::NotFound(format!("round {id}")))?;
+    let Some(feature) = &session.state else {
+        return Err(ApiError::Conflict(format!("round {id} has no feature in progress")).into());
+    };
+    let cursor = session.cursor as u64;
+    if let Some(expected) = req.cursor.filter(|&c| c != cursor) {
+        return Err(
+            ApiError::Conflict(format!("round {id} is at step {cursor}, not {expected}")).into(),
+        );
+    }
+    let feature = stored_feature(id, feature.clone())?;
+    let def = configs::config_by_hash(state.store.as_ref(), &session.config_hash).await?;
+    let server_seed = round_seed(&state, &session).await?;
+    let action = round_action(req.action);
+    let step = tigrinho_core::advance_round(
+        &def,
+        &server_seed,
+        &session.client_seed,
+        session.nonce as u64,
+        session.bet,
+        &feature,
+        cursor,
+        action,
+    )
+    .map_err(|e| ApiError::Conflict(format!("round {id}: {e}")))?;
+
+    let entry = state
+        .store
+        .record_spin(&NewSpin {
+            ts: Utc::now(),
+            game: session.game.clone(),
+            player_id: player.player_id,
+            client_seed: session.client_seed.clone(),
+            nonce: session.nonce,
+            server_seed_hash: session.server_seed_hash.clone(),
+            config_hash: session.config_hash.clone(),
+            result_reels: reel_indices(&step.reel_window),
+            bet: session.bet,
+            payout: step.payout,
+            round_id: Some(id),
+            settle: step.state.is_none(),
+            session: Some(session_step(cursor, Some(action), step.state)?),
+            idempotency_key: None,
+        })
+        .await?
+        // None when another request played this step first or the round was voided
+        .ok_or_else(|| ApiError::Conflict(format!("round {id} has moved on")))?;
+
+    state.live.publish_spin(&entry, session.bet);
+    // free spins pay without a new wager
+    state.metrics.record_spin(&session.game, 0.0, step.payout);
+    if step.state.is_none() {
+        let round = require_round(state.store.as_ref(), id).await?;
+        monitor::after_spin(
+            &state,
+            &session.game,
+            &session.config_hash,
+            &def,
+            round.bet,
+            round.payout,
+        );
+    }
+    Ok(ApiJson(crate::spin_response(&state, &entry, step.state)))
+}
+
+/// `GET /admin/rounds`: rounds matching the filters, newest first.
+pub async fn route_admin_rounds(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    ApiQuery(q): ApiQuery<RoundQuery>,
+) -> AppResult<ApiJson<Vec<Round>>> {
+    admin.require(AdminRole::Viewer)?;
+    let limit = q.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if limit == 0 || limit > MAX_LIST_LIMIT {
+        return Err(
+            ApiError::Validation(format!("limit must be between 1 and {MAX_LIST_LIMIT}")).into(),
+        );
+    }
+    Ok(ApiJson(state.store.rounds(&q, limit).await?))
+}
+
+pub async fn route_admin_round(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    ApiPath(id): ApiPath<i64>,
+) -> AppResult<ApiJson<Round>> {
+    admin.require(AdminRole::Viewer)?;
+    Ok(ApiJson(require_round(state.store.as_ref(), id).await?))
+}
+
+/// `POST /admin/rounds/{id}/void`: refund a round, open or settled. The spins
+/// stay in the log; the ledger gets the entries that cancel the round out.
+pub async fn route_admin_void_round(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(req): ApiJson<VoidRoundRequest>,
+) -> AppResult<ApiJson<Round>> {
+    admin.require(AdminRole::Operator)?;
+    let reason = req.reason.trim();
+    if reason.is_empty() || reason.len() > MAX_VOID_REASON_LEN {
+        return Err(ApiError::Validation(format!(
+            "reason must be between 1 and {MAX_VOID_REASON_LEN} characters"
+        ))
+        .into());
+    }
+    let before = require_round(state.store.as_ref(), id).await?;
+    if before.status == RoundStatus::Voided {
+        return Err(ApiError::Conflict(format!("round {id} is already voided")).into());
+    }
+    // None when another request voided it first
+    let after = state
+        .store
+        .void_round(id, Utc::now(), reason, &admin.change("rounds.void"))
+        .await?
+        .ok_or_else(|| ApiError::Conflict(format!("round {id} is already voided")))?;
+    info!(
+        "{} voided round {id} of game {}: {reason}",
+        admin.actor, after.game
+    );
+    state.metrics.record_void(&after);
+    Ok(ApiJson(after))
+}
+
+/// `GET /admin/games/{slug}/report`: the game's rounds over a period, with
+/// voided rounds counted apart from the RTP.
+pub async fn route_admin_game_report(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    GameSlug(slug): GameSlug,
+    ApiQuery(q): ApiQuery<ReportQuery>,
+) -> AppResult<ApiJson<RoundReport>> {
+    admin.require(AdminRole::Viewer)?;
+    if let (Some(from), Some(to)) = (q.from, q.to) {
+        if from >= to {
+            return Err(ApiError::Validation("from must be before to".into()).into());
+        }
+    }
+    games::require_game(state.store.as_ref(), &slug).await?;
+    Ok(ApiJson(
+        tigrinho_store::round_report(state.store.as_ref(), &slug, &q).await?,
+    ))
+}
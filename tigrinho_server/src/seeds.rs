@@ -0,0 +1,148 @@
+use axum::extract::State;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use tigrinho_shared::{AdminRole, ApiError, RotationTrigger, SeedRotation};
+use tigrinho_store::AdminChange;
+
+use crate::auth::AdminAuth;
+use crate::config::SeedRotationSettings;
+use crate::error::{ApiJson, AppResult};
+use crate::games::{self, GameSlug};
+use crate::AppState;
+
+/// Listed by `GET /seeds/revealed`.
+const REVEALED_LIST_LIMIT: u32 = 100;
+
+/// When the server rotates its seed on its own. Both limits are optional;
+/// whichever is reached first triggers a rotation.
+#[derive(Debug, Clone, Default)]
+pub struct RotationPolicy {
+    pub every_spins: Option<i64>,
+    pub every: Option<Duration>,
+}
+
+impl RotationPolicy {
+    pub fn from_config(settings: &SeedRotationSettings) -> Self {
+        Self {
+            every_spins: settings.every_spins,
+            every: settings
+                .every_hours
+                .map(|h| Duration::seconds((h * 3600.0) as i64)),
+        }
+    }
+}
+
+/// Rotate a game's seed through the store and log it. `change` is the admin
+/// who asked for it, if any.
+pub async fn rotate(
+    state: &AppState,
+    slug: &str,
+    trigger: RotationTrigger,
+    only_if_hash: Option<&str>,
+    change: Option<&AdminChange>,
+) -> anyhow::Result<Option<SeedRotation>> {
+    let rotation = state
+        .store
+        .rotate_seed(slug, trigger, only_if_hash, &state.seeds, change)
+        .await?;
+    if let Some(rotation) = &rotation {
+        info!(
+            "rotated server seed of {slug} ({}): {} -> {}",
+            trigger.as_str(),
+            rotation.previous_seed_hash,
+            rotation.new_seed_hash
+        );
+    }
+    Ok(rotation)
+}
+
+/// Called after each spin: rotate once the seed has served its quota.
+pub fn after_spin(state: &Arc<AppState>, slug: &str, seed_hash: &str, nonce: i64) {
+    let Some(limit) = state.rotation.every_spins else {
+        return;
+    };
+    if nonce < limit {
+        return;
+    }
+    let state = state.clone();
+    let slug = slug.to_string();
+    let seed_hash = seed_hash.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = rotate(
+            &state,
+            &slug,
+            RotationTrigger::SpinCount,
+            Some(&seed_hash),
+            None,
+        )
+        .await
+        {
+            error!("spin-count seed rotation failed: {e:#}");
+        }
+    });
+}
+
+/// Background task rotating the seed once it is older than the policy allows.
+pub fn spawn_scheduler(state: Arc<AppState>) {
+    let Some(every) = state.rotation.every else {
+        return;
+    };
+    let tick = std::time::Duration::from_secs(60).min(every.to_std().unwrap_or_default() / 10);
+    let tick = tick.max(std::time::Duration::from_secs(1));
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tick);
+        loop {
+            interval.tick().await;
+            if let Err(e) = rotate_if_expired(&state, every).await {
+                error!("scheduled seed rotation failed: {e:#}");
+            }
+        }
+    });
+}
+
+async fn rotate_if_expired(state: &AppState, every: Duration) -> anyhow::Result<()> {
+    for game in state.store.list_games().await? {
+        if game
+            .seed_committed_at
+            .is_some_and(|committed| Utc::now() - committed >= every)
+        {
+            rotate(
+                state,
+                &game.slug,
+                RotationTrigger::Schedule,
+                Some(&game.server_seed_hash),
+                None,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+pub async fn route_admin_rotate_seed(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    GameSlug(slug): GameSlug,
+) -> AppResult<ApiJson<SeedRotation>> {
+    admin.require(AdminRole::Operator)?;
+    let change = admin.change("seeds.rotate");
+    let rotation = rotate(&state, &slug, RotationTrigger::Admin, None, Some(&change))
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("game {slug}")))?;
+    Ok(ApiJson(rotation))
+}
+
+/// Most recent rotations of a game, newest first, with the retired seeds revealed.
+pub async fn route_revealed_seeds(
+    State(state): State<Arc<AppState>>,
+    GameSlug(slug): GameSlug,
+) -> AppResult<ApiJson<Vec<SeedRotation>>> {
+    games::require_game(state.store.as_ref(), &slug).await?;
+    let rotations = state
+        .store
+        .seed_rotations(&slug, REVEALED_LIST_LIMIT)
+        .await?;
+    Ok(ApiJson(rotations))
+}
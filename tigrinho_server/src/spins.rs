@@ -0,0 +1,154 @@
+use axum::extract::State;
+use std::sync::Arc;
+
+use tigrinho_shared::{AdminRole, ApiError, SpinPage, SpinQuery};
+use tigrinho_store::GameStore;
+
+use crate::auth::{AdminAuth, AuthPlayer};
+use crate::error::{ApiJson, ApiQuery, AppResult};
+use crate::AppState;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+fn validate(q: &SpinQuery) -> Result<u32, ApiError> {
+    let limit = q.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(ApiError::Validation(format!(
+            "limit must be between 1 and {MAX_PAGE_SIZE}"
+        )));
+    }
+    if let (Some(from), Some(to)) = (q.from, q.to) {
+        if from >= to {
+            return Err(ApiError::Validation("from must be before to".into()));
+        }
+    }
+    if let (Some(min), Some(max)) = (q.min_payout, q.max_payout) {
+        if min > max {
+            return Err(ApiError::Validation(
+                "min_payout must not exceed max_payout".into(),
+            ));
+        }
+    }
+    Ok(limit)
+}
+
+/// One page of spins matching `q`, newest first, optionally restricted to a player.
+///
+/// The cursor is the id of the last spin returned, so pages stay stable while
+/// new spins are appended.
+pub async fn query_spins(
+    store: &dyn GameStore,
+    q: &SpinQuery,
+    player_id: Option<i64>,
+) -> AppResult<SpinPage> {
+    let limit = validate(q)?;
+    // fetch one extra spin to learn whether another page exists
+    let mut spins = store.spins(q, player_id, limit + 1).await?;
+    let next_cursor = if spins.len() > limit as usize {
+        spins.truncate(limit as usize);
+        spins.last().map(|s| s.id)
+    } else {
+        None
+    };
+    Ok(SpinPage { spins, next_cursor })
+}
+
+pub async fn route_admin_spins(
+    State(state): State<Arc<AppState>>,
+    admin: AdminAuth,
+    ApiQuery(q): ApiQuery<SpinQuery>,
+) -> AppResult<ApiJson<SpinPage>> {
+    admin.require(AdminRole::Viewer)?;
+    Ok(ApiJson(query_spins(state.store.as_ref(), &q, None).await?))
+}
+
+/// The caller's own spins.
+pub async fn route_my_spins(
+    State(state): State<Arc<AppState>>,
+    player: AuthPlayer,
+    ApiQuery(q): ApiQuery<SpinQuery>,
+) -> AppResult<ApiJson<SpinPage>> {
+    Ok(ApiJson(
+        query_spins(state.store.as_ref(), &q, Some(player.player_id)).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+    use tigrinho_store::{MemoryStore, NewSpin};
+
+    fn start() -> DateTime<Utc> {
+        "2026-01-01T00:00:00Z".parse().unwrap()
+    }
+
+    async fn seeded_store() -> MemoryStore {
+        let store = MemoryStore::new();
+        for i in 0..7i64 {
+            store
+                .record_spin(&NewSpin {
+                    ts: start() + Duration::minutes(i),
+                    game: if i < 5 { "tigrinho" } else { "ox" }.into(),
+                    player_id: 1,
+                    client_seed: if i % 2 == 0 { "even" } else { "odd" }.into(),
+                    nonce: i,
+                    server_seed_hash: "h".into(),
+                    config_hash: "c".into(),
+                    result_reels: vec![vec![0]],
+                    bet: 1.0,
+                    payout: i as f64,
+                    round_id: None,
+                    settle: true,
+                    session: None,
+                    idempotency_key: None,
+                })
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn pages_walk_back_without_overlap() {
+        let store = seeded_store().await;
+        let mut q = SpinQuery {
+            limit: Some(3),
+            ..Default::default()
+        };
+        let mut seen = Vec::new();
+        loop {
+            let page = query_spins(&store, &q, None).await.unwrap();
+            seen.extend(page.spins.iter().map(|s| s.nonce));
+            match page.next_cursor {
+                Some(cursor) => q.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(seen, vec![6, 5, 4, 3, 2, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn filters_combine() {
+        let store = seeded_store().await;
+        let q = SpinQuery {
+            game: Some("tigrinho".into()),
+            client_seed: Some("even".into()),
+            min_payout: Some(1.0),
+            ..Default::default()
+        };
+        let page = query_spins(&store, &q, None).await.unwrap();
+        let nonces: Vec<i64> = page.spins.iter().map(|s| s.nonce).collect();
+        assert_eq!(nonces, vec![4, 2]);
+
+        let q = SpinQuery {
+            from: Some(start() + Duration::seconds(150)),
+            to: Some(start() + Duration::minutes(5)),
+            ..Default::default()
+        };
+        let page = query_spins(&store, &q, None).await.unwrap();
+        let nonces: Vec<i64> = page.spins.iter().map(|s| s.nonce).collect();
+        assert_eq!(nonces, vec![4, 3]);
+    }
+}
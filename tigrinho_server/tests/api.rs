@@ -0,0 +1,1028 @@
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Method, Request, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use tigrinho_server::config::{ServerConfig, DEV_API_KEY, DEV_SEED_KEK, DEV_SERVER_SEED};
+use tigrinho_shared::{AdminRole, AdminSignature};
+use tigrinho_store::{AdminChange, GameStore, MemoryStore, SeedVault};
+
+async fn app_over(store: Arc<MemoryStore>, edit: impl FnOnce(&mut ServerConfig)) -> Router {
+    let mut cfg = ServerConfig {
+        dev: true,
+        ..Default::default()
+    };
+    edit(&mut cfg);
+    cfg.finish().unwrap();
+    let vault = SeedVault::parse(&cfg.seed_kek).unwrap();
+    tigrinho_store::reseal_seeds(store.as_ref(), &vault, None)
+        .await
+        .unwrap();
+    tigrinho_server::build_router(&cfg, store).unwrap()
+}
+
+async fn app_with(edit: impl FnOnce(&mut ServerConfig)) -> Router {
+    app_over(Arc::new(MemoryStore::new()), edit).await
+}
+
+async fn app() -> Router {
+    app_with(|_| {}).await
+}
+
+async fn call(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut req = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        req = req.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+    let req = match body {
+        Some(body) => req
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string())),
+        None => req.body(Body::empty()),
+    }
+    .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    (status, json)
+}
+
+async fn login(app: &Router, username: &str) -> String {
+    let creds = json!({ "username": username, "password": "password1" });
+    let (status, _) = call(
+        app,
+        Method::POST,
+        "/auth/register",
+        None,
+        Some(creds.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, body) = call(app, Method::POST, "/auth/login", None, Some(creds)).await;
+    assert_eq!(status, StatusCode::OK);
+    body["token"].as_str().unwrap().to_string()
+}
+
+fn spin_body(client_seed: &str) -> Value {
+    json!({ "client_seed": client_seed, "bet": 1.0, "lines": 1 })
+}
+
+#[tokio::test]
+async fn games_are_listed_with_their_config() {
+    let app = app().await;
+    let (status, games) = call(&app, Method::GET, "/games", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(games[0]["slug"], "tigrinho");
+
+    let (status, config) = call(&app, Method::GET, "/games/tigrinho/config", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(config["config_hash"], games[0]["config_hash"]);
+
+    let (status, verify) = call(&app, Method::GET, "/verify", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(verify["server_seed_hash"], games[0]["server_seed_hash"]);
+}
+
+#[tokio::test]
+async fn spins_are_recorded_per_player() {
+    let app = app().await;
+    let token = login(&app, "ana").await;
+
+    let (status, spin) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&token),
+        Some(spin_body("c1")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{spin}");
+    assert_eq!(spin["nonce"], 1);
+
+    let (status, page) = call(&app, Method::GET, "/me/spins", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["spins"].as_array().unwrap().len(), 1);
+    assert_eq!(page["spins"][0]["client_seed"], "c1");
+
+    let (status, head) = call(&app, Method::GET, "/audit/chain-head", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(head["length"], 1);
+    assert_eq!(head["head_hash"], page["spins"][0]["row_hash"]);
+
+    // players cannot read everyone's history
+    let (status, _) = call(&app, Method::GET, "/spins", Some(&token), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, page) = call(&app, Method::GET, "/spins", Some(DEV_API_KEY), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["spins"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn spins_default_to_the_players_client_seed() {
+    let app = app().await;
+    let token = login(&app, "ana").await;
+    let unseeded = json!({ "bet": 1.0, "lines": 1 });
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&token),
+        Some(unseeded.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let seed = |client_seed: &str| Some(json!({ "client_seed": client_seed }));
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/me/client-seed",
+        Some(&token),
+        seed(" "),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, me) = call(
+        &app,
+        Method::POST,
+        "/me/client-seed",
+        Some(&token),
+        seed("mine"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{me}");
+    assert_eq!(me["client_seed"], "mine");
+
+    let (status, _) = call(&app, Method::POST, "/spin", Some(&token), Some(unseeded)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&token),
+        Some(spin_body("other")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, page) = call(&app, Method::GET, "/me/spins", Some(&token), None).await;
+    assert_eq!(page["spins"][0]["client_seed"], "other");
+    assert_eq!(page["spins"][1]["client_seed"], "mine");
+}
+
+#[tokio::test]
+async fn spin_responses_are_signed() {
+    let app = app().await;
+    let token = login(&app, "ana").await;
+    let (_, spin) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&token),
+        Some(spin_body("c1")),
+    )
+    .await;
+    let spin: tigrinho_shared::SpinResponse = serde_json::from_value(spin).unwrap();
+    let (status, keys) = call(&app, Method::GET, "/keys", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(keys[0]["key_id"], spin.key_id.as_str());
+    assert_eq!(keys[0]["algorithm"], "ed25519");
+    let public_key = keys[0]["public_key"].as_str().unwrap();
+
+    let mut receipt = tigrinho_core::Receipt {
+        config_hash: &spin.config_hash,
+        server_seed_hash: &spin.server_seed_hash,
+        nonce: spin.nonce,
+        reels: &spin.reels,
+        payout: spin.payout,
+        cursor: spin.cursor,
+    };
+    assert_eq!(
+        tigrinho_core::verify_receipt(&receipt, public_key, &spin.signature),
+        Ok(())
+    );
+    receipt.payout += 100.0;
+    assert!(tigrinho_core::verify_receipt(&receipt, public_key, &spin.signature).is_err());
+}
+
+#[tokio::test]
+async fn committed_spins_have_inclusion_proofs() {
+    let store = Arc::new(MemoryStore::new());
+    let app = app_over(store.clone(), |_| {}).await;
+    let token = login(&app, "ana").await;
+    for seed in ["c1", "c2", "c3"] {
+        let (status, _) = call(
+            &app,
+            Method::POST,
+            "/spin",
+            Some(&token),
+            Some(spin_body(seed)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _) = call(&app, Method::GET, "/spins/2/proof", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    tigrinho_store::commit_merkle_root(store.as_ref())
+        .await
+        .unwrap()
+        .unwrap();
+    let (status, roots) = call(&app, Method::GET, "/audit/roots", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(roots.as_array().unwrap().len(), 1);
+    let (status, proof) = call(&app, Method::GET, "/spins/2/proof", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let proof: tigrinho_shared::SpinProof = serde_json::from_value(proof).unwrap();
+    assert_eq!(proof.root_id, roots[0]["id"]);
+    assert!(tigrinho_core::verify_inclusion(
+        &proof.row_hash,
+        proof.leaf_index,
+        roots[0]["leaf_count"].as_u64().unwrap(),
+        &proof.siblings,
+        roots[0]["root"].as_str().unwrap()
+    ));
+
+    let (status, body) = call(&app, Method::GET, "/spins/x/proof", None, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_request");
+}
+
+#[tokio::test]
+async fn errors_use_the_envelope() {
+    let app = app().await;
+    let (status, body) = call(&app, Method::POST, "/spin", None, Some(spin_body("c"))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "unauthorized");
+    assert!(body["request_id"].is_string());
+
+    let (status, body) = call(&app, Method::GET, "/nope", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+
+    let (status, body) = call(&app, Method::GET, "/games/nope/config", None, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+
+    // a plain GET of the live feed is not a WebSocket handshake
+    let (status, body) = call(&app, Method::GET, "/ws?channel=public", None, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_request");
+
+    let req = Request::builder()
+        .method(Method::DELETE)
+        .uri("/spin")
+        .body(Body::empty())
+        .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()[header::ALLOW], "POST");
+    let bytes = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["code"], "method_not_allowed");
+    assert!(body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn admin_routes_need_the_api_key() {
+    let app = app().await;
+    let (status, _) = call(&app, Method::GET, "/admin/rtp-alerts", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(&app, Method::GET, "/admin/rtp-alerts", Some("wrong"), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, alerts) = call(
+        &app,
+        Method::GET,
+        "/admin/rtp-alerts",
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(alerts, json!([]));
+
+    let (status, rotation) = call(
+        &app,
+        Method::POST,
+        "/admin/seeds/rotate",
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{rotation}");
+    assert_eq!(rotation["revealed_seed"], DEV_SERVER_SEED);
+    let (_, revealed) = call(&app, Method::GET, "/seeds/revealed", None, None).await;
+    assert_eq!(revealed.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn admin_keys_are_limited_to_their_role_and_audited() {
+    let store = Arc::new(MemoryStore::new());
+    let vault = SeedVault::parse(DEV_SEED_KEK).unwrap();
+    let mut keys = Vec::new();
+    for (name, role) in [("watch", AdminRole::Viewer), ("ops", AdminRole::Operator)] {
+        let (new, key) = tigrinho_store::generate_admin_key(name, role, None, &vault);
+        let change = AdminChange::new("test", "admin_keys.create");
+        store
+            .create_admin_key(&new, &change)
+            .await
+            .unwrap()
+            .unwrap();
+        keys.push(key);
+    }
+    let (viewer, operator) = (keys[0].as_str(), keys[1].as_str());
+    let app = app_over(store.clone(), |_| {}).await;
+    let limits = json!({ "min_bet": 0.5, "max_bet": 50.0 });
+
+    let (status, _) = call(&app, Method::GET, "/admin/rtp-alerts", Some(viewer), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = call(&app, Method::GET, "/metrics", Some(viewer), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/admin/games/tigrinho/limits",
+        Some(viewer),
+        Some(limits.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    let wrong_secret = format!(
+        "{}.{}",
+        operator.split('.').next().unwrap(),
+        "00".repeat(32)
+    );
+    let (status, _) = call(
+        &app,
+        Method::GET,
+        "/admin/rtp-alerts",
+        Some(&wrong_secret),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/admin/games/tigrinho/limits",
+        Some(operator),
+        Some(limits),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let (status, _) = call(&app, Method::GET, "/admin/audit", Some(operator), None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, audit) = call(&app, Method::GET, "/admin/audit", Some(DEV_API_KEY), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let entry = &audit[0];
+    assert_eq!(entry["actor"], "ops");
+    assert_eq!(entry["action"], "games.set_limits");
+    assert_eq!(entry["target"], "tigrinho");
+    assert_eq!(entry["after"]["min_bet"], 0.5);
+    assert_ne!(entry["before"]["min_bet"], 0.5);
+
+    let change = AdminChange::new("test", "admin_keys.revoke");
+    store
+        .revoke_admin_key("ops", chrono::Utc::now(), &change)
+        .await
+        .unwrap();
+    let (status, _) = call(&app, Method::GET, "/admin/rtp-alerts", Some(operator), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn params_apply_only_what_the_server_previewed() {
+    let app = app().await;
+    let admin = |uri: &'static str, body: Value| {
+        let app = app.clone();
+        async move { call(&app, Method::POST, uri, Some(DEV_API_KEY), Some(body)).await }
+    };
+    let (status, _) = admin(
+        "/admin/games",
+        json!({ "slug": "ox", "name": "Ox", "min_bet": 0.1, "max_bet": 10.0 }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, config) = call(&app, Method::GET, "/games/tigrinho/config", None, None).await;
+    let proposal = json!({ "rtp_target": 0.9, "paytable": config["paytable"] });
+    let (status, preview) = admin("/admin/games/tigrinho/params/preview", proposal.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{preview}");
+
+    let apply = |token: &Value, rtp_target: f64| {
+        let mut body = proposal.clone();
+        body["preview_token"] = token.clone();
+        body["rtp_target"] = json!(rtp_target);
+        body
+    };
+    // the config hash alone is not a preview
+    let (status, _) = admin(
+        "/admin/games/tigrinho/params",
+        apply(&preview["preview_hash"], 0.9),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    // nor is a preview of another proposal or of another game
+    let token = &preview["preview_token"];
+    let (status, _) = admin("/admin/games/tigrinho/params", apply(token, 0.8)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = admin("/admin/games/ox/params", apply(token, 0.9)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, set) = admin("/admin/games/tigrinho/params", apply(token, 0.9)).await;
+    assert_eq!(status, StatusCode::OK, "{set}");
+    assert_eq!(set["config_hash"], preview["preview_hash"]);
+}
+
+#[tokio::test]
+async fn voided_rounds_are_refunded_and_reported_apart() {
+    let store = Arc::new(MemoryStore::new());
+    let app = app_over(store.clone(), |_| {}).await;
+    let token = login(&app, "ana").await;
+    let mut rounds = Vec::new();
+    for seed in ["c1", "c2"] {
+        let (status, spin) = call(
+            &app,
+            Method::POST,
+            "/spin",
+            Some(&token),
+            Some(spin_body(seed)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{spin}");
+        rounds.push(spin["round_id"].as_i64().unwrap());
+    }
+    let (status, round) = call(
+        &app,
+        Method::GET,
+        &format!("/admin/rounds/{}", rounds[0]),
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(round["status"], "settled");
+
+    let void = |id: i64, reason: &str| {
+        let (app, uri) = (app.clone(), format!("/admin/rounds/{id}/void"));
+        let body = json!({ "reason": reason });
+        async move { call(&app, Method::POST, &uri, Some(DEV_API_KEY), Some(body)).await }
+    };
+    let (status, _) = void(rounds[0], " ").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, voided) = void(rounds[0], "reel malfunction").await;
+    assert_eq!(status, StatusCode::OK, "{voided}");
+    assert_eq!(voided["status"], "voided");
+    assert_eq!(void(rounds[0], "again").await.0, StatusCode::CONFLICT);
+    assert_eq!(
+        void(rounds[1] + 100, "unknown").await.0,
+        StatusCode::NOT_FOUND
+    );
+
+    // the spin stays in the log; the ledger cancels the round out
+    let (_, page) = call(&app, Method::GET, "/me/spins", Some(&token), None).await;
+    assert_eq!(page["spins"].as_array().unwrap().len(), 2);
+    let (_, me) = call(&app, Method::GET, "/me", Some(&token), None).await;
+    let ledger = store.ledger(me["id"].as_i64().unwrap(), 10).await.unwrap();
+    let net: f64 = ledger
+        .iter()
+        .filter(|e| e.round_id == Some(rounds[0]))
+        .map(|e| e.amount)
+        .sum();
+    assert_eq!(net, 0.0);
+
+    let (status, report) = call(
+        &app,
+        Method::GET,
+        "/admin/games/tigrinho/report",
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["settled"]["rounds"], 1);
+    assert_eq!(report["voided"]["rounds"], 1);
+    assert_eq!(report["voided"]["wagered"], 1.0);
+
+    let (_, audit) = call(&app, Method::GET, "/admin/audit", Some(DEV_API_KEY), None).await;
+    assert_eq!(audit[0]["action"], "rounds.void");
+    assert_eq!(audit[0]["target"], format!("rounds/{}", rounds[0]));
+    assert_eq!(audit[0]["before"]["status"], "settled");
+    assert_eq!(audit[0]["after"]["void_reason"], "reel malfunction");
+}
+
+async fn call_signed(app: &Router, uri: &str, body: &str, signed: &AdminSignature) -> StatusCode {
+    let mut req = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json");
+    for (name, value) in signed.headers() {
+        req = req.header(name, value);
+    }
+    let req = req.body(Body::from(body.to_string())).unwrap();
+    app.clone().oneshot(req).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn signed_admin_requests_cannot_be_replayed() {
+    let store = Arc::new(MemoryStore::new());
+    let vault = SeedVault::parse(DEV_SEED_KEK).unwrap();
+    let (new, key) = tigrinho_store::generate_admin_key("ops", AdminRole::Operator, None, &vault);
+    let change = AdminChange::new("test", "admin_keys.create");
+    store
+        .create_admin_key(&new, &change)
+        .await
+        .unwrap()
+        .unwrap();
+    let app = app_over(store, |_| {}).await;
+    let uri = "/admin/games/tigrinho/limits";
+    let body = json!({ "min_bet": 0.5, "max_bet": 50.0 }).to_string();
+    let now = chrono::Utc::now().timestamp();
+    let sign = |body: &str, ts: i64, nonce: &str| {
+        AdminSignature::sign(&key, "POST", uri, body.as_bytes(), ts, nonce).unwrap()
+    };
+
+    let signed = sign(&body, now, "n1");
+    assert_eq!(call_signed(&app, uri, &body, &signed).await, StatusCode::OK);
+    // the same request captured and sent again
+    assert_eq!(
+        call_signed(&app, uri, &body, &signed).await,
+        StatusCode::UNAUTHORIZED
+    );
+    let tampered = json!({ "min_bet": 0.1, "max_bet": 5000.0 }).to_string();
+    assert_eq!(
+        call_signed(&app, uri, &tampered, &sign(&body, now, "n2")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        call_signed(&app, uri, &body, &sign(&body, now - 3600, "n3")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        call_signed(&app, uri, &body, &sign(&body, now, "n4")).await,
+        StatusCode::OK
+    );
+
+    let (_, audit) = call(&app, Method::GET, "/admin/audit", Some(DEV_API_KEY), None).await;
+    // the key's creation, then the two changes made with it
+    assert_eq!(audit.as_array().unwrap().len(), 3);
+    assert_eq!(audit[0]["actor"], "ops");
+    assert_eq!(audit[2]["action"], "admin_keys.create");
+}
+
+#[tokio::test]
+async fn spins_are_rate_limited_per_player() {
+    let app = app_with(|cfg| cfg.rate_limits.player = "1:2".into()).await;
+    let token = login(&app, "ana").await;
+    for i in 0..2 {
+        let (status, _) = call(
+            &app,
+            Method::POST,
+            "/spin",
+            Some(&token),
+            Some(spin_body(&format!("c{i}"))),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&token),
+        Some(spin_body("c")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "rate_limited");
+
+    let (status, _) = call(&app, Method::GET, "/metrics", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(&app, Method::GET, "/metrics", Some(&token), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(&app, Method::GET, "/metrics", Some(DEV_API_KEY), None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn expired_sessions_do_not_spend_a_players_rate_limit() {
+    use hmac::{Hmac, Mac};
+
+    let store = Arc::new(MemoryStore::new());
+    let app = app_over(store.clone(), |cfg| {
+        cfg.session_secret = "test-session-secret".into();
+        cfg.rate_limits.player = "0.01:1".into();
+    })
+    .await;
+    let token = login(&app, "ana").await;
+    let (_, me) = call(&app, Method::GET, "/me", Some(&token), None).await;
+
+    // a well-signed token whose session has expired in the store
+    let now = chrono::Utc::now();
+    store
+        .create_session(&tigrinho_store::NewSession {
+            id: "stale".into(),
+            player_id: me["id"].as_i64().unwrap(),
+            issued_at: now - chrono::Duration::hours(2),
+            expires_at: now - chrono::Duration::hours(1),
+        })
+        .await
+        .unwrap();
+    let payload = format!("stale.{}", (now + chrono::Duration::hours(1)).timestamp());
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(b"test-session-secret").unwrap();
+    mac.update(payload.as_bytes());
+    let stale = format!("{payload}.{}", hex::encode(mac.finalize().into_bytes()));
+
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&stale),
+        Some(spin_body("c")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/spin",
+        Some(&token),
+        Some(spin_body("c")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn live_tickets_need_the_channels_credentials() {
+    let app = app().await;
+    let token = login(&app, "ana").await;
+
+    let (status, ticket) = call(&app, Method::POST, "/me/live-ticket", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ticket["channel"], "player");
+    assert_eq!(ticket["ticket"].as_str().unwrap().len(), 64);
+    let (status, _) = call(&app, Method::POST, "/me/live-ticket", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = call(&app, Method::POST, "/admin/live-ticket", Some(&token), None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, ticket) = call(
+        &app,
+        Method::POST,
+        "/admin/live-ticket",
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ticket["channel"], "admin");
+}
+
+#[tokio::test]
+async fn free_spins_resume_and_replay_from_the_seeds() {
+    let store = Arc::new(MemoryStore::new());
+    let app = app_over(store.clone(), |_| {}).await;
+    let token = login(&app, "ana").await;
+
+    // any A in the window awards three free spins at double pay
+    let (_, config) = call(&app, Method::GET, "/games/tigrinho/config", None, None).await;
+    let mut proposal = json!({
+        "rtp_target": config["rtp_target"],
+        "paytable": config["paytable"],
+        "free_spins": { "trigger_symbol": 0, "trigger_count": 1, "spins": 3, "multiplier": 2.0 },
+    });
+    let (status, preview) = call(
+        &app,
+        Method::POST,
+        "/admin/games/tigrinho/params/preview",
+        Some(DEV_API_KEY),
+        Some(proposal.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{preview}");
+    proposal["preview_token"] = preview["preview_token"].clone();
+    let (status, set) = call(
+        &app,
+        Method::POST,
+        "/admin/games/tigrinho/params",
+        Some(DEV_API_KEY),
+        Some(proposal),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{set}");
+
+    let (mut opening, mut client_seed) = (Value::Null, String::new());
+    for i in 0..50 {
+        client_seed = format!("c{i}");
+        let (status, spin) = call(
+            &app,
+            Method::POST,
+            "/spin",
+            Some(&token),
+            Some(spin_body(&client_seed)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{spin}");
+        if !spin["feature"].is_null() {
+            opening = spin;
+            break;
+        }
+    }
+    assert_eq!(opening["feature"]["remaining"], 3, "{opening}");
+    let id = opening["round_id"].as_i64().unwrap();
+    let action_uri = format!("/rounds/{id}/action");
+
+    // after a reconnect the player finds the round where they left it
+    let (status, sessions) = call(&app, Method::GET, "/me/sessions", Some(&token), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sessions[0]["round_id"], id);
+    assert_eq!(sessions[0]["cursor"], 1);
+
+    let stale = json!({ "action": "spin", "cursor": 2 });
+    let (status, _) = call(&app, Method::POST, &action_uri, Some(&token), Some(stale)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let other = login(&app, "bia").await;
+    let next = json!({ "action": "spin" });
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        &action_uri,
+        Some(&other),
+        Some(next.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let mut steps = vec![opening.clone()];
+    for cursor in 1..=3 {
+        let body = json!({ "action": "spin", "cursor": cursor });
+        let (status, step) = call(&app, Method::POST, &action_uri, Some(&token), Some(body)).await;
+        assert_eq!(status, StatusCode::OK, "{step}");
+        assert_eq!(step["cursor"], cursor);
+        steps.push(step);
+    }
+    assert!(steps[3]["feature"].is_null());
+    let (status, _) = call(&app, Method::POST, &action_uri, Some(&token), Some(next)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (_, sessions) = call(&app, Method::GET, "/me/sessions", Some(&token), None).await;
+    assert_eq!(sessions.as_array().unwrap().len(), 0);
+
+    let total: f64 = steps.iter().map(|s| s["payout"].as_f64().unwrap()).sum();
+    let (_, round) = call(
+        &app,
+        Method::GET,
+        &format!("/admin/rounds/{id}"),
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(round["status"], "settled");
+    assert_eq!(round["payout"].as_f64().unwrap(), total);
+
+    let uri = format!("/me/spins?round_id={id}");
+    let (_, page) = call(&app, Method::GET, &uri, Some(&token), None).await;
+    assert_eq!(page["spins"].as_array().unwrap().len(), 4);
+
+    // the whole round replays from the seed pair, the nonce and the actions
+    let hash = opening["config_hash"].as_str().unwrap();
+    let def = store.config(hash).await.unwrap().unwrap();
+    let replayed = tigrinho_core::replay_round(
+        &def,
+        DEV_SERVER_SEED,
+        &client_seed,
+        opening["nonce"].as_u64().unwrap(),
+        1.0,
+        &[tigrinho_core::RoundAction::Spin; 3],
+    )
+    .unwrap();
+    for (step, response) in replayed.iter().zip(&steps) {
+        let reels: Vec<Vec<u8>> = step
+            .reel_window
+            .iter()
+            .map(|row| row.iter().map(|s| s.to_index()).collect())
+            .collect();
+        assert_eq!(json!(reels), response["reels"]);
+        assert_eq!(json!(step.payout), response["payout"]);
+    }
+}
+
+async fn spin_with_key(
+    app: &Router,
+    token: &str,
+    key: &str,
+    body: &Value,
+) -> (StatusCode, bool, Value) {
+    let req = Request::builder()
+        .method(Method::POST)
+        .uri("/spin")
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("Idempotency-Key", key)
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    let status = resp.status();
+    let replayed = resp.headers().get("idempotent-replayed").is_some();
+    let bytes = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        replayed,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+/// The hash `/spin` binds an idempotency key to.
+fn spin_request_hash(body: &Value) -> String {
+    let req: tigrinho_shared::SpinRequest = serde_json::from_value(body.clone()).unwrap();
+    tigrinho_core::derive_hash_hex(&serde_json::to_vec(&("tigrinho", &req)).unwrap())
+}
+
+#[tokio::test]
+async fn idempotent_spins_replay_and_reject_conflicts() {
+    let store = Arc::new(MemoryStore::new());
+    let app = app_over(store.clone(), |cfg| cfg.idempotency_ttl_secs = 1).await;
+    let token = login(&app, "ana").await;
+    let body = spin_body("c1");
+
+    let (status, replayed, first) = spin_with_key(&app, &token, "k1", &body).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false), "{first}");
+    let (status, replayed, again) = spin_with_key(&app, &token, "k1", &body).await;
+    assert_eq!((status, replayed), (StatusCode::OK, true));
+    assert_eq!(again, first);
+    let (status, _, error) = spin_with_key(&app, &token, "k1", &spin_body("c2")).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{error}");
+
+    // a key still being worked on is refused rather than spun twice
+    let (_, me) = call(&app, Method::GET, "/me", Some(&token), None).await;
+    let player_id = me["id"].as_i64().unwrap();
+    let ttl = chrono::Duration::hours(1);
+    let hash = spin_request_hash(&body);
+    assert!(store
+        .claim_idempotency_key(player_id, "k2", &hash, ttl)
+        .await
+        .unwrap()
+        .is_none());
+    let (status, _, _) = spin_with_key(&app, &token, "k2", &body).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // once its spin is recorded, the response is rebuilt from the spin even
+    // though the first request never stored it
+    let game = store.game("tigrinho").await.unwrap().unwrap();
+    let spin = tigrinho_store::NewSpin {
+        ts: chrono::Utc::now(),
+        game: game.slug.clone(),
+        player_id,
+        client_seed: "c1".into(),
+        nonce: game.nonce + 1,
+        server_seed_hash: game.server_seed_hash.clone(),
+        config_hash: game.active_config_hash.clone().unwrap(),
+        result_reels: vec![vec![1, 2, 3]; 3],
+        bet: 1.0,
+        payout: 0.0,
+        round_id: None,
+        settle: true,
+        session: None,
+        idempotency_key: Some("k2".into()),
+    };
+    let recorded = store.record_spin(&spin).await.unwrap().unwrap();
+    let (status, replayed, rebuilt) = spin_with_key(&app, &token, "k2", &body).await;
+    assert_eq!((status, replayed), (StatusCode::OK, true), "{rebuilt}");
+    assert_eq!(rebuilt["nonce"], recorded.nonce);
+    assert_eq!(rebuilt["round_id"], recorded.round_id.unwrap());
+    assert_eq!(spin_with_key(&app, &token, "k2", &body).await.2, rebuilt);
+
+    // expired keys spin afresh
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let (status, replayed, fresh) = spin_with_key(&app, &token, "k1", &body).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+    assert_ne!(fresh["nonce"], first["nonce"]);
+}
+
+#[tokio::test]
+async fn idempotent_spins_replay_after_the_game_changes() {
+    let app = app().await;
+    let token = login(&app, "ana").await;
+    let body = spin_body("c1");
+    let (status, _, first) = spin_with_key(&app, &token, "k1", &body).await;
+    assert_eq!(status, StatusCode::OK, "{first}");
+
+    let limits = json!({ "min_bet": 5.0, "max_bet": 50.0 });
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/admin/games/tigrinho/limits",
+        Some(DEV_API_KEY),
+        Some(limits),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, replayed, again) = spin_with_key(&app, &token, "k1", &body).await;
+    assert_eq!((status, replayed), (StatusCode::OK, true), "{again}");
+    assert_eq!(again, first);
+    let (status, _, error) = spin_with_key(&app, &token, "k2", &body).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{error}");
+    // the refused request did not use up its key
+    let bigger = json!({ "client_seed": "c1", "bet": 5.0, "lines": 1 });
+    let (status, replayed, _) = spin_with_key(&app, &token, "k2", &bigger).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+}
+
+/// Poll the game's revealed seeds until `done` holds for them, for up to 5s.
+async fn revealed_until(app: &Router, done: impl Fn(&[Value]) -> bool) -> Vec<Value> {
+    for _ in 0..100 {
+        let (_, rotations) = call(app, Method::GET, "/seeds/revealed", None, None).await;
+        let rotations = rotations.as_array().cloned().unwrap_or_default();
+        if done(&rotations) {
+            return rotations;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("seeds were not rotated in time");
+}
+
+#[tokio::test]
+async fn admins_rotate_seeds_and_reveal_the_old_one() {
+    let app = app().await;
+    let (_, before) = call(&app, Method::GET, "/verify", None, None).await;
+    let (status, rotation) = call(
+        &app,
+        Method::POST,
+        "/admin/seeds/rotate",
+        Some(DEV_API_KEY),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{rotation}");
+    assert_eq!(rotation["trigger"], "admin");
+    assert_eq!(rotation["revealed_seed"], DEV_SERVER_SEED);
+    assert_eq!(
+        rotation["previous_seed_hash"],
+        tigrinho_core::derive_hash_hex(DEV_SERVER_SEED.as_bytes())
+    );
+    assert_eq!(rotation["previous_seed_hash"], before["server_seed_hash"]);
+    let (_, after) = call(&app, Method::GET, "/verify", None, None).await;
+    assert_eq!(after["server_seed_hash"], rotation["new_seed_hash"]);
+    let revealed = revealed_until(&app, |r| !r.is_empty()).await;
+    assert_eq!(revealed[0]["id"], rotation["id"]);
+}
+
+#[tokio::test]
+async fn seeds_rotate_once_their_spin_quota_is_served() {
+    let store = Arc::new(MemoryStore::new());
+    let app = app_over(store.clone(), |cfg| cfg.seed_rotation.every_spins = Some(2)).await;
+    let token = login(&app, "ana").await;
+    let (_, verify) = call(&app, Method::GET, "/verify", None, None).await;
+    let first_hash = verify["server_seed_hash"].as_str().unwrap().to_string();
+    for seed in ["c1", "c2"] {
+        let (status, _) = call(
+            &app,
+            Method::POST,
+            "/spin",
+            Some(&token),
+            Some(spin_body(seed)),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let revealed = revealed_until(&app, |r| !r.is_empty()).await;
+    assert_eq!(revealed.len(), 1);
+    assert_eq!(revealed[0]["trigger"], "spin_count");
+    assert_eq!(revealed[0]["previous_seed_hash"], first_hash);
+    assert_eq!(revealed[0]["final_nonce"], 2);
+
+    // spins racing past the quota on the same seed rotate it once: the
+    // rotation only applies while the game still has the seed they spun with
+    let vault = SeedVault::parse(DEV_SEED_KEK).unwrap();
+    let hash = revealed[0]["new_seed_hash"].as_str().unwrap();
+    let rotate = || {
+        store.rotate_seed(
+            "tigrinho",
+            tigrinho_shared::RotationTrigger::SpinCount,
+            Some(hash),
+            &vault,
+            None,
+        )
+    };
+    let (a, b) = tokio::join!(rotate(), rotate());
+    let rotated = [a.unwrap(), b.unwrap()];
+    assert_eq!(rotated.iter().filter(|r| r.is_some()).count(), 1);
+    assert!(rotate().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn seeds_rotate_on_schedule() {
+    // a one-second policy makes the scheduler check every second
+    let app = app_with(|cfg| cfg.seed_rotation.every_hours = Some(1.0 / 3600.0)).await;
+    let revealed = revealed_until(&app, |r| !r.is_empty()).await;
+    assert_eq!(revealed.last().unwrap()["trigger"], "schedule");
+    assert_eq!(revealed.last().unwrap()["revealed_seed"], DEV_SERVER_SEED);
+}
@@ -1,9 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+pub mod signing;
+pub use signing::AdminSignature;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SpinRequest {
-    pub client_seed: String,
+    /// Defaults to the player's client seed (see `POST /me/client-seed`).
+    #[serde(default)]
+    pub client_seed: Option<String>,
     pub bet: f64,
     pub lines: u32,
 }
@@ -11,20 +16,222 @@ pub struct SpinRequest {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SpinResponse {
     pub server_seed_hash: String,
+    /// Hash of the game configuration the spin was evaluated with (see `GET /configs/{hash}`).
+    pub config_hash: String,
     pub nonce: u64,
     pub reels: Vec<Vec<u8>>, // indices of symbols
     pub payout: f64,
+    /// Key at `GET /keys` that signed this response.
+    #[serde(default)]
+    pub key_id: String,
+    /// Hex Ed25519 signature over the fields above and `cursor`
+    /// (`tigrinho_core::Receipt`).
+    #[serde(default)]
+    pub signature: String,
+    /// Round the spin was played in; admins void rounds, not single spins.
+    #[serde(default)]
+    pub round_id: Option<i64>,
+    /// Step of the round's feature this spin played; absent for the spin that
+    /// opens the round.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u64>,
+    /// The round's feature after this spin. While present the round stays
+    /// open; continue it with `POST /rounds/{id}/action`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature: Option<FeatureState>,
+}
+
+/// Where an in-progress feature of a round stands (`tigrinho_core::FeatureState`).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "feature", rename_all = "snake_case")]
+pub enum FeatureState {
+    FreeSpins { remaining: u32, played: u32 },
+}
+
+/// What to do next in a round's feature (`tigrinho_core::RoundAction`).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundAction {
+    /// Play the next free spin.
+    Spin,
+}
+
+/// Body of `POST /rounds/{id}/action`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoundActionRequest {
+    pub action: RoundAction,
+    /// The step the client means to play, from its last response. When given,
+    /// the request is refused if the round is elsewhere, so a retried request
+    /// cannot play a step twice.
+    #[serde(default)]
+    pub cursor: Option<u64>,
+}
+
+/// A round whose feature is still in progress (`GET /me/sessions`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GameSessionInfo {
+    pub round_id: i64,
+    pub game: String,
+    pub bet: f64,
+    /// Paid so far in the round.
+    pub payout: f64,
+    /// The next step to play.
+    pub cursor: u64,
+    pub feature: FeatureState,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Free spins awarded for `trigger_count` or more `trigger_symbol`s anywhere
+/// in the window of a round's opening spin (`tigrinho_core::FreeSpins`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FreeSpins {
+    pub trigger_symbol: u8,
+    pub trigger_count: u8,
+    pub spins: u32,
+    /// Free spins pay the paytable times this.
+    pub multiplier: f64,
+}
+
+/// A receipt signing key, listed at `GET /keys`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReceiptKey {
+    pub key_id: String,
+    /// Always `ed25519`.
+    pub algorithm: String,
+    /// Hex public key.
+    pub public_key: String,
+    /// Whether the server still signs with it; retired keys stay listed so
+    /// older receipts remain verifiable.
+    pub active: bool,
+}
+
+/// Catalogue entry of a game hosted by the server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameSummary {
+    pub slug: String,
+    pub name: String,
+    pub server_seed_hash: String,
+    pub config_hash: String,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    /// Spins are refused with `game_suspended` until an admin resumes the game.
+    pub suspended: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SymbolInfo {
+    pub id: u8,
+    pub name: String,
+    /// Substitutes for any symbol on a payline.
+    pub wild: bool,
+}
+
+/// Public rules of a game: enough to re-evaluate any spin and audit the RTP.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameConfigResponse {
+    pub slug: String,
+    pub name: String,
+    pub config_hash: String,
+    pub server_seed_hash: String,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    pub symbols: Vec<SymbolInfo>,
+    /// Reel strips as symbol ids; reel `c` stops at an index and shows `rows` symbols from there, wrapping.
+    pub reels: Vec<Vec<u8>>,
+    pub rows: usize,
+    /// Each payline as `[row, col]` window positions.
+    pub paylines: Vec<Vec<[usize; 2]>>,
+    pub paytable: Vec<PaytableEntry>,
+    pub rtp_target: f64,
+    #[serde(default)]
+    pub free_spins: Option<FreeSpins>,
+    /// Theoretical figures of the active config, per unit bet.
+    pub math: MathReport,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateGameRequest {
+    /// Lowercase letters, digits and `-`, at most 32 characters.
+    pub slug: String,
+    pub name: String,
+    pub min_bet: f64,
+    pub max_bet: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameLimitsRequest {
+    pub min_bet: f64,
+    pub max_bet: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VerifyResponse {
     pub server_seed_hash: String,
+    /// Hash of the currently active game configuration.
+    pub config_hash: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AdminSetParamsRequest {
     pub rtp_target: f64,
     pub paytable: Vec<PaytableEntry>,
+    /// The free spins feature; absent for a game without one.
+    #[serde(default)]
+    pub free_spins: Option<FreeSpins>,
+    /// `preview_token` returned by `POST /admin/params/preview` for this exact
+    /// proposal and game.
+    pub preview_token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminParamsPreviewRequest {
+    pub rtp_target: f64,
+    pub paytable: Vec<PaytableEntry>,
+    /// The free spins feature; absent for a game without one.
+    #[serde(default)]
+    pub free_spins: Option<FreeSpins>,
+    /// Force a Monte Carlo run of this many rounds instead of the exact calculation.
+    #[serde(default)]
+    pub simulate_rounds: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MathMethod {
+    Exact,
+    Simulated,
+}
+
+/// Per-unit-bet statistics of a game configuration.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MathReport {
+    pub method: MathMethod,
+    pub rounds: u64,
+    pub rtp: f64,
+    pub hit_frequency: f64,
+    pub variance: f64,
+    pub std_dev: f64,
+    pub max_multiplier: f64,
+    pub rtp_std_error: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminParamsPreviewResponse {
+    /// Config hash of the proposal.
+    pub preview_hash: String,
+    /// Pass back as `preview_token` to apply this exact proposal to this game,
+    /// before `preview_expires_at`. Only the server can issue it.
+    pub preview_token: String,
+    pub preview_expires_at: DateTime<Utc>,
+    pub rtp_target: f64,
+    pub report: MathReport,
+    /// Non-fatal findings, e.g. the computed RTP being far from `rtp_target`.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdminSetParamsResponse {
+    pub config_hash: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -34,23 +241,482 @@ pub struct PaytableEntry {
     pub payout_multiplier: f64,
 }
 
+/// Why a server seed was rotated.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationTrigger {
+    /// `POST /admin/seeds/rotate`
+    Admin,
+    /// The seed reached the configured number of spins.
+    SpinCount,
+    /// The seed reached the configured age.
+    Schedule,
+    /// `tigrinho_cli rotate-seed`
+    Cli,
+}
+
+impl RotationTrigger {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RotationTrigger::Admin => "admin",
+            RotationTrigger::SpinCount => "spin_count",
+            RotationTrigger::Schedule => "schedule",
+            RotationTrigger::Cli => "cli",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "admin" => Some(RotationTrigger::Admin),
+            "spin_count" => Some(RotationTrigger::SpinCount),
+            "schedule" => Some(RotationTrigger::Schedule),
+            "cli" => Some(RotationTrigger::Cli),
+            _ => None,
+        }
+    }
+}
+
+/// What an admin API key may do; each role includes the ones before it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// Read the spin log, alerts and live feed.
+    Viewer,
+    /// Rotate seeds, resume games and change bet limits.
+    Operator,
+    /// Create games, change their math and read the admin audit trail.
+    Owner,
+}
+
+impl AdminRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AdminRole::Viewer => "viewer",
+            AdminRole::Operator => "operator",
+            AdminRole::Owner => "owner",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "viewer" => Some(AdminRole::Viewer),
+            "operator" => Some(AdminRole::Operator),
+            "owner" => Some(AdminRole::Owner),
+            _ => None,
+        }
+    }
+}
+
+/// One change made by an admin, with snapshots of what it changed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AdminAuditEntry {
+    pub id: i64,
+    pub ts: DateTime<Utc>,
+    /// Name of the admin key, `api_key` for the configured key or `cli`.
+    pub actor: String,
+    /// e.g. `games.set_limits`
+    pub action: String,
+    /// What was acted on, e.g. a game slug.
+    pub target: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// A completed seed rotation. `revealed_seed` is the retired seed, so every
+/// spin with `previous_seed_hash` and nonce up to `final_nonce` can be verified.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SeedRotation {
+    pub id: i64,
+    pub game: String,
+    pub ts: DateTime<Utc>,
+    pub trigger: RotationTrigger,
+    pub previous_seed_hash: String,
+    pub revealed_seed: String,
+    pub final_nonce: i64,
+    pub new_seed_hash: String,
+}
+
+/// Raised when a game's observed RTP is statistically inconsistent with its
+/// theoretical RTP.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RtpAlert {
+    pub id: i64,
+    pub game: String,
+    pub ts: DateTime<Utc>,
+    pub config_hash: String,
+    /// Spins observed under `config_hash` when the alert fired.
+    pub spins: i64,
+    pub observed_rtp: f64,
+    pub theoretical_rtp: f64,
+    /// Deviation in standard errors of the mean per-unit-bet return.
+    pub z_score: f64,
+    /// Whether the game was suspended as a result.
+    pub suspended: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionResponse {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlayerInfo {
+    pub id: i64,
+    pub username: String,
+    pub created_at: DateTime<Utc>,
+    /// Client seed of the player's spins that name none; `None` until set.
+    #[serde(default)]
+    pub client_seed: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SetClientSeedRequest {
+    pub client_seed: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SpinLogEntry {
     pub id: i64,
     pub ts: DateTime<Utc>,
+    pub game: String,
+    /// `None` for spins recorded before player accounts existed.
+    pub player_id: Option<i64>,
     pub client_seed: String,
     pub nonce: i64,
     pub server_seed_hash: String,
+    /// `None` for spins recorded before configs were versioned.
+    pub config_hash: Option<String>,
     pub result_reels: Vec<Vec<u8>>,
     pub payout: f64,
+    /// `row_hash` of the spin before this one in the log (see `tigrinho_core::chain`).
+    pub prev_hash: String,
+    pub row_hash: String,
+    /// `None` for spins recorded before rounds existed.
+    #[serde(default)]
+    pub round_id: Option<i64>,
+    /// Step of the round's feature; `None` for the spin that opens a round.
+    #[serde(default)]
+    pub cursor: Option<i64>,
+    /// The player's action that played this step, e.g. `spin`.
+    #[serde(default)]
+    pub action: Option<String>,
+}
+
+/// The latest link of the spin log hash chain (`GET /audit/chain-head`).
+/// Publishing it elsewhere pins the log: any later rewrite of the first
+/// `length` rows no longer reaches `head_hash`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ChainHead {
+    /// Spins in the chain.
+    pub length: i64,
+    pub last_spin_id: Option<i64>,
+    pub head_hash: String,
+}
+
+/// A Merkle root committed over a contiguous batch of the spin log: the spins
+/// with ids `first_spin_id..=last_spin_id`, the first `chain_length` of the
+/// chain ending there. Leaves are the spins' `row_hash`es in id order (see
+/// `tigrinho_core::MerkleTree`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MerkleRoot {
+    pub id: i64,
+    pub ts: DateTime<Utc>,
+    pub first_spin_id: i64,
+    pub last_spin_id: i64,
+    pub leaf_count: i64,
+    pub chain_length: i64,
+    pub root: String,
+}
+
+/// Inclusion proof of one spin in a committed batch (`GET /spins/{id}/proof`).
+/// Hashing `row_hash` up through `siblings` gives the `root` of batch
+/// `root_id`; check it with `tigrinho_core::verify_inclusion` against the root
+/// and leaf count published at `GET /audit/roots`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpinProof {
+    pub spin_id: i64,
+    pub row_hash: String,
+    pub root_id: i64,
+    pub root: String,
+    /// Position of the spin in the batch.
+    pub leaf_index: u64,
+    pub leaf_count: u64,
+    /// Leaf to root; levels where the path has no sibling are skipped.
+    pub siblings: Vec<String>,
+}
+
+/// Query string of `GET /spins` and `GET /me/spins`. Every filter is optional;
+/// results are newest first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SpinQuery {
+    /// `next_cursor` of the previous page.
+    pub cursor: Option<i64>,
+    pub limit: Option<u32>,
+    /// Inclusive lower bound on the spin time.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on the spin time.
+    pub to: Option<DateTime<Utc>>,
+    pub game: Option<String>,
+    pub client_seed: Option<String>,
+    pub server_seed_hash: Option<String>,
+    pub min_payout: Option<f64>,
+    pub max_payout: Option<f64>,
+    /// Only the spins of this round: its opening spin and its feature steps.
+    pub round_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SpinPage {
+    pub spins: Vec<SpinLogEntry>,
+    /// Pass as `cursor` to fetch the next (older) page; `None` on the last page.
+    pub next_cursor: Option<i64>,
+}
+
+/// Where a round is in its lifecycle: open until its last step settles it.
+/// Settled rounds may still be voided; voided rounds are final.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundStatus {
+    Open,
+    Settled,
+    Voided,
+}
+
+impl RoundStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoundStatus::Open => "open",
+            RoundStatus::Settled => "settled",
+            RoundStatus::Voided => "voided",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "open" => Some(RoundStatus::Open),
+            "settled" => Some(RoundStatus::Settled),
+            "voided" => Some(RoundStatus::Voided),
+            _ => None,
+        }
+    }
+}
+
+/// One wager and everything it paid, over one or more spins.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Round {
+    pub id: i64,
+    pub game: String,
+    pub player_id: i64,
+    pub bet: f64,
+    /// Paid so far; final once the round is settled.
+    pub payout: f64,
+    pub status: RoundStatus,
+    pub opened_at: DateTime<Utc>,
+    pub settled_at: Option<DateTime<Utc>>,
+    pub voided_at: Option<DateTime<Utc>>,
+    pub void_reason: Option<String>,
+}
+
+/// Query string of `GET /admin/rounds`; newest first.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RoundQuery {
+    pub game: Option<String>,
+    pub player_id: Option<i64>,
+    pub status: Option<RoundStatus>,
+    pub limit: Option<u32>,
+}
+
+/// Body of `POST /admin/rounds/{id}/void`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VoidRoundRequest {
+    /// Kept with the round and in the admin audit trail.
+    pub reason: String,
+}
+
+/// Rounds of one status and what they wagered and paid.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct RoundTotals {
+    pub rounds: i64,
+    pub wagered: f64,
+    pub paid: f64,
+}
+
+/// Query string of `GET /admin/games/{slug}/report`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReportQuery {
+    /// Inclusive lower bound on when rounds opened.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on when rounds opened.
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// A game's rounds over a period. Voided rounds were refunded, so they count
+/// towards neither the handle nor the RTP. Spins from before rounds existed
+/// belong to no round and are not counted.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoundReport {
+    pub game: String,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub settled: RoundTotals,
+    pub open: RoundTotals,
+    pub voided: RoundTotals,
+    /// Paid over wagered across settled rounds; `None` without any.
+    pub rtp: Option<f64>,
+}
+
+/// A win on the public feed, without anything identifying the player.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BigWin {
+    pub game: String,
+    pub ts: DateTime<Utc>,
+    pub bet: f64,
+    pub payout: f64,
+    pub multiplier: f64,
 }
 
-#[derive(thiserror::Error, Debug)]
+/// Feed to subscribe to on `GET /ws?channel=...`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveChannel {
+    /// The caller's own spins; needs a ticket from `POST /me/live-ticket`.
+    Player,
+    /// Anonymized big wins of every game; no authentication.
+    Public,
+    /// Every spin and RTP alert; needs a ticket from `POST /admin/live-ticket`.
+    Admin,
+}
+
+/// A single-use pass to open one `GET /ws` connection on `channel`, so that
+/// no session token or admin key has to travel in a URL.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LiveTicket {
+    pub ticket: String,
+    pub channel: LiveChannel,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Query string of `GET /ws`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LiveQuery {
+    pub channel: LiveChannel,
+    /// Browsers cannot set headers on WebSocket requests, so a [`LiveTicket`]
+    /// stands in for the credentials here.
+    #[serde(default)]
+    pub ticket: Option<String>,
+}
+
+/// One JSON text frame on the WebSocket feed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    Spin(SpinLogEntry),
+    BigWin(BigWin),
+    RtpAlert(RtpAlert),
+}
+
+/// Machine-readable error codes carried by [`ApiErrorBody`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidRequest,
+    Validation,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    MethodNotAllowed,
+    InsufficientFunds,
+    RateLimited,
+    Conflict,
+    GameSuspended,
+    Internal,
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
 pub enum ApiError {
+    /// Malformed request: bad JSON, missing or unreadable headers.
     #[error("invalid request: {0}")]
     Invalid(String),
+    /// Well-formed request whose values are not acceptable.
+    #[error("validation failed: {0}")]
+    Validation(String),
+    #[error("authentication required")]
+    Unauthorized,
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// The route exists but does not take this method.
+    #[error("method not allowed")]
+    MethodNotAllowed,
+    #[error("insufficient funds")]
+    InsufficientFunds,
+    #[error("rate limited, retry after {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
+    #[error("conflict: {0}")]
+    Conflict(String),
+    /// The game was halted, e.g. by the RTP monitor, until an admin resumes it.
+    #[error("game {0} is suspended")]
+    GameSuspended(String),
     #[error("internal server error")]
     Internal,
 }
 
+impl ApiError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::Invalid(_) => ErrorCode::InvalidRequest,
+            ApiError::Validation(_) => ErrorCode::Validation,
+            ApiError::Unauthorized => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::MethodNotAllowed => ErrorCode::MethodNotAllowed,
+            ApiError::InsufficientFunds => ErrorCode::InsufficientFunds,
+            ApiError::RateLimited { .. } => ErrorCode::RateLimited,
+            ApiError::Conflict(_) => ErrorCode::Conflict,
+            ApiError::GameSuspended(_) => ErrorCode::GameSuspended,
+            ApiError::Internal => ErrorCode::Internal,
+        }
+    }
+
+    /// HTTP status the server answers this error with.
+    pub fn status(&self) -> u16 {
+        match self {
+            ApiError::Invalid(_) => 400,
+            ApiError::Validation(_) => 422,
+            ApiError::Unauthorized => 401,
+            ApiError::Forbidden(_) => 403,
+            ApiError::NotFound(_) => 404,
+            ApiError::MethodNotAllowed => 405,
+            ApiError::InsufficientFunds => 402,
+            ApiError::RateLimited { .. } => 429,
+            ApiError::Conflict(_) => 409,
+            ApiError::GameSuspended(_) => 503,
+            ApiError::Internal => 500,
+        }
+    }
+}
+
+/// JSON envelope of every error response.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApiErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Same value as the `x-request-id` response header; quote it when reporting issues.
+    pub request_id: String,
+}
+
 pub type ApiResult<T> = Result<T, ApiError>;
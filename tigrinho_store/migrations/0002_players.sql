@@ -0,0 +1,23 @@
+-- 2026-10-18: players and login sessions
+CREATE TABLE IF NOT EXISTS players (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    -- the client seed the player's spins use when the request names none
+    client_seed TEXT
+);
+
+-- one row per issued token; tokens are only accepted while their row is live
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    issued_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS sessions_player ON sessions (player_id);
+
+-- spins recorded before this migration stay anonymous (NULL)
+ALTER TABLE spins ADD COLUMN player_id INTEGER REFERENCES players(id);
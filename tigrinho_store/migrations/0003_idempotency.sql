@@ -0,0 +1,15 @@
+-- 2026-10-18: stored /spin responses keyed by the client's Idempotency-Key
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    -- NULL while the first request is still being processed
+    response_json TEXT,
+    -- the spin the first request recorded, set in the spin's own transaction
+    -- so a key whose response was never stored is answered from its spin
+    spin_id INTEGER REFERENCES spins(id),
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (player_id, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created ON idempotency_keys (created_at);
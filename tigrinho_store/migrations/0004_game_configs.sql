@@ -0,0 +1,26 @@
+-- 2026-10-18: immutable, content-hashed game configurations
+-- config_hash = SHA-256 of definition_json (tigrinho_core::GameDefinition canonical JSON)
+CREATE TABLE IF NOT EXISTS game_configs (
+    config_hash TEXT PRIMARY KEY,
+    definition_json TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS game_configs_no_update
+BEFORE UPDATE ON game_configs
+BEGIN
+    SELECT RAISE(ABORT, 'game_configs is immutable');
+END;
+
+CREATE TRIGGER IF NOT EXISTS game_configs_no_delete
+BEFORE DELETE ON game_configs
+BEGIN
+    SELECT RAISE(ABORT, 'game_configs is immutable');
+END;
+
+-- the active version; params.rtp_target/paytable_json are superseded and only
+-- read once at startup to build the first version
+ALTER TABLE params ADD COLUMN active_config_hash TEXT REFERENCES game_configs(config_hash);
+
+-- spins recorded before this migration keep NULL
+ALTER TABLE spins ADD COLUMN config_hash TEXT REFERENCES game_configs(config_hash);
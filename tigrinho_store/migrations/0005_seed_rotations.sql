@@ -0,0 +1,27 @@
+-- 2026-10-18: seed rotation audit log
+-- when the current server seed was committed (used by time-based rotation)
+ALTER TABLE params ADD COLUMN seed_committed_at TEXT;
+
+-- one row per rotation; revealed_seed is the retired seed, published so spins
+-- made under previous_seed_hash (nonces 1..=final_nonce) can be verified
+CREATE TABLE IF NOT EXISTS seed_rotations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TEXT NOT NULL,
+    trigger TEXT NOT NULL,
+    previous_seed_hash TEXT NOT NULL,
+    revealed_seed TEXT NOT NULL,
+    final_nonce INTEGER NOT NULL,
+    new_seed_hash TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS seed_rotations_no_update
+BEFORE UPDATE ON seed_rotations
+BEGIN
+    SELECT RAISE(ABORT, 'seed_rotations is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS seed_rotations_no_delete
+BEFORE DELETE ON seed_rotations
+BEGIN
+    SELECT RAISE(ABORT, 'seed_rotations is append-only');
+END;
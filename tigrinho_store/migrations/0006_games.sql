@@ -0,0 +1,28 @@
+-- 2026-10-18: multiple games per server, keyed by slug
+-- each game has its own seed lifecycle, nonce space, active config and bet limits
+CREATE TABLE IF NOT EXISTS games (
+    slug TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    server_seed TEXT NOT NULL,
+    server_seed_hash TEXT NOT NULL,
+    seed_committed_at TEXT,
+    nonce INTEGER NOT NULL,
+    active_config_hash TEXT REFERENCES game_configs(config_hash),
+    min_bet REAL NOT NULL DEFAULT 0.1,
+    max_bet REAL NOT NULL DEFAULT 100.0
+);
+
+-- the single game of earlier versions becomes 'tigrinho'
+INSERT OR IGNORE INTO games (slug, name, created_at, server_seed, server_seed_hash, seed_committed_at, nonce, active_config_hash)
+SELECT 'tigrinho', 'Tigrinho', strftime('%Y-%m-%dT%H:%M:%SZ', 'now'), server_seed, server_seed_hash, seed_committed_at, nonce, active_config_hash
+FROM params WHERE id = 1;
+
+DROP TABLE params;
+
+-- rows written before this migration belong to 'tigrinho'; new rows always set it
+-- (no REFERENCES: sqlite refuses a foreign key column with a non-NULL default)
+ALTER TABLE spins ADD COLUMN game TEXT NOT NULL DEFAULT 'tigrinho';
+ALTER TABLE seed_rotations ADD COLUMN game TEXT NOT NULL DEFAULT 'tigrinho';
+
+CREATE INDEX IF NOT EXISTS spins_game ON spins (game, id);
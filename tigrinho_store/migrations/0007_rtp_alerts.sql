@@ -0,0 +1,28 @@
+-- 2026-10-18: RTP deviation monitor
+-- a suspended game refuses spins until an admin resumes it
+ALTER TABLE games ADD COLUMN suspended_at TEXT;
+ALTER TABLE games ADD COLUMN suspended_reason TEXT;
+
+CREATE TABLE IF NOT EXISTS rtp_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game TEXT NOT NULL REFERENCES games(slug),
+    ts TEXT NOT NULL,
+    config_hash TEXT NOT NULL REFERENCES game_configs(config_hash),
+    spins INTEGER NOT NULL,
+    observed_rtp REAL NOT NULL,
+    theoretical_rtp REAL NOT NULL,
+    z_score REAL NOT NULL,
+    suspended INTEGER NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS rtp_alerts_no_update
+BEFORE UPDATE ON rtp_alerts
+BEGIN
+    SELECT RAISE(ABORT, 'rtp_alerts is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS rtp_alerts_no_delete
+BEFORE DELETE ON rtp_alerts
+BEGIN
+    SELECT RAISE(ABORT, 'rtp_alerts is append-only');
+END;
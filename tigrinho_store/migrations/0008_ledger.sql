@@ -0,0 +1,25 @@
+-- 2026-10-18: per-player money movements
+-- one row per wager and per winning payout; amounts are signed (wagers negative)
+CREATE TABLE IF NOT EXISTS ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TEXT NOT NULL,
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    game TEXT NOT NULL REFERENCES games(slug),
+    spin_id INTEGER REFERENCES spins(id),
+    kind TEXT NOT NULL,
+    amount REAL NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS ledger_player ON ledger (player_id, id);
+
+CREATE TRIGGER IF NOT EXISTS ledger_no_update
+BEFORE UPDATE ON ledger
+BEGIN
+    SELECT RAISE(ABORT, 'ledger is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS ledger_no_delete
+BEFORE DELETE ON ledger
+BEGIN
+    SELECT RAISE(ABORT, 'ledger is append-only');
+END;
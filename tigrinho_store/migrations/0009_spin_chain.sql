@@ -0,0 +1,32 @@
+-- 2026-10-18: hash-chained spin log (see tigrinho_core::chain)
+-- row_hash = SHA-256(prev_hash || '\n' || canonical row); NULL only until the
+-- row is sealed, which happens in the transaction that inserts it (or, for rows
+-- from before this migration, once at the next startup)
+ALTER TABLE spins ADD COLUMN prev_hash TEXT;
+ALTER TABLE spins ADD COLUMN row_hash TEXT;
+
+-- sealed rows are immutable; an unsealed row may only be sealed
+DROP TRIGGER IF EXISTS spins_no_update;
+CREATE TRIGGER spins_no_update
+BEFORE UPDATE ON spins
+WHEN OLD.row_hash IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'spins is append-only');
+END;
+
+-- the chain head: the last sealed spin, kept alongside the log so rows removed
+-- from its end are detected too. backfill_through is the last spin from before
+-- the chain, until those have been sealed.
+CREATE TABLE IF NOT EXISTS spin_chain (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    length INTEGER NOT NULL,
+    last_spin_id INTEGER REFERENCES spins(id),
+    head_hash TEXT NOT NULL,
+    backfill_through INTEGER
+);
+
+INSERT OR IGNORE INTO spin_chain (id, length, last_spin_id, head_hash, backfill_through)
+VALUES (
+    1, 0, NULL, '0000000000000000000000000000000000000000000000000000000000000000',
+    (SELECT MAX(id) FROM spins)
+);
@@ -0,0 +1,24 @@
+-- 2026-10-18: Merkle roots committed over batches of the spin log
+-- each batch is the spins with ids first_spin_id..=last_spin_id, the first
+-- chain_length spins of the chain ending there; batches never overlap
+CREATE TABLE IF NOT EXISTS merkle_roots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TEXT NOT NULL,
+    first_spin_id INTEGER NOT NULL UNIQUE REFERENCES spins(id),
+    last_spin_id INTEGER NOT NULL UNIQUE REFERENCES spins(id),
+    leaf_count INTEGER NOT NULL,
+    chain_length INTEGER NOT NULL,
+    root TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS merkle_roots_no_update
+BEFORE UPDATE ON merkle_roots
+BEGIN
+    SELECT RAISE(ABORT, 'merkle_roots is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS merkle_roots_no_delete
+BEFORE DELETE ON merkle_roots
+BEGIN
+    SELECT RAISE(ABORT, 'merkle_roots is append-only');
+END;
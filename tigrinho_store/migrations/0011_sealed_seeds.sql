@@ -0,0 +1,3 @@
+-- 2026-10-18: server seeds are stored sealed by the server's seed vault
+-- seeds still in plaintext are sealed by the next server start or `tigrinho_cli rotate-kek`
+ALTER TABLE games RENAME COLUMN server_seed TO sealed_seed;
@@ -0,0 +1,35 @@
+-- 2026-10-18: named admin API keys with roles, and the trail of what admins changed
+-- only SHA-256 of a key's secret is stored; key_id is the public part of the key
+CREATE TABLE IF NOT EXISTS admin_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL UNIQUE,
+    role TEXT NOT NULL CHECK (role IN ('viewer', 'operator', 'owner')),
+    secret_hash TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT,
+    revoked_at TEXT
+);
+
+-- before_json/after_json snapshot what the action changed; NULL where there was nothing
+CREATE TABLE IF NOT EXISTS admin_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT,
+    before_json TEXT,
+    after_json TEXT
+);
+
+CREATE TRIGGER IF NOT EXISTS admin_audit_no_update
+BEFORE UPDATE ON admin_audit
+BEGIN
+    SELECT RAISE(ABORT, 'admin_audit is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS admin_audit_no_delete
+BEFORE DELETE ON admin_audit
+BEGIN
+    SELECT RAISE(ABORT, 'admin_audit is append-only');
+END;
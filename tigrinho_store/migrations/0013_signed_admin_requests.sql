@@ -0,0 +1,14 @@
+-- 2026-10-18: signed admin requests
+-- the HMAC key an admin key signs requests with, sealed under the seed keyring;
+-- NULL for keys made before, which can only be sent as bearer tokens
+ALTER TABLE admin_keys ADD COLUMN sealed_signing_key TEXT;
+
+-- signatures accepted within the replay window; each is accepted once
+CREATE TABLE IF NOT EXISTS admin_request_signatures (
+    key_id TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    PRIMARY KEY (key_id, signature)
+);
+
+CREATE INDEX IF NOT EXISTS admin_request_signatures_expires ON admin_request_signatures (expires_at);
@@ -0,0 +1,40 @@
+-- 2026-10-18: rounds
+-- a round is one wager and everything it pays: a single spin, or the spins of
+-- a feature it triggered; it stays open until its last step settles it
+CREATE TABLE IF NOT EXISTS rounds (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    game TEXT NOT NULL REFERENCES games(slug),
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    bet REAL NOT NULL,
+    -- paid so far; final once the round is settled
+    payout REAL NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('open', 'settled', 'voided')),
+    opened_at TEXT NOT NULL,
+    settled_at TEXT,
+    voided_at TEXT,
+    void_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS rounds_game ON rounds (game, opened_at);
+CREATE INDEX IF NOT EXISTS rounds_player ON rounds (player_id, id);
+
+-- open -> settled or voided, settled -> voided; a voided round is final
+CREATE TRIGGER IF NOT EXISTS rounds_final
+BEFORE UPDATE ON rounds
+WHEN OLD.status = 'voided' OR (OLD.status = 'settled' AND NEW.status <> 'voided')
+BEGIN
+    SELECT RAISE(ABORT, 'rounds: settled and voided rounds are final');
+END;
+
+CREATE TRIGGER IF NOT EXISTS rounds_no_delete
+BEFORE DELETE ON rounds
+BEGIN
+    SELECT RAISE(ABORT, 'rounds cannot be deleted');
+END;
+
+-- NULL on rows from before rounds; those spins cannot be voided
+ALTER TABLE spins ADD COLUMN round_id INTEGER REFERENCES rounds(id);
+ALTER TABLE ledger ADD COLUMN round_id INTEGER REFERENCES rounds(id);
+
+CREATE INDEX IF NOT EXISTS spins_round ON spins (round_id);
+CREATE INDEX IF NOT EXISTS ledger_round ON ledger (round_id);
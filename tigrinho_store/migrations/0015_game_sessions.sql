@@ -0,0 +1,40 @@
+-- 2026-10-18: game sessions
+-- the state of a round's feature between requests: what its steps are drawn
+-- from and the next step to play; kept once the feature is over
+CREATE TABLE IF NOT EXISTS game_sessions (
+    round_id INTEGER PRIMARY KEY REFERENCES rounds(id),
+    game TEXT NOT NULL REFERENCES games(slug),
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    client_seed TEXT NOT NULL,
+    nonce INTEGER NOT NULL,
+    server_seed_hash TEXT NOT NULL,
+    config_hash TEXT NOT NULL REFERENCES game_configs(config_hash),
+    bet REAL NOT NULL,
+    -- the next step to play
+    cursor INTEGER NOT NULL,
+    -- tigrinho_core::FeatureState; NULL once the feature is over
+    state_json TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS game_sessions_player ON game_sessions (player_id, round_id);
+
+-- a session only moves forward, and stays over once it is
+CREATE TRIGGER IF NOT EXISTS game_sessions_forward
+BEFORE UPDATE ON game_sessions
+WHEN OLD.state_json IS NULL OR NEW.cursor < OLD.cursor
+BEGIN
+    SELECT RAISE(ABORT, 'game_sessions: sessions only move forward');
+END;
+
+CREATE TRIGGER IF NOT EXISTS game_sessions_no_delete
+BEFORE DELETE ON game_sessions
+BEGIN
+    SELECT RAISE(ABORT, 'game_sessions cannot be deleted');
+END;
+
+-- the step of the round's feature a spin played and the action that played
+-- it; NULL for the spin that opens a round
+ALTER TABLE spins ADD COLUMN cursor INTEGER;
+ALTER TABLE spins ADD COLUMN action TEXT;
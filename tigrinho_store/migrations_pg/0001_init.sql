@@ -0,0 +1,164 @@
+-- 2026-10-18: initial PostgreSQL schema
+-- mirrors the SQLite schema as of migrations/0008_ledger.sql; timestamps are
+-- TIMESTAMPTZ here instead of RFC 3339 text
+
+-- raised by the append-only triggers below; the message is the trigger argument
+CREATE FUNCTION refuse_change() RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    RAISE EXCEPTION '%', TG_ARGV[0];
+END;
+$$;
+
+-- config_hash = SHA-256 of definition_json (tigrinho_core::GameDefinition canonical JSON)
+CREATE TABLE game_configs (
+    config_hash TEXT PRIMARY KEY,
+    definition_json TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TRIGGER game_configs_no_update BEFORE UPDATE ON game_configs
+FOR EACH ROW EXECUTE FUNCTION refuse_change('game_configs is immutable');
+CREATE TRIGGER game_configs_no_delete BEFORE DELETE ON game_configs
+FOR EACH ROW EXECUTE FUNCTION refuse_change('game_configs is immutable');
+CREATE TRIGGER game_configs_no_truncate BEFORE TRUNCATE ON game_configs
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('game_configs is immutable');
+
+-- each game has its own seed lifecycle, nonce space, active config and bet limits;
+-- a suspended game refuses spins until an admin resumes it
+CREATE TABLE games (
+    slug TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    server_seed TEXT NOT NULL,
+    server_seed_hash TEXT NOT NULL,
+    seed_committed_at TIMESTAMPTZ,
+    nonce BIGINT NOT NULL,
+    active_config_hash TEXT REFERENCES game_configs(config_hash),
+    min_bet DOUBLE PRECISION NOT NULL DEFAULT 0.1,
+    max_bet DOUBLE PRECISION NOT NULL DEFAULT 100.0,
+    suspended_at TIMESTAMPTZ,
+    suspended_reason TEXT
+);
+
+-- the default game starts on the development seed; the store gives it the
+-- default config after migrating
+INSERT INTO games (slug, name, created_at, server_seed, server_seed_hash, seed_committed_at, nonce)
+VALUES ('tigrinho', 'Tigrinho', now(), 'dev-secret-seed', encode(sha256('dev-secret-seed'), 'hex'), now(), 0);
+
+CREATE TABLE players (
+    id BIGSERIAL PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    -- the client seed the player's spins use when the request names none
+    client_seed TEXT
+);
+
+-- one row per issued token; tokens are only accepted while their row is live
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    player_id BIGINT NOT NULL REFERENCES players(id),
+    issued_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX sessions_player ON sessions (player_id);
+
+CREATE TABLE spins (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    game TEXT NOT NULL REFERENCES games(slug),
+    player_id BIGINT REFERENCES players(id),
+    client_seed TEXT NOT NULL,
+    nonce BIGINT NOT NULL,
+    server_seed_hash TEXT NOT NULL,
+    config_hash TEXT REFERENCES game_configs(config_hash),
+    result_reels_json TEXT NOT NULL,
+    payout DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX spins_game ON spins (game, id);
+
+-- enforce append-only for spins
+CREATE TRIGGER spins_no_update BEFORE UPDATE ON spins
+FOR EACH ROW EXECUTE FUNCTION refuse_change('spins is append-only');
+CREATE TRIGGER spins_no_delete BEFORE DELETE ON spins
+FOR EACH ROW EXECUTE FUNCTION refuse_change('spins is append-only');
+CREATE TRIGGER spins_no_truncate BEFORE TRUNCATE ON spins
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('spins is append-only');
+
+-- stored /spin responses keyed by the client's Idempotency-Key
+CREATE TABLE idempotency_keys (
+    player_id BIGINT NOT NULL REFERENCES players(id),
+    key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    -- NULL while the first request is still being processed
+    response_json TEXT,
+    -- the spin the first request recorded, set in the spin's own transaction
+    -- so a key whose response was never stored is answered from its spin
+    spin_id BIGINT REFERENCES spins(id),
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (player_id, key)
+);
+
+CREATE INDEX idempotency_keys_created ON idempotency_keys (created_at);
+
+-- one row per rotation; revealed_seed is the retired seed, published so spins
+-- made under previous_seed_hash (nonces 1..=final_nonce) can be verified
+CREATE TABLE seed_rotations (
+    id BIGSERIAL PRIMARY KEY,
+    game TEXT NOT NULL REFERENCES games(slug),
+    ts TIMESTAMPTZ NOT NULL,
+    trigger TEXT NOT NULL,
+    previous_seed_hash TEXT NOT NULL,
+    revealed_seed TEXT NOT NULL,
+    final_nonce BIGINT NOT NULL,
+    new_seed_hash TEXT NOT NULL
+);
+
+CREATE TRIGGER seed_rotations_no_update BEFORE UPDATE ON seed_rotations
+FOR EACH ROW EXECUTE FUNCTION refuse_change('seed_rotations is append-only');
+CREATE TRIGGER seed_rotations_no_delete BEFORE DELETE ON seed_rotations
+FOR EACH ROW EXECUTE FUNCTION refuse_change('seed_rotations is append-only');
+CREATE TRIGGER seed_rotations_no_truncate BEFORE TRUNCATE ON seed_rotations
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('seed_rotations is append-only');
+
+CREATE TABLE rtp_alerts (
+    id BIGSERIAL PRIMARY KEY,
+    game TEXT NOT NULL REFERENCES games(slug),
+    ts TIMESTAMPTZ NOT NULL,
+    config_hash TEXT NOT NULL REFERENCES game_configs(config_hash),
+    spins BIGINT NOT NULL,
+    observed_rtp DOUBLE PRECISION NOT NULL,
+    theoretical_rtp DOUBLE PRECISION NOT NULL,
+    z_score DOUBLE PRECISION NOT NULL,
+    suspended BOOLEAN NOT NULL
+);
+
+CREATE TRIGGER rtp_alerts_no_update BEFORE UPDATE ON rtp_alerts
+FOR EACH ROW EXECUTE FUNCTION refuse_change('rtp_alerts is append-only');
+CREATE TRIGGER rtp_alerts_no_delete BEFORE DELETE ON rtp_alerts
+FOR EACH ROW EXECUTE FUNCTION refuse_change('rtp_alerts is append-only');
+CREATE TRIGGER rtp_alerts_no_truncate BEFORE TRUNCATE ON rtp_alerts
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('rtp_alerts is append-only');
+
+-- one row per wager and per winning payout; amounts are signed (wagers negative)
+CREATE TABLE ledger (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    player_id BIGINT NOT NULL REFERENCES players(id),
+    game TEXT NOT NULL REFERENCES games(slug),
+    spin_id BIGINT REFERENCES spins(id),
+    kind TEXT NOT NULL,
+    amount DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX ledger_player ON ledger (player_id, id);
+
+CREATE TRIGGER ledger_no_update BEFORE UPDATE ON ledger
+FOR EACH ROW EXECUTE FUNCTION refuse_change('ledger is append-only');
+CREATE TRIGGER ledger_no_delete BEFORE DELETE ON ledger
+FOR EACH ROW EXECUTE FUNCTION refuse_change('ledger is append-only');
+CREATE TRIGGER ledger_no_truncate BEFORE TRUNCATE ON ledger
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('ledger is append-only');
@@ -0,0 +1,24 @@
+-- 2026-10-18: hash-chained spin log, mirrors migrations/0009_spin_chain.sql
+ALTER TABLE spins ADD COLUMN prev_hash TEXT, ADD COLUMN row_hash TEXT;
+
+-- sealed rows are immutable; an unsealed row may only be sealed
+DROP TRIGGER spins_no_update ON spins;
+CREATE TRIGGER spins_no_update BEFORE UPDATE ON spins
+FOR EACH ROW WHEN (OLD.row_hash IS NOT NULL)
+EXECUTE FUNCTION refuse_change('spins is append-only');
+
+-- backfill_through is the last spin from before the chain, until those have
+-- been sealed
+CREATE TABLE spin_chain (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    length BIGINT NOT NULL,
+    last_spin_id BIGINT REFERENCES spins(id),
+    head_hash TEXT NOT NULL,
+    backfill_through BIGINT
+);
+
+INSERT INTO spin_chain (id, length, last_spin_id, head_hash, backfill_through)
+VALUES (
+    1, 0, NULL, '0000000000000000000000000000000000000000000000000000000000000000',
+    (SELECT MAX(id) FROM spins)
+);
@@ -0,0 +1,17 @@
+-- 2026-10-18: Merkle roots over spin batches, mirrors migrations/0010_merkle_roots.sql
+CREATE TABLE merkle_roots (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    first_spin_id BIGINT NOT NULL UNIQUE REFERENCES spins(id),
+    last_spin_id BIGINT NOT NULL UNIQUE REFERENCES spins(id),
+    leaf_count BIGINT NOT NULL,
+    chain_length BIGINT NOT NULL,
+    root TEXT NOT NULL
+);
+
+CREATE TRIGGER merkle_roots_no_update BEFORE UPDATE ON merkle_roots
+FOR EACH ROW EXECUTE FUNCTION refuse_change('merkle_roots is append-only');
+CREATE TRIGGER merkle_roots_no_delete BEFORE DELETE ON merkle_roots
+FOR EACH ROW EXECUTE FUNCTION refuse_change('merkle_roots is append-only');
+CREATE TRIGGER merkle_roots_no_truncate BEFORE TRUNCATE ON merkle_roots
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('merkle_roots is append-only');
@@ -0,0 +1,28 @@
+-- 2026-10-18: admin keys and the admin audit trail, mirrors migrations/0012_admin_keys.sql
+CREATE TABLE admin_keys (
+    id BIGSERIAL PRIMARY KEY,
+    key_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL UNIQUE,
+    role TEXT NOT NULL CHECK (role IN ('viewer', 'operator', 'owner')),
+    secret_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE TABLE admin_audit (
+    id BIGSERIAL PRIMARY KEY,
+    ts TIMESTAMPTZ NOT NULL,
+    actor TEXT NOT NULL,
+    action TEXT NOT NULL,
+    target TEXT,
+    before_json TEXT,
+    after_json TEXT
+);
+
+CREATE TRIGGER admin_audit_no_update BEFORE UPDATE ON admin_audit
+FOR EACH ROW EXECUTE FUNCTION refuse_change('admin_audit is append-only');
+CREATE TRIGGER admin_audit_no_delete BEFORE DELETE ON admin_audit
+FOR EACH ROW EXECUTE FUNCTION refuse_change('admin_audit is append-only');
+CREATE TRIGGER admin_audit_no_truncate BEFORE TRUNCATE ON admin_audit
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('admin_audit is append-only');
@@ -0,0 +1,11 @@
+-- 2026-10-18: signed admin requests, mirrors migrations/0013_signed_admin_requests.sql
+ALTER TABLE admin_keys ADD COLUMN sealed_signing_key TEXT;
+
+CREATE TABLE admin_request_signatures (
+    key_id TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (key_id, signature)
+);
+
+CREATE INDEX admin_request_signatures_expires ON admin_request_signatures (expires_at);
@@ -0,0 +1,30 @@
+-- 2026-10-18: rounds, mirrors migrations/0014_rounds.sql
+CREATE TABLE rounds (
+    id BIGSERIAL PRIMARY KEY,
+    game TEXT NOT NULL REFERENCES games(slug),
+    player_id BIGINT NOT NULL REFERENCES players(id),
+    bet DOUBLE PRECISION NOT NULL,
+    payout DOUBLE PRECISION NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('open', 'settled', 'voided')),
+    opened_at TIMESTAMPTZ NOT NULL,
+    settled_at TIMESTAMPTZ,
+    voided_at TIMESTAMPTZ,
+    void_reason TEXT
+);
+
+CREATE INDEX rounds_game ON rounds (game, opened_at);
+CREATE INDEX rounds_player ON rounds (player_id, id);
+
+CREATE TRIGGER rounds_final BEFORE UPDATE ON rounds
+FOR EACH ROW WHEN (OLD.status = 'voided' OR (OLD.status = 'settled' AND NEW.status <> 'voided'))
+EXECUTE FUNCTION refuse_change('rounds: settled and voided rounds are final');
+CREATE TRIGGER rounds_no_delete BEFORE DELETE ON rounds
+FOR EACH ROW EXECUTE FUNCTION refuse_change('rounds cannot be deleted');
+CREATE TRIGGER rounds_no_truncate BEFORE TRUNCATE ON rounds
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('rounds cannot be deleted');
+
+ALTER TABLE spins ADD COLUMN round_id BIGINT REFERENCES rounds(id);
+ALTER TABLE ledger ADD COLUMN round_id BIGINT REFERENCES rounds(id);
+
+CREATE INDEX spins_round ON spins (round_id);
+CREATE INDEX ledger_round ON ledger (round_id);
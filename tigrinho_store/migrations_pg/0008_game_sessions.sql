@@ -0,0 +1,28 @@
+-- 2026-10-18: game sessions, mirrors migrations/0015_game_sessions.sql
+CREATE TABLE game_sessions (
+    round_id BIGINT PRIMARY KEY REFERENCES rounds(id),
+    game TEXT NOT NULL REFERENCES games(slug),
+    player_id BIGINT NOT NULL REFERENCES players(id),
+    client_seed TEXT NOT NULL,
+    nonce BIGINT NOT NULL,
+    server_seed_hash TEXT NOT NULL,
+    config_hash TEXT NOT NULL REFERENCES game_configs(config_hash),
+    bet DOUBLE PRECISION NOT NULL,
+    cursor BIGINT NOT NULL,
+    state_json TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX game_sessions_player ON game_sessions (player_id, round_id);
+
+CREATE TRIGGER game_sessions_forward BEFORE UPDATE ON game_sessions
+FOR EACH ROW WHEN (OLD.state_json IS NULL OR NEW.cursor < OLD.cursor)
+EXECUTE FUNCTION refuse_change('game_sessions: sessions only move forward');
+CREATE TRIGGER game_sessions_no_delete BEFORE DELETE ON game_sessions
+FOR EACH ROW EXECUTE FUNCTION refuse_change('game_sessions cannot be deleted');
+CREATE TRIGGER game_sessions_no_truncate BEFORE TRUNCATE ON game_sessions
+FOR EACH STATEMENT EXECUTE FUNCTION refuse_change('game_sessions cannot be deleted');
+
+ALTER TABLE spins ADD COLUMN cursor BIGINT;
+ALTER TABLE spins ADD COLUMN action TEXT;
@@ -0,0 +1,2115 @@
+//! Persistent state of the game server behind one trait, so the server and the
+//! CLI share a single code path and tests can run without a database.
+//!
+//! [`SqliteStore`] and [`PgStore`] are the database backends, each with its
+//! own schema migrations; [`connect`] picks one from a database URL.
+//! [`MemoryStore`] keeps everything in process.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::RngCore;
+use std::sync::Arc;
+
+use tigrinho_core::{ChainVerifier, ChainedSpin, GameDefinition, MerkleTree};
+use tigrinho_shared::{
+    AdminAuditEntry, AdminRole, ChainHead, GameSummary, MerkleRoot, PlayerInfo, ReportQuery,
+    RotationTrigger, Round, RoundQuery, RoundReport, RoundStatus, RoundTotals, RtpAlert,
+    SeedRotation, SpinLogEntry, SpinProof, SpinQuery,
+};
+
+mod memory;
+mod pg;
+mod sqlite;
+pub mod vault;
+
+pub use memory::MemoryStore;
+pub use pg::PgStore;
+pub use sqlite::SqliteStore;
+pub use vault::SeedVault;
+
+/// Game created by the initial migration.
+pub const DEFAULT_GAME: &str = "tigrinho";
+/// Seed the initial migration gives [`DEFAULT_GAME`]; only acceptable in development.
+pub const DEV_SERVER_SEED: &str = "dev-secret-seed";
+
+/// A game with its current seed, nonce, active config and limits.
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub slug: String,
+    pub name: String,
+    /// The server seed as stored: sealed by a [`SeedVault`], or plaintext on a
+    /// database no vault has resealed yet.
+    pub sealed_seed: String,
+    pub server_seed_hash: String,
+    pub seed_committed_at: Option<DateTime<Utc>>,
+    pub nonce: i64,
+    pub active_config_hash: Option<String>,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    pub suspended_at: Option<DateTime<Utc>>,
+}
+
+impl GameRecord {
+    pub fn summary(&self) -> GameSummary {
+        GameSummary {
+            slug: self.slug.clone(),
+            name: self.name.clone(),
+            server_seed_hash: self.server_seed_hash.clone(),
+            config_hash: self.active_config_hash.clone().unwrap_or_default(),
+            min_bet: self.min_bet,
+            max_bet: self.max_bet,
+            suspended: self.suspended_at.is_some(),
+        }
+    }
+}
+
+/// A game to create; the store generates and seals its first seed.
+#[derive(Debug, Clone)]
+pub struct NewGame {
+    pub slug: String,
+    pub name: String,
+    pub config_hash: String,
+    pub min_bet: f64,
+    pub max_bet: f64,
+}
+
+/// A spin to record together with its ledger entries and its round.
+#[derive(Debug, Clone)]
+pub struct NewSpin {
+    pub ts: DateTime<Utc>,
+    pub game: String,
+    pub player_id: i64,
+    pub client_seed: String,
+    pub nonce: i64,
+    pub server_seed_hash: String,
+    pub config_hash: String,
+    pub result_reels: Vec<Vec<u8>>,
+    /// The stake the spin was evaluated at; only wagered when it opens a round.
+    pub bet: f64,
+    pub payout: f64,
+    /// `None` opens a round; otherwise a later step of an open round of the
+    /// same game and player, such as a free spin.
+    pub round_id: Option<i64>,
+    /// Whether this is the round's last step.
+    pub settle: bool,
+    /// How the spin moves the round's game session; `None` for rounds without
+    /// a feature.
+    pub session: Option<SessionStep>,
+    /// The player's `Idempotency-Key` the spin was requested under. The key is
+    /// pointed at the spin in the same transaction, so a retry finds the spin
+    /// even if its response was never stored.
+    pub idempotency_key: Option<String>,
+}
+
+/// The step of a round's feature a spin plays, and the state it leaves the
+/// round's game session in.
+#[derive(Debug, Clone)]
+pub struct SessionStep {
+    /// 0 for the opening spin, which starts the session; otherwise the
+    /// session must still be at this step.
+    pub cursor: i64,
+    /// The player's action; `None` for the opening spin.
+    pub action: Option<String>,
+    /// The feature's state after the spin (`tigrinho_core::FeatureState`);
+    /// `None` ends the session.
+    pub state: Option<serde_json::Value>,
+}
+
+impl NewSpin {
+    /// The `(cursor, action)` a spin row records: those of its step, except
+    /// for opening spins.
+    fn step(&self) -> (Option<i64>, Option<&str>) {
+        match &self.session {
+            Some(step) if step.cursor > 0 => (Some(step.cursor), step.action.as_deref()),
+            _ => (None, None),
+        }
+    }
+}
+
+/// Where a round's feature stands between requests, and what its steps are
+/// drawn from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameSession {
+    pub round_id: i64,
+    pub game: String,
+    pub player_id: i64,
+    pub client_seed: String,
+    pub nonce: i64,
+    pub server_seed_hash: String,
+    pub config_hash: String,
+    pub bet: f64,
+    /// The next step to play.
+    pub cursor: i64,
+    /// `None` once the feature is over.
+    pub state: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKind {
+    Wager,
+    Payout,
+    /// Returns the wager of a voided round.
+    Refund,
+    /// Takes back what a voided round paid.
+    Reversal,
+}
+
+impl LedgerKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LedgerKind::Wager => "wager",
+            LedgerKind::Payout => "payout",
+            LedgerKind::Refund => "refund",
+            LedgerKind::Reversal => "reversal",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "wager" => Some(LedgerKind::Wager),
+            "payout" => Some(LedgerKind::Payout),
+            "refund" => Some(LedgerKind::Refund),
+            "reversal" => Some(LedgerKind::Reversal),
+            _ => None,
+        }
+    }
+}
+
+/// One money movement of a player. Wagers and reversals are negative,
+/// payouts and refunds positive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub ts: DateTime<Utc>,
+    pub player_id: i64,
+    pub game: String,
+    /// `None` for the entries voiding a round.
+    pub spin_id: Option<i64>,
+    /// `None` for entries from before rounds.
+    pub round_id: Option<i64>,
+    pub kind: LedgerKind,
+    pub amount: f64,
+}
+
+/// A login session to store; the token handed to the client is derived from `id`.
+#[derive(Debug, Clone)]
+pub struct NewSession {
+    pub id: String,
+    pub player_id: i64,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A session that has not been revoked. Whether it has expired is up to the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveSession {
+    pub player_id: i64,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An idempotency key claimed by an earlier request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotencyRecord {
+    pub request_hash: String,
+    /// `None` while the first request is still being processed.
+    pub response_json: Option<String>,
+    /// The spin the first request recorded, once it has.
+    pub spin_id: Option<i64>,
+}
+
+/// A significant RTP deviation to record.
+#[derive(Debug, Clone)]
+pub struct NewAlert {
+    pub game: String,
+    pub config_hash: String,
+    pub spins: i64,
+    pub observed_rtp: f64,
+    pub theoretical_rtp: f64,
+    pub z_score: f64,
+}
+
+/// Connection pool usage, for backends that have one.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub max: u32,
+}
+
+/// A Merkle root to record (see [`commit_merkle_root`]).
+#[derive(Debug, Clone)]
+pub struct NewMerkleRoot {
+    pub ts: DateTime<Utc>,
+    pub first_spin_id: i64,
+    pub last_spin_id: i64,
+    pub leaf_count: i64,
+    pub chain_length: i64,
+    pub root: String,
+}
+
+/// A named admin API key. Only the SHA-256 of its secret is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdminKey {
+    pub id: i64,
+    /// Public part of the key, also printed with it.
+    pub key_id: String,
+    pub name: String,
+    pub role: AdminRole,
+    pub secret_hash: String,
+    /// The key it signs requests with, sealed (see [`open_signing_key`]);
+    /// `None` for keys made before requests could be signed.
+    pub sealed_signing_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl AdminKey {
+    /// What the audit trail keeps of a key: never its secrets.
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::json!({
+            "key_id": self.key_id,
+            "name": self.name,
+            "role": self.role.as_str(),
+            "expires_at": self.expires_at,
+            "revoked_at": self.revoked_at,
+        })
+    }
+}
+
+/// An admin key to store (see [`generate_admin_key`]).
+#[derive(Debug, Clone)]
+pub struct NewAdminKey {
+    pub key_id: String,
+    pub name: String,
+    pub role: AdminRole,
+    pub secret_hash: String,
+    pub sealed_signing_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// An admin action to add to the audit trail.
+#[derive(Debug, Clone)]
+pub struct NewAdminAudit {
+    pub ts: DateTime<Utc>,
+    pub actor: String,
+    pub action: String,
+    pub target: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// The admin behind a change, for the store methods that add it to the audit
+/// trail in the same transaction as the change itself.
+#[derive(Debug, Clone)]
+pub struct AdminChange {
+    pub actor: String,
+    /// Audit action, e.g. `games.set_limits`.
+    pub action: String,
+}
+
+impl AdminChange {
+    pub fn new(actor: impl Into<String>, action: impl Into<String>) -> Self {
+        Self {
+            actor: actor.into(),
+            action: action.into(),
+        }
+    }
+
+    /// The audit entry of a change to a game, with snapshots of its summary
+    /// before (`None` when the change created it) and after.
+    fn game_entry(
+        &self,
+        ts: DateTime<Utc>,
+        before: Option<&GameRecord>,
+        after: &GameRecord,
+    ) -> anyhow::Result<NewAdminAudit> {
+        Ok(NewAdminAudit {
+            ts,
+            actor: self.actor.clone(),
+            action: self.action.clone(),
+            target: Some(after.slug.clone()),
+            before: before
+                .map(|g| serde_json::to_value(g.summary()))
+                .transpose()?,
+            after: Some(serde_json::to_value(after.summary())?),
+        })
+    }
+
+    /// The audit entry of a change to an admin key, with snapshots of it
+    /// before (`None` when the change created it) and after.
+    fn key_entry(
+        &self,
+        ts: DateTime<Utc>,
+        before: Option<&AdminKey>,
+        after: &AdminKey,
+    ) -> NewAdminAudit {
+        NewAdminAudit {
+            ts,
+            actor: self.actor.clone(),
+            action: self.action.clone(),
+            target: Some(after.name.clone()),
+            before: before.map(AdminKey::snapshot),
+            after: Some(after.snapshot()),
+        }
+    }
+
+    /// The audit entry of sealing `target`'s secret anew: which vault key it
+    /// was sealed under before (`null` for plaintext) and after.
+    fn reseal_entry(&self, target: &str, current: &str, sealed: &str) -> NewAdminAudit {
+        let under = |stored| serde_json::json!({ "sealed_under": vault::sealed_key_id(stored) });
+        NewAdminAudit {
+            ts: Utc::now(),
+            actor: self.actor.clone(),
+            action: self.action.clone(),
+            target: Some(target.to_string()),
+            before: Some(under(current)),
+            after: Some(under(sealed)),
+        }
+    }
+
+    /// The audit entry of voiding a round, given the voided round.
+    fn void_entry(&self, voided: &Round) -> anyhow::Result<NewAdminAudit> {
+        // voiding only sets the status and the void fields; a round was settled
+        // before exactly when it has a settle time
+        let before = Round {
+            status: if voided.settled_at.is_some() {
+                RoundStatus::Settled
+            } else {
+                RoundStatus::Open
+            },
+            voided_at: None,
+            void_reason: None,
+            ..voided.clone()
+        };
+        Ok(NewAdminAudit {
+            ts: voided.voided_at.unwrap_or_else(Utc::now),
+            actor: self.actor.clone(),
+            action: self.action.clone(),
+            target: Some(format!("rounds/{}", voided.id)),
+            before: Some(serde_json::to_value(before)?),
+            after: Some(serde_json::to_value(voided)?),
+        })
+    }
+}
+
+/// The ledger rows a spin produces: the wager when it opens a round and, when
+/// it won, its payout.
+fn spin_ledger(spin: &NewSpin) -> Vec<(LedgerKind, f64)> {
+    let mut entries = Vec::new();
+    if spin.round_id.is_none() {
+        entries.push((LedgerKind::Wager, -spin.bet));
+    }
+    if spin.payout > 0.0 {
+        entries.push((LedgerKind::Payout, spin.payout));
+    }
+    entries
+}
+
+/// The ledger rows that void a round: its wager refunded and whatever it paid
+/// taken back, so the round nets to zero.
+fn void_ledger(round: &Round) -> Vec<(LedgerKind, f64)> {
+    let mut entries = vec![(LedgerKind::Refund, round.bet)];
+    if round.payout > 0.0 {
+        entries.push((LedgerKind::Reversal, -round.payout));
+    }
+    entries
+}
+
+/// The part of a logged spin that its chain hash covers.
+pub fn chained_spin(spin: &SpinLogEntry) -> ChainedSpin<'_> {
+    ChainedSpin {
+        id: spin.id,
+        ts: spin.ts.to_rfc3339_opts(SecondsFormat::Micros, true),
+        game: &spin.game,
+        player_id: spin.player_id,
+        client_seed: &spin.client_seed,
+        nonce: spin.nonce,
+        server_seed_hash: &spin.server_seed_hash,
+        config_hash: spin.config_hash.as_deref(),
+        result_reels: &spin.result_reels,
+        payout: spin.payout,
+        round_id: spin.round_id,
+        cursor: spin.cursor,
+        action: spin.action.as_deref(),
+    }
+}
+
+/// Link a freshly inserted spin to the chain head, returning the new head.
+fn seal_spin(spin: &mut SpinLogEntry, head: &ChainHead) -> ChainHead {
+    spin.prev_hash = head.head_hash.clone();
+    spin.row_hash = chained_spin(spin).row_hash(&spin.prev_hash);
+    ChainHead {
+        length: head.length + 1,
+        last_spin_id: Some(spin.id),
+        head_hash: spin.row_hash.clone(),
+    }
+}
+
+/// A fresh 256-bit server seed from the OS CSPRNG. Seeds are never supplied by a person.
+pub fn generate_seed() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// SHA-256 of an admin key's secret, as stored.
+pub fn admin_secret_hash(secret: &str) -> String {
+    tigrinho_core::derive_hash_hex(secret.as_bytes())
+}
+
+/// What a signing key is sealed against, so it opens only for its own key.
+/// Slugs have no `/`, so this never matches a game's seed.
+fn signing_key_aad(key_id: &str) -> String {
+    format!("admin-key/{key_id}")
+}
+
+/// The key an admin key signs requests with
+/// ([`tigrinho_shared::signing::admin_signing_key`]), `None` for keys made
+/// before requests could be signed.
+pub fn open_signing_key(vault: &SeedVault, key: &AdminKey) -> anyhow::Result<Option<Vec<u8>>> {
+    let Some(sealed) = &key.sealed_signing_key else {
+        return Ok(None);
+    };
+    let signing_key = vault.open(&signing_key_aad(&key.key_id), sealed)?;
+    Ok(Some(hex::decode(signing_key)?))
+}
+
+/// A fresh admin key: the record to store and the key to hand out, once, as
+/// `<key_id>.<secret>`. Its signing key is sealed under the vault's active key.
+pub fn generate_admin_key(
+    name: &str,
+    role: AdminRole,
+    expires_at: Option<DateTime<Utc>>,
+    vault: &SeedVault,
+) -> (NewAdminKey, String) {
+    let mut key_id = [0u8; 8];
+    rand::rngs::OsRng.fill_bytes(&mut key_id);
+    let key_id = hex::encode(key_id);
+    let secret = generate_seed();
+    let key = format!("{key_id}.{secret}");
+    let sealed_signing_key = vault.seal(
+        &signing_key_aad(&key_id),
+        &hex::encode(tigrinho_shared::signing::admin_signing_key(&secret)),
+    );
+    let record = NewAdminKey {
+        key_id,
+        name: name.to_string(),
+        role,
+        secret_hash: admin_secret_hash(&secret),
+        sealed_signing_key: Some(sealed_signing_key),
+        created_at: Utc::now(),
+        expires_at,
+    };
+    (record, key)
+}
+
+/// Games, their configs and seeds, spins and the ledger, plus the players,
+/// sessions, idempotency keys and RTP alerts around them, and the admin keys
+/// and audit trail.
+///
+/// Every method that changes more than one record does so atomically.
+#[async_trait]
+pub trait GameStore: Send + Sync {
+    /// Bring the schema up to date and backfill what older schemas left empty.
+    async fn migrate(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Connection pool usage; `None` for backends without a pool.
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+
+    /// All games, ordered by slug.
+    async fn list_games(&self) -> anyhow::Result<Vec<GameRecord>>;
+
+    async fn game(&self, slug: &str) -> anyhow::Result<Option<GameRecord>>;
+
+    /// Create a game under a freshly generated seed sealed by `vault`, audited
+    /// as `change`; `false` if the slug is taken.
+    async fn create_game(
+        &self,
+        game: &NewGame,
+        vault: &SeedVault,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool>;
+
+    /// Audited as `change`; `false` if there is no such game.
+    async fn set_limits(
+        &self,
+        slug: &str,
+        min_bet: f64,
+        max_bet: f64,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool>;
+
+    /// Store a definition as a new immutable version and return its hash.
+    /// Storing identical content twice yields the same version.
+    async fn insert_config(&self, def: &GameDefinition) -> anyhow::Result<String>;
+
+    /// A stored definition, checked against its hash.
+    async fn config(&self, hash: &str) -> anyhow::Result<Option<GameDefinition>>;
+
+    /// Audited as `change` when an admin made it; `false` if there is no such game.
+    async fn set_active_config(
+        &self,
+        slug: &str,
+        hash: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool>;
+
+    /// Take the next nonce of a game together with the seed and config it
+    /// belongs to, so a concurrent spin or seed rotation can never hand out the
+    /// same pair twice. The returned record carries the reserved nonce.
+    async fn reserve_nonce(&self, slug: &str) -> anyhow::Result<Option<GameRecord>>;
+
+    /// Replace a game's server seed with a freshly generated one, reveal the old
+    /// one and record the rotation. With `only_if_hash`, nothing happens unless
+    /// that seed is still the current one, so racing triggers rotate at most once.
+    /// `vault` opens the old seed and seals the new one. Audited as `change`
+    /// when an admin made it.
+    async fn rotate_seed(
+        &self,
+        slug: &str,
+        trigger: RotationTrigger,
+        only_if_hash: Option<&str>,
+        vault: &SeedVault,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<Option<SeedRotation>>;
+
+    /// Store a game's seed sealed anew, unless its stored form is no longer
+    /// `current`, audited as `change` if given. `false` if nothing was replaced.
+    async fn replace_sealed_seed(
+        &self,
+        slug: &str,
+        current: &str,
+        sealed: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool>;
+
+    /// Most recent rotations of a game, newest first.
+    async fn seed_rotations(&self, slug: &str, limit: u32) -> anyhow::Result<Vec<SeedRotation>>;
+
+    /// The rotation that retired the game's seed with this hash, revealing it.
+    async fn seed_rotation_of(
+        &self,
+        slug: &str,
+        seed_hash: &str,
+    ) -> anyhow::Result<Option<SeedRotation>>;
+
+    /// Append a spin and its ledger entries, chaining it to the previous spin
+    /// and advancing the chain head. Opens the spin's round or adds to it,
+    /// settles it if asked, and starts or advances its game session. `None`,
+    /// recording nothing, if a continued round is not an open round of the
+    /// spin's game and player, or its session is no longer at the spin's step.
+    async fn record_spin(&self, spin: &NewSpin) -> anyhow::Result<Option<SpinLogEntry>>;
+
+    async fn game_session(&self, round_id: i64) -> anyhow::Result<Option<GameSession>>;
+
+    /// A player's sessions whose feature is still in progress, oldest first.
+    async fn game_sessions(&self, player_id: i64) -> anyhow::Result<Vec<GameSession>>;
+
+    /// Up to `limit` spins matching the filters of `q` (its `limit` is
+    /// ignored), newest first, optionally restricted to a player.
+    async fn spins(
+        &self,
+        q: &SpinQuery,
+        player_id: Option<i64>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<SpinLogEntry>>;
+
+    async fn spin(&self, id: i64) -> anyhow::Result<Option<SpinLogEntry>>;
+
+    /// Up to `limit` spins with an id above `after_id`, oldest first.
+    async fn spins_after(&self, after_id: i64, limit: u32) -> anyhow::Result<Vec<SpinLogEntry>>;
+
+    /// The last link of the spin log hash chain.
+    async fn chain_head(&self) -> anyhow::Result<ChainHead>;
+
+    /// `(id, row_hash)` of the spins with `after_id < id <= through_id`, oldest first.
+    async fn row_hashes(
+        &self,
+        after_id: i64,
+        through_id: i64,
+    ) -> anyhow::Result<Vec<(i64, String)>>;
+
+    /// Record a Merkle root. Fails if its batch starts or ends where a
+    /// recorded one does.
+    async fn insert_merkle_root(&self, root: &NewMerkleRoot) -> anyhow::Result<MerkleRoot>;
+
+    /// Most recent Merkle roots, newest first.
+    async fn merkle_roots(&self, limit: u32) -> anyhow::Result<Vec<MerkleRoot>>;
+
+    /// The Merkle root whose batch contains a spin.
+    async fn merkle_root_of(&self, spin_id: i64) -> anyhow::Result<Option<MerkleRoot>>;
+
+    /// A player's most recent ledger entries, newest first.
+    async fn ledger(&self, player_id: i64, limit: u32) -> anyhow::Result<Vec<LedgerEntry>>;
+
+    async fn round(&self, id: i64) -> anyhow::Result<Option<Round>>;
+
+    /// Up to `limit` rounds matching the filters of `q` (its `limit` is
+    /// ignored), newest first.
+    async fn rounds(&self, q: &RoundQuery, limit: u32) -> anyhow::Result<Vec<Round>>;
+
+    /// Void a round, open or settled, appending the ledger entries that refund
+    /// it and ending its game session, audited as `change`. `None` if there is
+    /// no such round or it already was voided.
+    async fn void_round(
+        &self,
+        id: i64,
+        at: DateTime<Utc>,
+        reason: &str,
+        change: &AdminChange,
+    ) -> anyhow::Result<Option<Round>>;
+
+    /// A game's rounds opened in `[from, to)`, totalled per status. Statuses
+    /// without rounds are left out.
+    async fn round_totals(
+        &self,
+        game: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<(RoundStatus, RoundTotals)>>;
+
+    /// `None` if the username is taken.
+    async fn create_player(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> anyhow::Result<Option<PlayerInfo>>;
+
+    async fn player(&self, id: i64) -> anyhow::Result<Option<PlayerInfo>>;
+
+    /// Replace the player's client seed; `None` if there is no such player.
+    async fn set_client_seed(
+        &self,
+        player_id: i64,
+        client_seed: &str,
+    ) -> anyhow::Result<Option<PlayerInfo>>;
+
+    /// Id and password hash of the player with this username.
+    async fn player_credentials(&self, username: &str) -> anyhow::Result<Option<(i64, String)>>;
+
+    async fn create_session(&self, session: &NewSession) -> anyhow::Result<()>;
+
+    /// The session with this id, unless it was revoked.
+    async fn session(&self, id: &str) -> anyhow::Result<Option<LiveSession>>;
+
+    async fn revoke_session(&self, id: &str) -> anyhow::Result<()>;
+
+    /// Revoke every live session of a player.
+    async fn revoke_player_sessions(&self, player_id: i64) -> anyhow::Result<()>;
+
+    /// Claim `key` for a request, first forgetting keys older than `ttl`.
+    /// Returns `None` when the claim succeeded and the earlier claim otherwise.
+    async fn claim_idempotency_key(
+        &self,
+        player_id: i64,
+        key: &str,
+        request_hash: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<Option<IdempotencyRecord>>;
+
+    /// Store the response of a claimed key.
+    async fn complete_idempotency_key(
+        &self,
+        player_id: i64,
+        key: &str,
+        response_json: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Drop a claim that has neither a response nor a recorded spin yet.
+    async fn release_idempotency_key(&self, player_id: i64, key: &str) -> anyhow::Result<()>;
+
+    /// Record an alert and, with `suspend`, suspend its game in the same transaction.
+    async fn raise_alert(&self, alert: &NewAlert, suspend: bool) -> anyhow::Result<RtpAlert>;
+
+    /// Clear a game's suspension, audited as `change`; `false` if there is no
+    /// such game.
+    async fn resume_game(&self, slug: &str, change: &AdminChange) -> anyhow::Result<bool>;
+
+    /// The most recent alerts across all games, newest first.
+    async fn rtp_alerts(&self, limit: u32) -> anyhow::Result<Vec<RtpAlert>>;
+
+    /// Store a new key, audited as `change`; `None` if the name is taken.
+    async fn create_admin_key(
+        &self,
+        key: &NewAdminKey,
+        change: &AdminChange,
+    ) -> anyhow::Result<Option<AdminKey>>;
+
+    /// The key with this public id, whether or not it is still valid.
+    async fn admin_key(&self, key_id: &str) -> anyhow::Result<Option<AdminKey>>;
+
+    /// All admin keys, oldest first.
+    async fn admin_keys(&self) -> anyhow::Result<Vec<AdminKey>>;
+
+    /// Revoke the key with this name, audited as `change`; `false` if there is
+    /// no such unrevoked key.
+    async fn revoke_admin_key(
+        &self,
+        name: &str,
+        at: DateTime<Utc>,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool>;
+
+    /// Swap the sealed signing key of an admin key, only if it still is
+    /// `current`, audited as `change` if given.
+    async fn replace_sealed_signing_key(
+        &self,
+        key_id: &str,
+        current: &str,
+        sealed: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool>;
+
+    /// Accept a request signature once: `false` if it was already accepted and
+    /// has not yet expired. Expired signatures are forgotten.
+    async fn claim_admin_signature(
+        &self,
+        key_id: &str,
+        signature: &str,
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// Append an entry to the admin audit trail.
+    async fn record_admin_action(&self, entry: &NewAdminAudit) -> anyhow::Result<AdminAuditEntry>;
+
+    /// The most recent admin actions, newest first.
+    async fn admin_audit(&self, limit: u32) -> anyhow::Result<Vec<AdminAuditEntry>>;
+
+    /// The active configuration of a game and its hash.
+    async fn active_config(&self, slug: &str) -> anyhow::Result<Option<(String, GameDefinition)>> {
+        let Some(game) = self.game(slug).await? else {
+            return Ok(None);
+        };
+        let hash = game
+            .active_config_hash
+            .ok_or_else(|| anyhow::anyhow!("game {slug} has no active config"))?;
+        let def = self
+            .config(&hash)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("config {hash} is missing"))?;
+        Ok(Some((hash, def)))
+    }
+}
+
+/// Open the backend a database URL names (`sqlite:` or `postgres:`) without
+/// migrating it.
+pub async fn connect(url: &str, max_connections: u32) -> anyhow::Result<Arc<dyn GameStore>> {
+    let scheme = url.split(':').next().unwrap_or_default();
+    Ok(match scheme {
+        "sqlite" => Arc::new(SqliteStore::connect(url, max_connections).await?),
+        "postgres" | "postgresql" => Arc::new(PgStore::connect(url, max_connections).await?),
+        // the URL itself may carry a password
+        _ => {
+            anyhow::bail!("unsupported database URL scheme {scheme:?}, expected sqlite or postgres")
+        }
+    })
+}
+
+/// Spins read per round trip while verifying the chain.
+const VERIFY_BATCH: u32 = 1_000;
+
+/// Walk the whole spin log and check every link, plus that the log passes
+/// through the store's chain head and, if given, a head published earlier.
+/// Returns the head the walk ended at.
+pub async fn verify_spin_chain(
+    store: &dyn GameStore,
+    published: Option<&ChainHead>,
+) -> anyhow::Result<ChainHead> {
+    let mut verifier = ChainVerifier::new();
+    for head in std::iter::once(store.chain_head().await?).chain(published.cloned()) {
+        verifier.expect_head(head.length.try_into()?, &head.head_hash);
+    }
+    let mut last_spin_id = None;
+    loop {
+        let spins = store
+            .spins_after(last_spin_id.unwrap_or(0), VERIFY_BATCH)
+            .await?;
+        let Some(last) = spins.last() else {
+            break;
+        };
+        last_spin_id = Some(last.id);
+        for spin in &spins {
+            verifier.push(&chained_spin(spin), &spin.prev_hash, &spin.row_hash)?;
+        }
+    }
+    verifier.finish()?;
+    Ok(ChainHead {
+        length: verifier.length() as i64,
+        last_spin_id,
+        head_hash: verifier.head().to_string(),
+    })
+}
+
+/// Seal the seeds still stored in plaintext and re-wrap the others under the
+/// vault's active key, without decrypting any of them, auditing each as
+/// `change` if given. Returns how many changed.
+pub async fn reseal_seeds(
+    store: &dyn GameStore,
+    vault: &SeedVault,
+    change: Option<&AdminChange>,
+) -> anyhow::Result<usize> {
+    let mut changed = 0;
+    for game in store.list_games().await? {
+        let mut current = game.sealed_seed;
+        while let Some(sealed) = vault.reseal(&game.slug, &current)? {
+            if store
+                .replace_sealed_seed(&game.slug, &current, &sealed, change)
+                .await?
+            {
+                changed += 1;
+                break;
+            }
+            // a rotation replaced the seed meanwhile; reseal the new one
+            match store.game(&game.slug).await? {
+                Some(game) => current = game.sealed_seed,
+                None => break,
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Re-wrap the sealed signing keys of admin keys under the vault's active key,
+/// like [`reseal_seeds`]. Returns how many changed.
+pub async fn reseal_admin_keys(
+    store: &dyn GameStore,
+    vault: &SeedVault,
+    change: Option<&AdminChange>,
+) -> anyhow::Result<usize> {
+    let mut changed = 0;
+    for key in store.admin_keys().await? {
+        let Some(current) = &key.sealed_signing_key else {
+            continue;
+        };
+        // signing keys never change once made, so there is no race to retry
+        if let Some(sealed) = vault.reseal(&signing_key_aad(&key.key_id), current)? {
+            if store
+                .replace_sealed_signing_key(&key.key_id, current, &sealed, change)
+                .await?
+            {
+                changed += 1;
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Commit a Merkle root over the spins chained since the last committed batch,
+/// up to the current chain head. `None` when there are none.
+pub async fn commit_merkle_root(store: &dyn GameStore) -> anyhow::Result<Option<MerkleRoot>> {
+    let head = store.chain_head().await?;
+    let Some(through) = head.last_spin_id else {
+        return Ok(None);
+    };
+    let after = store
+        .merkle_roots(1)
+        .await?
+        .first()
+        .map_or(0, |r| r.last_spin_id);
+    let batch = store.row_hashes(after, through).await?;
+    let Some(&(first_spin_id, _)) = batch.first() else {
+        return Ok(None);
+    };
+    let hashes: Vec<&str> = batch.iter().map(|(_, h)| h.as_str()).collect();
+    let tree = MerkleTree::new(&hashes);
+    let root = store
+        .insert_merkle_root(&NewMerkleRoot {
+            ts: Utc::now(),
+            first_spin_id,
+            last_spin_id: through,
+            leaf_count: batch.len() as i64,
+            chain_length: head.length,
+            root: tree.root().expect("non-empty batch"),
+        })
+        .await?;
+    Ok(Some(root))
+}
+
+/// Inclusion proof of a spin in its committed batch; `None` when the spin
+/// does not exist or is not committed yet. Fails if the batch no longer hashes
+/// to its root.
+pub async fn spin_proof(store: &dyn GameStore, spin_id: i64) -> anyhow::Result<Option<SpinProof>> {
+    let Some(root) = store.merkle_root_of(spin_id).await? else {
+        return Ok(None);
+    };
+    let batch = store
+        .row_hashes(root.first_spin_id - 1, root.last_spin_id)
+        .await?;
+    let Some(index) = batch.iter().position(|(id, _)| *id == spin_id) else {
+        return Ok(None);
+    };
+    let hashes: Vec<&str> = batch.iter().map(|(_, h)| h.as_str()).collect();
+    let tree = MerkleTree::new(&hashes);
+    anyhow::ensure!(
+        tree.root().as_deref() == Some(root.root.as_str()),
+        "spins {}..={} no longer match Merkle root {}",
+        root.first_spin_id,
+        root.last_spin_id,
+        root.id
+    );
+    Ok(Some(SpinProof {
+        spin_id,
+        row_hash: batch[index].1.clone(),
+        root_id: root.id,
+        root: root.root,
+        leaf_index: index as u64,
+        leaf_count: batch.len() as u64,
+        siblings: tree.proof(index).expect("index within the batch"),
+    }))
+}
+
+/// A game's rounds over a period, voided ones apart from the rest.
+pub async fn round_report(
+    store: &dyn GameStore,
+    game: &str,
+    q: &ReportQuery,
+) -> anyhow::Result<RoundReport> {
+    let mut report = RoundReport {
+        game: game.to_string(),
+        from: q.from,
+        to: q.to,
+        settled: RoundTotals::default(),
+        open: RoundTotals::default(),
+        voided: RoundTotals::default(),
+        rtp: None,
+    };
+    for (status, totals) in store.round_totals(game, q.from, q.to).await? {
+        match status {
+            RoundStatus::Settled => report.settled = totals,
+            RoundStatus::Open => report.open = totals,
+            RoundStatus::Voided => report.voided = totals,
+        }
+    }
+    if report.settled.wagered > 0.0 {
+        report.rtp = Some(report.settled.paid / report.settled.wagered);
+    }
+    Ok(report)
+}
+
+/// Give games without a config version (fresh databases) the default game.
+async fn ensure_active_configs(store: &dyn GameStore) -> anyhow::Result<()> {
+    for game in store.list_games().await? {
+        if game.active_config_hash.is_none() {
+            let hash = store.insert_config(&GameDefinition::default_game()).await?;
+            store.set_active_config(&game.slug, &hash, None).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether a spin passes the filters of a [`SpinQuery`], for backends that
+/// filter in process.
+fn matches(q: &SpinQuery, player_id: Option<i64>, spin: &SpinLogEntry) -> bool {
+    player_id.is_none_or(|p| spin.player_id == Some(p))
+        && q.cursor.is_none_or(|c| spin.id < c)
+        && q.from.is_none_or(|from| spin.ts >= from)
+        && q.to.is_none_or(|to| spin.ts < to)
+        && q.game.as_ref().is_none_or(|g| &spin.game == g)
+        && q.client_seed
+            .as_ref()
+            .is_none_or(|c| &spin.client_seed == c)
+        && q.server_seed_hash
+            .as_ref()
+            .is_none_or(|h| &spin.server_seed_hash == h)
+        && q.min_payout.is_none_or(|min| spin.payout >= min)
+        && q.max_payout.is_none_or(|max| spin.payout <= max)
+        && q.round_id.is_none_or(|r| spin.round_id == Some(r))
+}
+
+/// Whether a round passes the filters of a [`RoundQuery`], for backends that
+/// filter in process.
+fn round_matches(q: &RoundQuery, round: &Round) -> bool {
+    q.game.as_ref().is_none_or(|g| &round.game == g)
+        && q.player_id.is_none_or(|p| round.player_id == p)
+        && q.status.is_none_or(|s| round.status == s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin(game: &str, config_hash: &str, player_id: i64, payout: f64) -> NewSpin {
+        NewSpin {
+            ts: Utc::now(),
+            game: game.into(),
+            player_id,
+            client_seed: "c".into(),
+            nonce: 1,
+            server_seed_hash: "h".into(),
+            config_hash: config_hash.into(),
+            result_reels: vec![vec![0, 1, 2]],
+            bet: 2.0,
+            payout,
+            round_id: None,
+            settle: true,
+            session: None,
+            idempotency_key: None,
+        }
+    }
+
+    // The behaviour every backend must share, one check per feature. Each
+    // check makes its own players and games, so the backend tests run them
+    // all against one store.
+
+    /// A key of the seed keyring, `<id>:<32 bytes of byte>`.
+    fn keyring(id: &str, byte: &str) -> String {
+        format!("{id}:{}", byte.repeat(32))
+    }
+
+    fn test_vault() -> SeedVault {
+        SeedVault::parse(&keyring("k1", "11")).unwrap()
+    }
+
+    async fn new_player(store: &dyn GameStore, name: &str) -> PlayerInfo {
+        store.create_player(name, "hash").await.unwrap().unwrap()
+    }
+
+    /// A new game on the default game's config, which is returned.
+    async fn new_game(store: &dyn GameStore, slug: &str, vault: &SeedVault) -> String {
+        let (hash, _) = store.active_config(DEFAULT_GAME).await.unwrap().unwrap();
+        let game = NewGame {
+            slug: slug.into(),
+            name: slug.into(),
+            config_hash: hash.clone(),
+            min_bet: 1.0,
+            max_bet: 5.0,
+        };
+        let create = AdminChange::new("owner", "games.create");
+        assert!(store.create_game(&game, vault, &create).await.unwrap());
+        hash
+    }
+
+    /// Seeds migrated in plaintext are sealed once, then re-wrapped under a
+    /// new key without changing. Expects the seeds as migrated, so it runs
+    /// first.
+    async fn seed_vault(store: &dyn GameStore) {
+        let game = store.game(DEFAULT_GAME).await.unwrap().unwrap();
+        assert_eq!(game.sealed_seed, DEV_SERVER_SEED);
+        assert_eq!(
+            game.server_seed_hash,
+            tigrinho_core::derive_hash_hex(DEV_SERVER_SEED.as_bytes())
+        );
+        let vault = test_vault();
+        assert!(vault.open(DEFAULT_GAME, &game.sealed_seed).is_err());
+        assert_eq!(reseal_seeds(store, &vault, None).await.unwrap(), 1);
+        assert_eq!(reseal_seeds(store, &vault, None).await.unwrap(), 0);
+        let game = store.game(DEFAULT_GAME).await.unwrap().unwrap();
+        assert_eq!(
+            vault.open(DEFAULT_GAME, &game.sealed_seed).unwrap(),
+            DEV_SERVER_SEED
+        );
+        assert!(!store
+            .replace_sealed_seed(DEFAULT_GAME, DEV_SERVER_SEED, "x", None)
+            .await
+            .unwrap());
+
+        // rotating the KEK re-wraps every seed; the new key alone opens them
+        let next = keyring("k2", "22");
+        let rewrapping = SeedVault::parse(&format!("{next},{}", keyring("k1", "11"))).unwrap();
+        let rekey = AdminChange::new("cli", "seeds.rekey");
+        assert_eq!(
+            reseal_seeds(store, &rewrapping, Some(&rekey))
+                .await
+                .unwrap(),
+            1
+        );
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(
+            (audit[0].action.as_str(), audit[0].target.as_deref()),
+            ("seeds.rekey", Some(DEFAULT_GAME))
+        );
+        assert_eq!(audit[0].before.as_ref().unwrap()["sealed_under"], "k1");
+        assert_eq!(audit[0].after.as_ref().unwrap()["sealed_under"], "k2");
+        let next = SeedVault::parse(&next).unwrap();
+        let rewrapped = store.game(DEFAULT_GAME).await.unwrap().unwrap();
+        assert_eq!(
+            next.open(DEFAULT_GAME, &rewrapped.sealed_seed).unwrap(),
+            DEV_SERVER_SEED
+        );
+        assert!(vault.open(DEFAULT_GAME, &rewrapped.sealed_seed).is_err());
+    }
+
+    async fn players(store: &dyn GameStore) {
+        let a = store.create_player("ana", "hash-a").await.unwrap().unwrap();
+        assert!(store.create_player("ana", "x").await.unwrap().is_none());
+        assert_eq!(store.player(a.id).await.unwrap().unwrap().username, "ana");
+        assert_eq!(
+            store.player_credentials("ana").await.unwrap(),
+            Some((a.id, "hash-a".to_string()))
+        );
+        assert!(store.player_credentials("nope").await.unwrap().is_none());
+        assert_eq!(a.client_seed, None);
+        let seeded = store.set_client_seed(a.id, "mine").await.unwrap().unwrap();
+        assert_eq!(seeded.client_seed.as_deref(), Some("mine"));
+        assert_eq!(
+            store.player(a.id).await.unwrap().unwrap().client_seed,
+            seeded.client_seed
+        );
+        assert!(store
+            .set_client_seed(a.id + 1000, "x")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    /// Keys are kept by the hash of their secret, and each change to one is
+    /// audited with the key before and after.
+    async fn admin_keys(store: &dyn GameStore) {
+        let vault = test_vault();
+        let (ops, token) = generate_admin_key("ops", AdminRole::Operator, None, &vault);
+        let create = AdminChange::new("cli", "admin_keys.create");
+        let created = store
+            .create_admin_key(&ops, &create)
+            .await
+            .unwrap()
+            .unwrap();
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(audit[0].action, "admin_keys.create");
+        assert_eq!(audit[0].target.as_deref(), Some("ops"));
+        assert!(audit[0].before.is_none());
+        assert_eq!(audit[0].after.as_ref().unwrap()["role"], "operator");
+        let (taken, _) = generate_admin_key("ops", AdminRole::Owner, None, &vault);
+        assert!(store
+            .create_admin_key(&taken, &create)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(store.admin_audit(1).await.unwrap(), audit);
+        let (key_id, secret) = token.split_once('.').unwrap();
+        let found = store.admin_key(key_id).await.unwrap().unwrap();
+        assert_eq!(found, created);
+        assert_eq!(found.role, AdminRole::Operator);
+        assert_eq!(found.secret_hash, admin_secret_hash(secret));
+
+        let revoke = AdminChange::new("cli", "admin_keys.revoke");
+        assert!(store
+            .revoke_admin_key("ops", Utc::now(), &revoke)
+            .await
+            .unwrap());
+        assert!(!store
+            .revoke_admin_key("ops", Utc::now(), &revoke)
+            .await
+            .unwrap());
+        let revoked = store.admin_key(key_id).await.unwrap().unwrap();
+        assert!(revoked.revoked_at.is_some());
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(audit[0].action, "admin_keys.revoke");
+        assert!(audit[0].before.as_ref().unwrap()["revoked_at"].is_null());
+        assert!(audit[0].after.as_ref().unwrap()["revoked_at"].is_string());
+
+        let action = store
+            .record_admin_action(&NewAdminAudit {
+                ts: Utc::now(),
+                actor: "ops".into(),
+                action: "games.set_limits".into(),
+                target: Some("ox".into()),
+                before: Some(serde_json::json!({ "min_bet": 1.0 })),
+                after: Some(serde_json::json!({ "min_bet": 2.0 })),
+            })
+            .await
+            .unwrap();
+        let note = store
+            .record_admin_action(&NewAdminAudit {
+                ts: Utc::now(),
+                actor: "cli".into(),
+                action: "admin_keys.revoke".into(),
+                target: Some("ops".into()),
+                before: None,
+                after: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(store.admin_audit(2).await.unwrap(), vec![note, action]);
+    }
+
+    /// Each key signs with a key derived from its secret and stored sealed,
+    /// and a request signature is accepted once until it expires.
+    async fn admin_signatures(store: &dyn GameStore) {
+        let vault = test_vault();
+        let (signer, token) = generate_admin_key("signer", AdminRole::Operator, None, &vault);
+        let create = AdminChange::new("cli", "admin_keys.create");
+        let created = store
+            .create_admin_key(&signer, &create)
+            .await
+            .unwrap()
+            .unwrap();
+        let (key_id, secret) = token.split_once('.').unwrap();
+        let signing_key = tigrinho_shared::signing::admin_signing_key(secret).to_vec();
+        assert_eq!(
+            open_signing_key(&vault, &created).unwrap(),
+            Some(signing_key.clone())
+        );
+        // every key is sealed under the test keyring, so a new key re-wraps all
+        let next = keyring("k2", "22");
+        let rewrapping = SeedVault::parse(&format!("{next},{}", keyring("k1", "11"))).unwrap();
+        let rekey = AdminChange::new("cli", "admin_keys.rekey");
+        let keys = store.admin_keys().await.unwrap().len();
+        assert_eq!(
+            reseal_admin_keys(store, &rewrapping, Some(&rekey))
+                .await
+                .unwrap(),
+            keys
+        );
+        assert_eq!(
+            reseal_admin_keys(store, &rewrapping, Some(&rekey))
+                .await
+                .unwrap(),
+            0
+        );
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(
+            (audit[0].action.as_str(), audit[0].target.as_deref()),
+            ("admin_keys.rekey", Some("signer"))
+        );
+        assert_eq!(audit[0].after.as_ref().unwrap()["sealed_under"], "k2");
+        let found = store.admin_key(key_id).await.unwrap().unwrap();
+        let next = SeedVault::parse(&next).unwrap();
+        assert_eq!(open_signing_key(&next, &found).unwrap(), Some(signing_key));
+
+        let window = Utc::now() + Duration::minutes(5);
+        assert!(store
+            .claim_admin_signature(key_id, "s1", window)
+            .await
+            .unwrap());
+        assert!(!store
+            .claim_admin_signature(key_id, "s1", window)
+            .await
+            .unwrap());
+        assert!(store
+            .claim_admin_signature("other", "s1", window)
+            .await
+            .unwrap());
+        let lapsed = Utc::now() - Duration::seconds(1);
+        assert!(store
+            .claim_admin_signature(key_id, "s2", lapsed)
+            .await
+            .unwrap());
+        assert!(store
+            .claim_admin_signature(key_id, "s2", window)
+            .await
+            .unwrap());
+    }
+
+    async fn sessions(store: &dyn GameStore) {
+        let p = new_player(store, "sam").await;
+        let expires_at = Utc::now() + Duration::hours(1);
+        for id in ["s1", "s2"] {
+            let session = NewSession {
+                id: id.into(),
+                player_id: p.id,
+                issued_at: Utc::now(),
+                expires_at,
+            };
+            store.create_session(&session).await.unwrap();
+        }
+        let live = store.session("s1").await.unwrap().unwrap();
+        assert_eq!(live.player_id, p.id);
+        assert_eq!(live.expires_at.timestamp(), expires_at.timestamp());
+        store.revoke_session("s1").await.unwrap();
+        assert!(store.session("s1").await.unwrap().is_none());
+        assert!(store.session("s2").await.unwrap().is_some());
+        store.revoke_player_sessions(p.id).await.unwrap();
+        assert!(store.session("s2").await.unwrap().is_none());
+    }
+
+    async fn idempotency_keys(store: &dyn GameStore) {
+        let (hash, _) = store.active_config(DEFAULT_GAME).await.unwrap().unwrap();
+        let a = new_player(store, "ida").await;
+        let b = new_player(store, "ivo").await;
+        let ttl = Duration::hours(1);
+        let claim = |key| store.claim_idempotency_key(a.id, key, "rh", ttl);
+        assert!(claim("k").await.unwrap().is_none());
+        let pending = claim("k").await.unwrap().unwrap();
+        assert_eq!(
+            (pending.request_hash.as_str(), pending.response_json),
+            ("rh", None)
+        );
+        store.release_idempotency_key(a.id, "k").await.unwrap();
+        assert!(claim("k").await.unwrap().is_none());
+        store
+            .complete_idempotency_key(a.id, "k", "{}")
+            .await
+            .unwrap();
+        // completed keys survive a release
+        store.release_idempotency_key(a.id, "k").await.unwrap();
+        let done = claim("k").await.unwrap().unwrap();
+        assert_eq!(done.response_json.as_deref(), Some("{}"));
+        // keys are per player
+        assert!(store
+            .claim_idempotency_key(b.id, "k", "rh", ttl)
+            .await
+            .unwrap()
+            .is_none());
+        // a spin recorded under a key is found from it, and pins the claim
+        assert!(claim("k2").await.unwrap().is_none());
+        let under_key = NewSpin {
+            idempotency_key: Some("k2".into()),
+            ..spin(DEFAULT_GAME, &hash, a.id, 0.0)
+        };
+        let spun = store.record_spin(&under_key).await.unwrap().unwrap();
+        store.release_idempotency_key(a.id, "k2").await.unwrap();
+        let recorded = claim("k2").await.unwrap().unwrap();
+        assert_eq!(
+            (recorded.response_json, recorded.spin_id),
+            (None, Some(spun.id))
+        );
+        let found = store.spin(spun.id).await.unwrap().unwrap();
+        assert_eq!(found.row_hash, spun.row_hash);
+        assert!(store.spin(spun.id + 1000).await.unwrap().is_none());
+    }
+
+    /// Games are created once and keep their own limits and nonces; each
+    /// change to one is audited with the game before and after.
+    async fn games(store: &dyn GameStore) {
+        let games = store.list_games().await.unwrap();
+        assert!(games.iter().any(|g| g.slug == DEFAULT_GAME));
+        let (hash, def) = store.active_config(DEFAULT_GAME).await.unwrap().unwrap();
+        assert_eq!(def.config_hash(), hash);
+        assert!(store.active_config("nope").await.unwrap().is_none());
+
+        let vault = test_vault();
+        let created = NewGame {
+            slug: "ox".into(),
+            name: "Ox".into(),
+            config_hash: hash.clone(),
+            min_bet: 1.0,
+            max_bet: 5.0,
+        };
+        let create = AdminChange::new("owner", "games.create");
+        assert!(store.create_game(&created, &vault, &create).await.unwrap());
+        assert!(!store.create_game(&created, &vault, &create).await.unwrap());
+        let limits = AdminChange::new("ops", "games.set_limits");
+        assert!(store.set_limits("ox", 2.0, 4.0, &limits).await.unwrap());
+        assert!(!store.set_limits("nope", 2.0, 4.0, &limits).await.unwrap());
+        assert_eq!(store.game("ox").await.unwrap().unwrap().min_bet, 2.0);
+        // each change is audited, and only once
+        let audit = store.admin_audit(2).await.unwrap();
+        assert_eq!(
+            (audit[0].action.as_str(), audit[0].actor.as_str()),
+            ("games.set_limits", "ops")
+        );
+        assert_eq!(audit[0].target.as_deref(), Some("ox"));
+        assert_eq!(audit[0].before.as_ref().unwrap()["min_bet"], 1.0);
+        assert_eq!(audit[0].after.as_ref().unwrap()["min_bet"], 2.0);
+        assert_eq!(audit[1].action, "games.create");
+        assert_eq!(
+            (audit[1].before.is_none(), audit[1].after.is_some()),
+            (true, true)
+        );
+
+        let first = store.reserve_nonce("ox").await.unwrap().unwrap();
+        let second = store.reserve_nonce("ox").await.unwrap().unwrap();
+        assert_eq!((first.nonce, second.nonce), (1, 2));
+        assert!(store.reserve_nonce("nope").await.unwrap().is_none());
+    }
+
+    async fn seed_rotation(store: &dyn GameStore) {
+        let vault = test_vault();
+        new_game(store, "rot", &vault).await;
+        let first = store.reserve_nonce("rot").await.unwrap().unwrap();
+        store.reserve_nonce("rot").await.unwrap().unwrap();
+
+        let rotate = AdminChange::new("ops", "seeds.rotate");
+        let stale = store
+            .rotate_seed(
+                "rot",
+                RotationTrigger::Admin,
+                Some("not-current"),
+                &vault,
+                Some(&rotate),
+            )
+            .await
+            .unwrap();
+        assert!(stale.is_none());
+        let rotation = store
+            .rotate_seed(
+                "rot",
+                RotationTrigger::Admin,
+                Some(&first.server_seed_hash),
+                &vault,
+                Some(&rotate),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(audit[0].action, "seeds.rotate");
+        assert_eq!(
+            audit[0].before.as_ref().unwrap()["server_seed_hash"],
+            first.server_seed_hash
+        );
+        assert_eq!(
+            audit[0].after.as_ref().unwrap()["server_seed_hash"],
+            rotation.new_seed_hash
+        );
+        assert_eq!(
+            rotation.revealed_seed,
+            vault.open("rot", &first.sealed_seed).unwrap()
+        );
+        assert_eq!(
+            tigrinho_core::derive_hash_hex(rotation.revealed_seed.as_bytes()),
+            first.server_seed_hash
+        );
+        assert_eq!(rotation.final_nonce, 2);
+        let rotated = store.game("rot").await.unwrap().unwrap();
+        assert_eq!(rotated.nonce, 0);
+        assert_eq!(rotated.server_seed_hash, rotation.new_seed_hash);
+        let new_seed = vault.open("rot", &rotated.sealed_seed).unwrap();
+        assert_eq!(
+            tigrinho_core::derive_hash_hex(new_seed.as_bytes()),
+            rotation.new_seed_hash
+        );
+        assert_eq!(store.seed_rotations("rot", 10).await.unwrap().len(), 1);
+        let retired = store
+            .seed_rotation_of("rot", &first.server_seed_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retired.id, rotation.id);
+        assert!(store
+            .seed_rotation_of("rot", &rotation.new_seed_hash)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(store
+            .seed_rotations(DEFAULT_GAME, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Spins are listed newest first and filtered, and each one is in the
+    /// ledger of its player.
+    async fn spin_history(store: &dyn GameStore) {
+        let hash = new_game(store, "hist", &test_vault()).await;
+        let a = new_player(store, "hana").await;
+        let b = new_player(store, "hugo").await;
+        let mut ids = Vec::new();
+        for (game, player, payout) in [
+            ("hist", a.id, 0.0),
+            (DEFAULT_GAME, a.id, 5.0),
+            ("hist", b.id, 1.0),
+        ] {
+            ids.push(
+                store
+                    .record_spin(&spin(game, &hash, player, payout))
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .id,
+            );
+        }
+        let latest = store.spins(&SpinQuery::default(), None, 3).await.unwrap();
+        assert_eq!(
+            latest.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![ids[2], ids[1], ids[0]]
+        );
+        let mine = store
+            .spins(&SpinQuery::default(), Some(a.id), 10)
+            .await
+            .unwrap();
+        assert_eq!(mine.len(), 2);
+        let q = SpinQuery {
+            game: Some("hist".into()),
+            min_payout: Some(0.5),
+            ..Default::default()
+        };
+        let filtered = store.spins(&q, None, 10).await.unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, ids[2]);
+        let after = store.spins_after(ids[0], 10).await.unwrap();
+        assert_eq!(
+            after.iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![ids[1], ids[2]]
+        );
+
+        let ledger = store.ledger(a.id, 10).await.unwrap();
+        let movements: Vec<_> = ledger.iter().map(|e| (e.kind, e.amount)).collect();
+        assert_eq!(
+            movements,
+            vec![
+                (LedgerKind::Payout, 5.0),
+                (LedgerKind::Wager, -2.0),
+                (LedgerKind::Wager, -2.0)
+            ]
+        );
+        assert_eq!(ledger[0].spin_id, Some(ids[1]));
+    }
+
+    /// Each spin links to the one before it, and the head follows the last.
+    async fn spin_chain(store: &dyn GameStore) {
+        let (hash, _) = store.active_config(DEFAULT_GAME).await.unwrap().unwrap();
+        let p = new_player(store, "chen").await;
+        let before = store.chain_head().await.unwrap();
+        let mut spins = Vec::new();
+        for payout in [0.0, 1.0] {
+            let spin = spin(DEFAULT_GAME, &hash, p.id, payout);
+            spins.push(store.record_spin(&spin).await.unwrap().unwrap());
+        }
+        assert_eq!(spins[0].prev_hash, before.head_hash);
+        assert_eq!(spins[1].prev_hash, spins[0].row_hash);
+        let head = store.chain_head().await.unwrap();
+        assert_eq!(
+            (head.length, head.last_spin_id),
+            (before.length + 2, Some(spins[1].id))
+        );
+        assert_eq!(head.head_hash, spins[1].row_hash);
+        assert_eq!(verify_spin_chain(store, Some(&head)).await.unwrap(), head);
+    }
+
+    /// A root covers the spins chained since the previous one, and proves
+    /// each of them.
+    async fn merkle_roots(store: &dyn GameStore) {
+        let (hash, _) = store.active_config(DEFAULT_GAME).await.unwrap().unwrap();
+        let p = new_player(store, "mia").await;
+        // commit what came before, so the next root is over these spins alone
+        commit_merkle_root(store).await.unwrap();
+        let mut spins = Vec::new();
+        for payout in [0.0, 5.0, 1.0] {
+            let spin = spin(DEFAULT_GAME, &hash, p.id, payout);
+            spins.push(store.record_spin(&spin).await.unwrap().unwrap());
+        }
+        let head = store.chain_head().await.unwrap();
+        let batch = commit_merkle_root(store).await.unwrap().unwrap();
+        assert_eq!(
+            (batch.first_spin_id, batch.last_spin_id, batch.leaf_count),
+            (spins[0].id, spins[2].id, 3)
+        );
+        assert_eq!(batch.chain_length, head.length);
+        assert!(commit_merkle_root(store).await.unwrap().is_none());
+        let proof = spin_proof(store, spins[1].id).await.unwrap().unwrap();
+        assert_eq!((proof.root_id, proof.leaf_index), (batch.id, 1));
+        assert_eq!(proof.row_hash, spins[1].row_hash);
+        assert!(tigrinho_core::verify_inclusion(
+            &proof.row_hash,
+            proof.leaf_index,
+            batch.leaf_count as u64,
+            &proof.siblings,
+            &batch.root
+        ));
+        let late = store
+            .record_spin(&spin(DEFAULT_GAME, &hash, p.id, 0.0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(spin_proof(store, late.id).await.unwrap().is_none());
+        let next = commit_merkle_root(store).await.unwrap().unwrap();
+        assert_eq!((next.first_spin_id, next.leaf_count), (late.id, 1));
+        assert_eq!(store.merkle_roots(2).await.unwrap(), vec![next, batch]);
+        assert!(spin_proof(store, late.id + 1).await.unwrap().is_none());
+    }
+
+    /// Alerts are kept newest first, and one can suspend its game until it
+    /// is resumed.
+    async fn rtp_alerts(store: &dyn GameStore) {
+        let hash = new_game(store, "hot", &test_vault()).await;
+        let alert = NewAlert {
+            game: "hot".into(),
+            config_hash: hash,
+            spins: 1000,
+            observed_rtp: 0.5,
+            theoretical_rtp: 0.96,
+            z_score: -7.5,
+        };
+        let first = store.raise_alert(&alert, false).await.unwrap();
+        assert!(store
+            .game("hot")
+            .await
+            .unwrap()
+            .unwrap()
+            .suspended_at
+            .is_none());
+        let second = store.raise_alert(&alert, true).await.unwrap();
+        assert!(second.suspended);
+        assert!(store
+            .game("hot")
+            .await
+            .unwrap()
+            .unwrap()
+            .suspended_at
+            .is_some());
+        let alerts = store.rtp_alerts(2).await.unwrap();
+        assert_eq!(
+            alerts.iter().map(|a| a.id).collect::<Vec<_>>(),
+            vec![second.id, first.id]
+        );
+        assert_eq!(alerts[1].z_score, -7.5);
+        let resume = AdminChange::new("ops", "games.resume");
+        assert!(store.resume_game("hot", &resume).await.unwrap());
+        assert!(!store.resume_game("nope", &resume).await.unwrap());
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(audit[0].action, "games.resume");
+        assert_eq!(audit[0].before.as_ref().unwrap()["suspended"], true);
+        assert_eq!(audit[0].after.as_ref().unwrap()["suspended"], false);
+        assert!(store
+            .game("hot")
+            .await
+            .unwrap()
+            .unwrap()
+            .suspended_at
+            .is_none());
+    }
+
+    /// Rounds settle with their last spin, can be voided once, and are
+    /// reported per game.
+    async fn rounds(store: &dyn GameStore) {
+        let hash = new_game(store, "reels", &test_vault()).await;
+        let p = new_player(store, "rhea").await;
+        let other = new_player(store, "rory").await;
+        let single = store
+            .record_spin(&spin("reels", &hash, p.id, 5.0))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // a multi-step round stays open until its last step settles it
+        let opening = NewSpin {
+            settle: false,
+            ..spin("reels", &hash, p.id, 1.0)
+        };
+        let round_id = store
+            .record_spin(&opening)
+            .await
+            .unwrap()
+            .unwrap()
+            .round_id
+            .unwrap();
+        let round = store.round(round_id).await.unwrap().unwrap();
+        assert_eq!(
+            (round.status, round.bet, round.payout),
+            (RoundStatus::Open, 2.0, 1.0)
+        );
+        let step = NewSpin {
+            round_id: Some(round_id),
+            ..spin("reels", &hash, p.id, 3.0)
+        };
+        for wrong in [
+            NewSpin {
+                player_id: other.id,
+                ..step.clone()
+            },
+            NewSpin {
+                game: DEFAULT_GAME.into(),
+                ..step.clone()
+            },
+        ] {
+            assert!(store.record_spin(&wrong).await.unwrap().is_none());
+        }
+        assert_eq!(
+            store.record_spin(&step).await.unwrap().unwrap().round_id,
+            Some(round_id)
+        );
+        assert!(store.record_spin(&step).await.unwrap().is_none());
+        let settled = store.round(round_id).await.unwrap().unwrap();
+        assert_eq!(
+            (settled.status, settled.payout),
+            (RoundStatus::Settled, 4.0)
+        );
+        assert!(settled.settled_at.is_some());
+        let open = RoundQuery {
+            game: Some("reels".into()),
+            status: Some(RoundStatus::Open),
+            ..Default::default()
+        };
+        assert!(store.rounds(&open, 10).await.unwrap().is_empty());
+        let mine = RoundQuery {
+            player_id: Some(p.id),
+            ..Default::default()
+        };
+        let ids: Vec<_> = store
+            .rounds(&mine, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec![round_id, single.round_id.unwrap()]);
+
+        let void = AdminChange::new("ops", "rounds.void");
+        let voided = store
+            .void_round(round_id, Utc::now(), "reel malfunction", &void)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(voided.status, RoundStatus::Voided);
+        assert_eq!(voided.void_reason.as_deref(), Some("reel malfunction"));
+        assert!(store
+            .void_round(round_id, Utc::now(), "again", &void)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(store
+            .void_round(round_id + 100, Utc::now(), "unknown", &void)
+            .await
+            .unwrap()
+            .is_none());
+        let audit = store.admin_audit(1).await.unwrap();
+        assert_eq!(audit[0].action, "rounds.void");
+        assert_eq!(audit[0].target, Some(format!("rounds/{round_id}")));
+        assert_eq!(audit[0].before.as_ref().unwrap()["status"], "settled");
+        assert_eq!(
+            audit[0].before.as_ref().unwrap()["void_reason"],
+            serde_json::Value::Null
+        );
+        assert_eq!(audit[0].after, Some(serde_json::to_value(&voided).unwrap()));
+        let ledger = store.ledger(p.id, 5).await.unwrap();
+        let movements: Vec<_> = ledger.iter().map(|e| (e.kind, e.amount)).collect();
+        assert_eq!(
+            movements,
+            vec![
+                (LedgerKind::Reversal, -4.0),
+                (LedgerKind::Refund, 2.0),
+                (LedgerKind::Payout, 3.0),
+                (LedgerKind::Payout, 1.0),
+                (LedgerKind::Wager, -2.0)
+            ]
+        );
+        assert!(ledger.iter().all(|e| e.round_id == Some(round_id)));
+        assert_eq!(ledger[0].spin_id, None);
+        verify_spin_chain(store, None).await.unwrap();
+
+        let report = round_report(store, "reels", &ReportQuery::default())
+            .await
+            .unwrap();
+        let totals = |rounds, wagered, paid| RoundTotals {
+            rounds,
+            wagered,
+            paid,
+        };
+        assert_eq!(report.settled, totals(1, 2.0, 5.0));
+        assert_eq!(report.voided, totals(1, 2.0, 4.0));
+        assert_eq!(report.open, RoundTotals::default());
+        assert_eq!(report.rtp, Some(2.5));
+        let later = ReportQuery {
+            from: Some(Utc::now() + Duration::hours(1)),
+            to: None,
+        };
+        let report = round_report(store, "reels", &later).await.unwrap();
+        assert_eq!((report.settled, report.rtp), (RoundTotals::default(), None));
+    }
+
+    /// A feature round keeps its game session in step with its spins.
+    async fn game_sessions(store: &dyn GameStore) {
+        let (hash, _) = store.active_config(DEFAULT_GAME).await.unwrap().unwrap();
+        let p = new_player(store, "gus").await;
+        let other = new_player(store, "gia").await;
+        let free_spins = |remaining: u32| serde_json::json!({"feature": "free_spins", "remaining": remaining, "played": 2 - remaining});
+        let opening = NewSpin {
+            settle: false,
+            session: Some(SessionStep {
+                cursor: 0,
+                action: None,
+                state: Some(free_spins(2)),
+            }),
+            ..spin(DEFAULT_GAME, &hash, p.id, 0.0)
+        };
+        let opened = store.record_spin(&opening).await.unwrap().unwrap();
+        assert_eq!((opened.cursor, opened.action.as_deref()), (None, None));
+        let feature_round = opened.round_id.unwrap();
+        let session = store.game_session(feature_round).await.unwrap().unwrap();
+        assert_eq!(
+            (
+                session.player_id,
+                session.nonce,
+                session.bet,
+                session.cursor
+            ),
+            (p.id, 1, 2.0, 1)
+        );
+        assert_eq!(session.config_hash, hash);
+        assert_eq!(session.state, Some(free_spins(2)));
+        assert_eq!(store.game_sessions(p.id).await.unwrap(), vec![session]);
+        assert!(store.game_sessions(other.id).await.unwrap().is_empty());
+        let step = |cursor: i64, remaining: u32| NewSpin {
+            round_id: Some(feature_round),
+            settle: remaining == 0,
+            session: Some(SessionStep {
+                cursor,
+                action: Some("spin".into()),
+                state: (remaining > 0).then(|| free_spins(remaining)),
+            }),
+            ..spin(DEFAULT_GAME, &hash, p.id, 1.5)
+        };
+        // a step the session is not at records nothing
+        let head = store.chain_head().await.unwrap();
+        assert!(store.record_spin(&step(2, 0)).await.unwrap().is_none());
+        assert_eq!(store.chain_head().await.unwrap(), head);
+        assert_eq!(
+            store.round(feature_round).await.unwrap().unwrap().payout,
+            0.0
+        );
+        let played = store.record_spin(&step(1, 1)).await.unwrap().unwrap();
+        assert_eq!(
+            (played.cursor, played.action.as_deref()),
+            (Some(1), Some("spin"))
+        );
+        assert!(store.record_spin(&step(1, 1)).await.unwrap().is_none());
+        store.record_spin(&step(2, 0)).await.unwrap().unwrap();
+        let over = store.game_session(feature_round).await.unwrap().unwrap();
+        assert_eq!((over.cursor, over.state), (3, None));
+        assert!(store.game_sessions(p.id).await.unwrap().is_empty());
+        assert!(store.record_spin(&step(3, 0)).await.unwrap().is_none());
+        let settled = store.round(feature_round).await.unwrap().unwrap();
+        assert_eq!(
+            (settled.status, settled.payout),
+            (RoundStatus::Settled, 3.0)
+        );
+        let in_round = SpinQuery {
+            round_id: Some(feature_round),
+            ..Default::default()
+        };
+        let cursors: Vec<_> = store
+            .spins(&in_round, None, 10)
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.cursor)
+            .collect();
+        assert_eq!(cursors, vec![Some(2), Some(1), None]);
+        // voiding a round ends its session
+        let voided_round = store
+            .record_spin(&opening)
+            .await
+            .unwrap()
+            .unwrap()
+            .round_id
+            .unwrap();
+        let void = AdminChange::new("ops", "rounds.void");
+        store
+            .void_round(voided_round, Utc::now(), "abandoned", &void)
+            .await
+            .unwrap()
+            .unwrap();
+        let ended = store.game_session(voided_round).await.unwrap().unwrap();
+        assert_eq!((ended.cursor, ended.state), (1, None));
+        assert!(store.game_sessions(p.id).await.unwrap().is_empty());
+        verify_spin_chain(store, None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_store_conforms() {
+        let store = MemoryStore::new();
+        seed_vault(&store).await;
+        players(&store).await;
+        sessions(&store).await;
+        idempotency_keys(&store).await;
+        games(&store).await;
+        seed_rotation(&store).await;
+        spin_history(&store).await;
+        rtp_alerts(&store).await;
+        spin_chain(&store).await;
+        merkle_roots(&store).await;
+        admin_keys(&store).await;
+        admin_signatures(&store).await;
+        rounds(&store).await;
+        game_sessions(&store).await;
+    }
+
+    fn chain_error(err: anyhow::Error) -> tigrinho_core::ChainError {
+        err.downcast().unwrap()
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_conforms() {
+        let store = SqliteStore::connect("sqlite::memory:", 1).await.unwrap();
+        store.migrate().await.unwrap();
+        seed_vault(&store).await;
+        players(&store).await;
+        sessions(&store).await;
+        idempotency_keys(&store).await;
+        games(&store).await;
+        seed_rotation(&store).await;
+        spin_history(&store).await;
+        rtp_alerts(&store).await;
+        spin_chain(&store).await;
+        merkle_roots(&store).await;
+        admin_keys(&store).await;
+        admin_signatures(&store).await;
+        rounds(&store).await;
+        game_sessions(&store).await;
+        for sql in ["UPDATE spins SET payout = 0", "DELETE FROM admin_audit"] {
+            let res = sqlx::query(sql).execute(store.pool()).await;
+            assert!(
+                res.unwrap_err().to_string().contains("append-only"),
+                "{sql}"
+            );
+        }
+        for sql in [
+            "UPDATE rounds SET status = 'open' WHERE status = 'voided'",
+            "UPDATE rounds SET payout = 0 WHERE status = 'settled'",
+            "DELETE FROM rounds",
+        ] {
+            let res = sqlx::query(sql).execute(store.pool()).await;
+            assert!(res.unwrap_err().to_string().contains("rounds"), "{sql}");
+        }
+        for sql in [
+            "UPDATE game_sessions SET state_json = '{}' WHERE state_json IS NULL",
+            "UPDATE game_sessions SET cursor = 0",
+            "DELETE FROM game_sessions",
+        ] {
+            let res = sqlx::query(sql).execute(store.pool()).await;
+            assert!(
+                res.unwrap_err().to_string().contains("game_sessions"),
+                "{sql}"
+            );
+        }
+
+        // a spin written around the store is never sealed, and the store will
+        // not start over it
+        sqlx::query(
+            "INSERT INTO spins (ts, client_seed, nonce, server_seed_hash, result_reels_json, payout) VALUES (?, 'old', 1, 'h', '[]', 0)",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(store.pool())
+        .await
+        .unwrap();
+        let err = store.migrate().await.unwrap_err();
+        assert!(err.to_string().contains("not sealed"), "{err}");
+
+        // whoever can drop the trigger can rewrite a row, but not unnoticed
+        sqlx::raw_sql("DROP TRIGGER spins_no_update; UPDATE spins SET payout = 99 WHERE id = 2")
+            .execute(store.pool())
+            .await
+            .unwrap();
+        let err = verify_spin_chain(&store, None).await.unwrap_err();
+        assert_eq!(chain_error(err), tigrinho_core::ChainError::Modified(2));
+    }
+
+    #[tokio::test]
+    async fn sqlite_keeps_a_single_game_paytable() {
+        let store = SqliteStore::connect("sqlite::memory:", 1).await.unwrap();
+        // a database from before games, whose operator tuned the paytable
+        sqlite::migrations_before(6)
+            .run(store.pool())
+            .await
+            .unwrap();
+        let paytable = r#"[{"symbol":0,"count":3,"payout_multiplier":7.5}]"#;
+        sqlx::query("UPDATE params SET rtp_target = 0.9, paytable_json = ? WHERE id = 1")
+            .bind(paytable)
+            .execute(store.pool())
+            .await
+            .unwrap();
+        store.migrate().await.unwrap();
+
+        let game = store.game("tigrinho").await.unwrap().unwrap();
+        let def = store
+            .config(game.active_config_hash.as_deref().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(def.rtp_target, 0.9);
+        assert_eq!(
+            def.paytable.0,
+            serde_json::from_str::<Vec<_>>(paytable).unwrap()
+        );
+        assert_eq!(def.reels, GameDefinition::default_game().reels);
+    }
+
+    #[tokio::test]
+    async fn sqlite_seals_spins_from_before_the_chain_once() {
+        let store = SqliteStore::connect("sqlite::memory:", 1).await.unwrap();
+        sqlite::migrations_before(9)
+            .run(store.pool())
+            .await
+            .unwrap();
+        for nonce in 1..=2 {
+            sqlx::query(
+                "INSERT INTO spins (ts, client_seed, nonce, server_seed_hash, result_reels_json, payout) VALUES (?, 'old', ?, 'h', '[]', 0)",
+            )
+            .bind(Utc::now().to_rfc3339())
+            .bind(nonce)
+            .execute(store.pool())
+            .await
+            .unwrap();
+        }
+        store.migrate().await.unwrap();
+        store.migrate().await.unwrap();
+        let head = verify_spin_chain(&store, None).await.unwrap();
+        assert_eq!((head.length, head.last_spin_id), (2, Some(2)));
+    }
+
+    /// Runs against the database in `TEST_POSTGRES_URL`, in a scratch schema
+    /// that is dropped and recreated each time; skipped when it is unset,
+    /// except in CI, which provides one.
+    #[tokio::test]
+    async fn pg_store_conforms() {
+        use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+        let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+            assert!(
+                std::env::var_os("CI").is_none(),
+                "TEST_POSTGRES_URL must be set in CI"
+            );
+            eprintln!("TEST_POSTGRES_URL not set, skipping");
+            return;
+        };
+        let schema = "tigrinho_conformance";
+        let admin = PgPoolOptions::new().connect(&url).await.unwrap();
+        sqlx::raw_sql(&format!(
+            "DROP SCHEMA IF EXISTS {schema} CASCADE; CREATE SCHEMA {schema}"
+        ))
+        .execute(&admin)
+        .await
+        .unwrap();
+        let opts = url
+            .parse::<PgConnectOptions>()
+            .unwrap()
+            .options([("search_path", schema)]);
+        let pool = PgPoolOptions::new().connect_with(opts).await.unwrap();
+        let store = PgStore::new(pool);
+        store.migrate().await.unwrap();
+        seed_vault(&store).await;
+        players(&store).await;
+        sessions(&store).await;
+        idempotency_keys(&store).await;
+        games(&store).await;
+        seed_rotation(&store).await;
+        spin_history(&store).await;
+        rtp_alerts(&store).await;
+        spin_chain(&store).await;
+        merkle_roots(&store).await;
+        admin_keys(&store).await;
+        admin_signatures(&store).await;
+        rounds(&store).await;
+        game_sessions(&store).await;
+        for sql in [
+            "UPDATE spins SET payout = 0",
+            "DELETE FROM spins",
+            "TRUNCATE spins CASCADE",
+            "UPDATE admin_audit SET actor = 'x'",
+        ] {
+            let res = sqlx::query(sql).execute(store.pool()).await;
+            assert!(
+                res.unwrap_err().to_string().contains("append-only"),
+                "{sql}"
+            );
+        }
+        for sql in [
+            "UPDATE rounds SET status = 'open' WHERE status = 'voided'",
+            "UPDATE rounds SET payout = 0 WHERE status = 'settled'",
+            "DELETE FROM rounds",
+        ] {
+            let res = sqlx::query(sql).execute(store.pool()).await;
+            assert!(res.unwrap_err().to_string().contains("rounds"), "{sql}");
+        }
+        for sql in [
+            "UPDATE game_sessions SET state_json = '{}' WHERE state_json IS NULL",
+            "UPDATE game_sessions SET cursor = 0",
+            "DELETE FROM game_sessions",
+        ] {
+            let res = sqlx::query(sql).execute(store.pool()).await;
+            assert!(
+                res.unwrap_err().to_string().contains("game_sessions"),
+                "{sql}"
+            );
+        }
+    }
+}
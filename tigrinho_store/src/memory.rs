@@ -0,0 +1,934 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{btree_map, hash_map::Entry, BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use tigrinho_core::{GameDefinition, GENESIS_HASH};
+use tigrinho_shared::{
+    AdminAuditEntry, ChainHead, MerkleRoot, PlayerInfo, RotationTrigger, Round, RoundQuery,
+    RoundStatus, RoundTotals, RtpAlert, SeedRotation, SpinLogEntry, SpinQuery,
+};
+
+use crate::{
+    generate_seed, matches, round_matches, seal_spin, spin_ledger, void_ledger, AdminChange,
+    AdminKey, GameRecord, GameSession, GameStore, IdempotencyRecord, LedgerEntry, LiveSession,
+    NewAdminAudit, NewAdminKey, NewAlert, NewGame, NewMerkleRoot, NewSession, NewSpin, SeedVault,
+    DEFAULT_GAME, DEV_SERVER_SEED,
+};
+
+#[derive(Default)]
+struct Data {
+    games: BTreeMap<String, GameRecord>,
+    configs: HashMap<String, GameDefinition>,
+    rotations: Vec<SeedRotation>,
+    spins: Vec<SpinLogEntry>,
+    ledger: Vec<LedgerEntry>,
+    rounds: Vec<Round>,
+    /// By round id.
+    game_sessions: BTreeMap<i64, GameSession>,
+    /// Players with their password hashes, id order.
+    players: Vec<(PlayerInfo, String)>,
+    /// By id, with whether each was revoked.
+    sessions: HashMap<String, (LiveSession, bool)>,
+    idempotency_keys: HashMap<(i64, String), (IdempotencyRecord, DateTime<Utc>)>,
+    alerts: Vec<RtpAlert>,
+    merkle_roots: Vec<MerkleRoot>,
+    admin_keys: Vec<AdminKey>,
+    /// Accepted request signatures by (key id, signature), with their expiry.
+    admin_signatures: HashMap<(String, String), DateTime<Utc>>,
+    admin_audit: Vec<AdminAuditEntry>,
+}
+
+impl Data {
+    /// Nothing can rewrite the log in memory, so its last spin is the head.
+    fn chain_head(&self) -> ChainHead {
+        ChainHead {
+            length: self.spins.len() as i64,
+            last_spin_id: self.spins.last().map(|s| s.id),
+            head_hash: self
+                .spins
+                .last()
+                .map_or(GENESIS_HASH.to_string(), |s| s.row_hash.clone()),
+        }
+    }
+
+    fn audit(&mut self, entry: &NewAdminAudit) -> AdminAuditEntry {
+        let recorded = AdminAuditEntry {
+            id: self.admin_audit.len() as i64 + 1,
+            ts: entry.ts,
+            actor: entry.actor.clone(),
+            action: entry.action.clone(),
+            target: entry.target.clone(),
+            before: entry.before.clone(),
+            after: entry.after.clone(),
+        };
+        self.admin_audit.push(recorded.clone());
+        recorded
+    }
+
+    /// Apply `edit` to a game, audited as `change`; `false` if there is no
+    /// such game.
+    fn change_game(
+        &mut self,
+        change: &AdminChange,
+        slug: &str,
+        edit: impl FnOnce(&mut GameRecord),
+    ) -> anyhow::Result<bool> {
+        let Some(game) = self.games.get_mut(slug) else {
+            return Ok(false);
+        };
+        let mut after = game.clone();
+        edit(&mut after);
+        // the entry is built first, so a failure leaves the game as it was
+        let entry = change.game_entry(Utc::now(), Some(game), &after)?;
+        *game = after;
+        self.audit(&entry);
+        Ok(true)
+    }
+}
+
+/// [`GameStore`] held in process memory, starting out like a freshly migrated
+/// database. Everything is lost when it is dropped.
+pub struct MemoryStore {
+    data: Mutex<Data>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        let def = GameDefinition::default_game();
+        let config_hash = def.config_hash();
+        let game = GameRecord {
+            slug: DEFAULT_GAME.into(),
+            name: "Tigrinho".into(),
+            sealed_seed: DEV_SERVER_SEED.into(),
+            server_seed_hash: tigrinho_core::derive_hash_hex(DEV_SERVER_SEED.as_bytes()),
+            seed_committed_at: Some(Utc::now()),
+            nonce: 0,
+            active_config_hash: Some(config_hash.clone()),
+            min_bet: 0.1,
+            max_bet: 100.0,
+            suspended_at: None,
+        };
+        let mut data = Data::default();
+        data.configs.insert(config_hash, def);
+        data.games.insert(game.slug.clone(), game);
+        Self {
+            data: Mutex::new(data),
+        }
+    }
+}
+
+#[async_trait]
+impl GameStore for MemoryStore {
+    async fn list_games(&self) -> anyhow::Result<Vec<GameRecord>> {
+        Ok(self.data.lock().unwrap().games.values().cloned().collect())
+    }
+
+    async fn game(&self, slug: &str) -> anyhow::Result<Option<GameRecord>> {
+        Ok(self.data.lock().unwrap().games.get(slug).cloned())
+    }
+
+    async fn create_game(
+        &self,
+        game: &NewGame,
+        vault: &SeedVault,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool> {
+        let mut data = self.data.lock().unwrap();
+        if data.games.contains_key(&game.slug) {
+            return Ok(false);
+        }
+        let seed = generate_seed();
+        let record = GameRecord {
+            slug: game.slug.clone(),
+            name: game.name.clone(),
+            sealed_seed: vault.seal(&game.slug, &seed),
+            server_seed_hash: tigrinho_core::derive_hash_hex(seed.as_bytes()),
+            seed_committed_at: Some(Utc::now()),
+            nonce: 0,
+            active_config_hash: Some(game.config_hash.clone()),
+            min_bet: game.min_bet,
+            max_bet: game.max_bet,
+            suspended_at: None,
+        };
+        let entry = change.game_entry(Utc::now(), None, &record)?;
+        data.games.insert(game.slug.clone(), record);
+        data.audit(&entry);
+        Ok(true)
+    }
+
+    async fn set_limits(
+        &self,
+        slug: &str,
+        min_bet: f64,
+        max_bet: f64,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool> {
+        self.data.lock().unwrap().change_game(change, slug, |game| {
+            game.min_bet = min_bet;
+            game.max_bet = max_bet;
+        })
+    }
+
+    async fn insert_config(&self, def: &GameDefinition) -> anyhow::Result<String> {
+        let hash = def.config_hash();
+        self.data
+            .lock()
+            .unwrap()
+            .configs
+            .entry(hash.clone())
+            .or_insert_with(|| def.clone());
+        Ok(hash)
+    }
+
+    async fn config(&self, hash: &str) -> anyhow::Result<Option<GameDefinition>> {
+        Ok(self.data.lock().unwrap().configs.get(hash).cloned())
+    }
+
+    async fn set_active_config(
+        &self,
+        slug: &str,
+        hash: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool> {
+        let mut data = self.data.lock().unwrap();
+        let activate = |game: &mut GameRecord| game.active_config_hash = Some(hash.to_string());
+        match change {
+            Some(change) => data.change_game(change, slug, activate),
+            None => Ok(data.games.get_mut(slug).map(activate).is_some()),
+        }
+    }
+
+    async fn reserve_nonce(&self, slug: &str) -> anyhow::Result<Option<GameRecord>> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data.games.get_mut(slug).map(|game| {
+            game.nonce += 1;
+            game.clone()
+        }))
+    }
+
+    async fn rotate_seed(
+        &self,
+        slug: &str,
+        trigger: RotationTrigger,
+        only_if_hash: Option<&str>,
+        vault: &SeedVault,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<Option<SeedRotation>> {
+        let mut data = self.data.lock().unwrap();
+        let id = data.rotations.len() as i64 + 1;
+        let Some(game) = data.games.get_mut(slug) else {
+            return Ok(None);
+        };
+        if only_if_hash.is_some_and(|h| h != game.server_seed_hash) {
+            return Ok(None);
+        }
+        let revealed = vault.open(slug, &game.sealed_seed)?;
+        let new_seed = generate_seed();
+        let new_hash = tigrinho_core::derive_hash_hex(new_seed.as_bytes());
+        let now = Utc::now();
+        let mut after = game.clone();
+        after.sealed_seed = vault.seal(slug, &new_seed);
+        let rotation = SeedRotation {
+            id,
+            game: slug.to_string(),
+            ts: now,
+            trigger,
+            previous_seed_hash: std::mem::replace(&mut after.server_seed_hash, new_hash.clone()),
+            revealed_seed: revealed,
+            final_nonce: std::mem::take(&mut after.nonce),
+            new_seed_hash: new_hash,
+        };
+        after.seed_committed_at = Some(now);
+        let entry = change
+            .map(|change| change.game_entry(now, Some(game), &after))
+            .transpose()?;
+        *game = after;
+        data.rotations.push(rotation.clone());
+        if let Some(entry) = entry {
+            data.audit(&entry);
+        }
+        Ok(Some(rotation))
+    }
+
+    async fn replace_sealed_seed(
+        &self,
+        slug: &str,
+        current: &str,
+        sealed: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool> {
+        let mut data = self.data.lock().unwrap();
+        match data.games.get_mut(slug) {
+            Some(game) if game.sealed_seed == current => {
+                game.sealed_seed = sealed.to_string();
+                if let Some(change) = change {
+                    data.audit(&change.reseal_entry(slug, current, sealed));
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn seed_rotations(&self, slug: &str, limit: u32) -> anyhow::Result<Vec<SeedRotation>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .rotations
+            .iter()
+            .rev()
+            .filter(|r| r.game == slug)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn seed_rotation_of(
+        &self,
+        slug: &str,
+        seed_hash: &str,
+    ) -> anyhow::Result<Option<SeedRotation>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .rotations
+            .iter()
+            .find(|r| r.game == slug && r.previous_seed_hash == seed_hash)
+            .cloned())
+    }
+
+    async fn record_spin(&self, spin: &NewSpin) -> anyhow::Result<Option<SpinLogEntry>> {
+        let mut data = self.data.lock().unwrap();
+        // check everything before changing anything
+        if let Some(id) = spin.round_id {
+            let open = data.rounds.get(id as usize - 1).is_some_and(|r| {
+                r.status == RoundStatus::Open
+                    && r.game == spin.game
+                    && r.player_id == spin.player_id
+            });
+            let at_step = spin.session.as_ref().is_none_or(|step| {
+                data.game_sessions
+                    .get(&id)
+                    .is_some_and(|s| s.cursor == step.cursor && s.state.is_some())
+            });
+            if !open || !at_step {
+                return Ok(None);
+            }
+        } else if let Some(step) = &spin.session {
+            anyhow::ensure!(step.cursor == 0, "a spin opening a round plays step 0");
+        }
+        let round_id = match spin.round_id {
+            Some(id) => id,
+            None => {
+                let id = data.rounds.len() as i64 + 1;
+                data.rounds.push(Round {
+                    id,
+                    game: spin.game.clone(),
+                    player_id: spin.player_id,
+                    bet: spin.bet,
+                    payout: 0.0,
+                    status: RoundStatus::Open,
+                    opened_at: spin.ts,
+                    settled_at: None,
+                    voided_at: None,
+                    void_reason: None,
+                });
+                id
+            }
+        };
+        let head = data.chain_head();
+        let mut entry = SpinLogEntry {
+            id: data.spins.len() as i64 + 1,
+            ts: spin.ts,
+            game: spin.game.clone(),
+            player_id: Some(spin.player_id),
+            client_seed: spin.client_seed.clone(),
+            nonce: spin.nonce,
+            server_seed_hash: spin.server_seed_hash.clone(),
+            config_hash: Some(spin.config_hash.clone()),
+            result_reels: spin.result_reels.clone(),
+            payout: spin.payout,
+            prev_hash: String::new(),
+            row_hash: String::new(),
+            round_id: Some(round_id),
+            cursor: spin.step().0,
+            action: spin.step().1.map(str::to_string),
+        };
+        seal_spin(&mut entry, &head);
+        for (kind, amount) in spin_ledger(spin) {
+            let id = data.ledger.len() as i64 + 1;
+            data.ledger.push(LedgerEntry {
+                id,
+                ts: spin.ts,
+                player_id: spin.player_id,
+                game: spin.game.clone(),
+                spin_id: Some(entry.id),
+                round_id: Some(round_id),
+                kind,
+                amount,
+            });
+        }
+        let round = &mut data.rounds[round_id as usize - 1];
+        round.payout += spin.payout;
+        if spin.settle {
+            round.status = RoundStatus::Settled;
+            round.settled_at = Some(spin.ts);
+        }
+        if let Some(step) = &spin.session {
+            match data.game_sessions.entry(round_id) {
+                btree_map::Entry::Occupied(mut session) => {
+                    let session = session.get_mut();
+                    session.cursor += 1;
+                    session.state = step.state.clone();
+                    session.updated_at = spin.ts;
+                }
+                btree_map::Entry::Vacant(vacant) => {
+                    vacant.insert(GameSession {
+                        round_id,
+                        game: spin.game.clone(),
+                        player_id: spin.player_id,
+                        client_seed: spin.client_seed.clone(),
+                        nonce: spin.nonce,
+                        server_seed_hash: spin.server_seed_hash.clone(),
+                        config_hash: spin.config_hash.clone(),
+                        bet: spin.bet,
+                        cursor: 1,
+                        state: step.state.clone(),
+                        created_at: spin.ts,
+                        updated_at: spin.ts,
+                    });
+                }
+            }
+        }
+        if let Some(key) = &spin.idempotency_key {
+            if let Some((record, _)) = data
+                .idempotency_keys
+                .get_mut(&(spin.player_id, key.clone()))
+            {
+                record.spin_id = Some(entry.id);
+            }
+        }
+        data.spins.push(entry.clone());
+        Ok(Some(entry))
+    }
+
+    async fn game_session(&self, round_id: i64) -> anyhow::Result<Option<GameSession>> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .game_sessions
+            .get(&round_id)
+            .cloned())
+    }
+
+    async fn game_sessions(&self, player_id: i64) -> anyhow::Result<Vec<GameSession>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .game_sessions
+            .values()
+            .filter(|s| s.player_id == player_id && s.state.is_some())
+            .cloned()
+            .collect())
+    }
+
+    async fn spins(
+        &self,
+        q: &SpinQuery,
+        player_id: Option<i64>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<SpinLogEntry>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .spins
+            .iter()
+            .rev()
+            .filter(|s| matches(q, player_id, s))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn spin(&self, id: i64) -> anyhow::Result<Option<SpinLogEntry>> {
+        let data = self.data.lock().unwrap();
+        Ok(data.spins.iter().find(|s| s.id == id).cloned())
+    }
+
+    async fn spins_after(&self, after_id: i64, limit: u32) -> anyhow::Result<Vec<SpinLogEntry>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .spins
+            .iter()
+            .filter(|s| s.id > after_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn chain_head(&self) -> anyhow::Result<ChainHead> {
+        Ok(self.data.lock().unwrap().chain_head())
+    }
+
+    async fn row_hashes(
+        &self,
+        after_id: i64,
+        through_id: i64,
+    ) -> anyhow::Result<Vec<(i64, String)>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .spins
+            .iter()
+            .filter(|s| s.id > after_id && s.id <= through_id)
+            .map(|s| (s.id, s.row_hash.clone()))
+            .collect())
+    }
+
+    async fn insert_merkle_root(&self, root: &NewMerkleRoot) -> anyhow::Result<MerkleRoot> {
+        let mut data = self.data.lock().unwrap();
+        if data
+            .merkle_roots
+            .iter()
+            .any(|r| r.first_spin_id == root.first_spin_id || r.last_spin_id == root.last_spin_id)
+        {
+            anyhow::bail!(
+                "a Merkle root already covers spin batch {}..={}",
+                root.first_spin_id,
+                root.last_spin_id
+            );
+        }
+        let recorded = MerkleRoot {
+            id: data.merkle_roots.len() as i64 + 1,
+            ts: root.ts,
+            first_spin_id: root.first_spin_id,
+            last_spin_id: root.last_spin_id,
+            leaf_count: root.leaf_count,
+            chain_length: root.chain_length,
+            root: root.root.clone(),
+        };
+        data.merkle_roots.push(recorded.clone());
+        Ok(recorded)
+    }
+
+    async fn merkle_roots(&self, limit: u32) -> anyhow::Result<Vec<MerkleRoot>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .merkle_roots
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn merkle_root_of(&self, spin_id: i64) -> anyhow::Result<Option<MerkleRoot>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .merkle_roots
+            .iter()
+            .find(|r| r.first_spin_id <= spin_id && spin_id <= r.last_spin_id)
+            .cloned())
+    }
+
+    async fn ledger(&self, player_id: i64, limit: u32) -> anyhow::Result<Vec<LedgerEntry>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .ledger
+            .iter()
+            .rev()
+            .filter(|e| e.player_id == player_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn round(&self, id: i64) -> anyhow::Result<Option<Round>> {
+        let data = self.data.lock().unwrap();
+        Ok(data.rounds.iter().find(|r| r.id == id).cloned())
+    }
+
+    async fn rounds(&self, q: &RoundQuery, limit: u32) -> anyhow::Result<Vec<Round>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .rounds
+            .iter()
+            .rev()
+            .filter(|r| round_matches(q, r))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn void_round(
+        &self,
+        id: i64,
+        at: DateTime<Utc>,
+        reason: &str,
+        change: &AdminChange,
+    ) -> anyhow::Result<Option<Round>> {
+        let mut data = self.data.lock().unwrap();
+        let Some(round) = data
+            .rounds
+            .iter_mut()
+            .find(|r| r.id == id && r.status != RoundStatus::Voided)
+        else {
+            return Ok(None);
+        };
+        let voided = Round {
+            status: RoundStatus::Voided,
+            voided_at: Some(at),
+            void_reason: Some(reason.to_string()),
+            ..round.clone()
+        };
+        let entry = change.void_entry(&voided)?;
+        *round = voided;
+        let round = round.clone();
+        if let Some(session) = data.game_sessions.get_mut(&id) {
+            if session.state.take().is_some() {
+                session.updated_at = at;
+            }
+        }
+        for (kind, amount) in void_ledger(&round) {
+            let id = data.ledger.len() as i64 + 1;
+            data.ledger.push(LedgerEntry {
+                id,
+                ts: at,
+                player_id: round.player_id,
+                game: round.game.clone(),
+                spin_id: None,
+                round_id: Some(round.id),
+                kind,
+                amount,
+            });
+        }
+        data.audit(&entry);
+        Ok(Some(round))
+    }
+
+    async fn round_totals(
+        &self,
+        game: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<(RoundStatus, RoundTotals)>> {
+        let data = self.data.lock().unwrap();
+        let mut totals: HashMap<RoundStatus, RoundTotals> = HashMap::new();
+        let rounds = data.rounds.iter().filter(|r| {
+            r.game == game
+                && from.is_none_or(|from| r.opened_at >= from)
+                && to.is_none_or(|to| r.opened_at < to)
+        });
+        for round in rounds {
+            let t = totals.entry(round.status).or_default();
+            t.rounds += 1;
+            t.wagered += round.bet;
+            t.paid += round.payout;
+        }
+        Ok(totals.into_iter().collect())
+    }
+
+    async fn create_player(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> anyhow::Result<Option<PlayerInfo>> {
+        let mut data = self.data.lock().unwrap();
+        if data.players.iter().any(|(p, _)| p.username == username) {
+            return Ok(None);
+        }
+        let player = PlayerInfo {
+            id: data.players.len() as i64 + 1,
+            username: username.to_string(),
+            created_at: Utc::now(),
+            client_seed: None,
+        };
+        data.players
+            .push((player.clone(), password_hash.to_string()));
+        Ok(Some(player))
+    }
+
+    async fn player(&self, id: i64) -> anyhow::Result<Option<PlayerInfo>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .players
+            .iter()
+            .find(|(p, _)| p.id == id)
+            .map(|(p, _)| p.clone()))
+    }
+
+    async fn set_client_seed(
+        &self,
+        player_id: i64,
+        client_seed: &str,
+    ) -> anyhow::Result<Option<PlayerInfo>> {
+        let mut data = self.data.lock().unwrap();
+        Ok(data
+            .players
+            .iter_mut()
+            .find(|(p, _)| p.id == player_id)
+            .map(|(p, _)| {
+                p.client_seed = Some(client_seed.to_string());
+                p.clone()
+            }))
+    }
+
+    async fn player_credentials(&self, username: &str) -> anyhow::Result<Option<(i64, String)>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .players
+            .iter()
+            .find(|(p, _)| p.username == username)
+            .map(|(p, hash)| (p.id, hash.clone())))
+    }
+
+    async fn create_session(&self, session: &NewSession) -> anyhow::Result<()> {
+        let live = LiveSession {
+            player_id: session.player_id,
+            expires_at: session.expires_at,
+        };
+        self.data
+            .lock()
+            .unwrap()
+            .sessions
+            .insert(session.id.clone(), (live, false));
+        Ok(())
+    }
+
+    async fn session(&self, id: &str) -> anyhow::Result<Option<LiveSession>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .sessions
+            .get(id)
+            .filter(|(_, revoked)| !revoked)
+            .map(|(s, _)| s.clone()))
+    }
+
+    async fn revoke_session(&self, id: &str) -> anyhow::Result<()> {
+        if let Some((_, revoked)) = self.data.lock().unwrap().sessions.get_mut(id) {
+            *revoked = true;
+        }
+        Ok(())
+    }
+
+    async fn revoke_player_sessions(&self, player_id: i64) -> anyhow::Result<()> {
+        for (s, revoked) in self.data.lock().unwrap().sessions.values_mut() {
+            if s.player_id == player_id {
+                *revoked = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn claim_idempotency_key(
+        &self,
+        player_id: i64,
+        key: &str,
+        request_hash: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<Option<IdempotencyRecord>> {
+        let now = Utc::now();
+        let mut data = self.data.lock().unwrap();
+        data.idempotency_keys
+            .retain(|_, (_, created_at)| *created_at >= now - ttl);
+        let slot = data.idempotency_keys.entry((player_id, key.to_string()));
+        match slot {
+            Entry::Occupied(e) => Ok(Some(e.get().0.clone())),
+            Entry::Vacant(e) => {
+                let record = IdempotencyRecord {
+                    request_hash: request_hash.to_string(),
+                    response_json: None,
+                    spin_id: None,
+                };
+                e.insert((record, now));
+                Ok(None)
+            }
+        }
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        player_id: i64,
+        key: &str,
+        response_json: &str,
+    ) -> anyhow::Result<()> {
+        let mut data = self.data.lock().unwrap();
+        if let Some((record, _)) = data.idempotency_keys.get_mut(&(player_id, key.to_string())) {
+            record.response_json = Some(response_json.to_string());
+        }
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, player_id: i64, key: &str) -> anyhow::Result<()> {
+        let mut data = self.data.lock().unwrap();
+        let id = (player_id, key.to_string());
+        if data
+            .idempotency_keys
+            .get(&id)
+            .is_some_and(|(record, _)| record.response_json.is_none() && record.spin_id.is_none())
+        {
+            data.idempotency_keys.remove(&id);
+        }
+        Ok(())
+    }
+
+    async fn raise_alert(&self, alert: &NewAlert, suspend: bool) -> anyhow::Result<RtpAlert> {
+        let mut data = self.data.lock().unwrap();
+        let now = Utc::now();
+        let recorded = RtpAlert {
+            id: data.alerts.len() as i64 + 1,
+            game: alert.game.clone(),
+            ts: now,
+            config_hash: alert.config_hash.clone(),
+            spins: alert.spins,
+            observed_rtp: alert.observed_rtp,
+            theoretical_rtp: alert.theoretical_rtp,
+            z_score: alert.z_score,
+            suspended: suspend,
+        };
+        if suspend {
+            if let Some(game) = data.games.get_mut(&alert.game) {
+                game.suspended_at.get_or_insert(now);
+            }
+        }
+        data.alerts.push(recorded.clone());
+        Ok(recorded)
+    }
+
+    async fn resume_game(&self, slug: &str, change: &AdminChange) -> anyhow::Result<bool> {
+        self.data
+            .lock()
+            .unwrap()
+            .change_game(change, slug, |game| game.suspended_at = None)
+    }
+
+    async fn rtp_alerts(&self, limit: u32) -> anyhow::Result<Vec<RtpAlert>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .alerts
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_admin_key(
+        &self,
+        key: &NewAdminKey,
+        change: &AdminChange,
+    ) -> anyhow::Result<Option<AdminKey>> {
+        let mut data = self.data.lock().unwrap();
+        if data
+            .admin_keys
+            .iter()
+            .any(|k| k.name == key.name || k.key_id == key.key_id)
+        {
+            return Ok(None);
+        }
+        let created = AdminKey {
+            id: data.admin_keys.len() as i64 + 1,
+            key_id: key.key_id.clone(),
+            name: key.name.clone(),
+            role: key.role,
+            secret_hash: key.secret_hash.clone(),
+            sealed_signing_key: key.sealed_signing_key.clone(),
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            revoked_at: None,
+        };
+        data.admin_keys.push(created.clone());
+        data.audit(&change.key_entry(key.created_at, None, &created));
+        Ok(Some(created))
+    }
+
+    async fn admin_key(&self, key_id: &str) -> anyhow::Result<Option<AdminKey>> {
+        let data = self.data.lock().unwrap();
+        Ok(data.admin_keys.iter().find(|k| k.key_id == key_id).cloned())
+    }
+
+    async fn admin_keys(&self) -> anyhow::Result<Vec<AdminKey>> {
+        Ok(self.data.lock().unwrap().admin_keys.clone())
+    }
+
+    async fn revoke_admin_key(
+        &self,
+        name: &str,
+        at: DateTime<Utc>,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool> {
+        let mut data = self.data.lock().unwrap();
+        let Some(key) = data
+            .admin_keys
+            .iter_mut()
+            .find(|k| k.name == name && k.revoked_at.is_none())
+        else {
+            return Ok(false);
+        };
+        let before = key.clone();
+        key.revoked_at = Some(at);
+        let entry = change.key_entry(at, Some(&before), key);
+        data.audit(&entry);
+        Ok(true)
+    }
+
+    async fn replace_sealed_signing_key(
+        &self,
+        key_id: &str,
+        current: &str,
+        sealed: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool> {
+        let mut data = self.data.lock().unwrap();
+        match data.admin_keys.iter_mut().find(|k| k.key_id == key_id) {
+            Some(key) if key.sealed_signing_key.as_deref() == Some(current) => {
+                key.sealed_signing_key = Some(sealed.to_string());
+                if let Some(change) = change {
+                    let entry = change.reseal_entry(&key.name, current, sealed);
+                    data.audit(&entry);
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn claim_admin_signature(
+        &self,
+        key_id: &str,
+        signature: &str,
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        let now = Utc::now();
+        let mut data = self.data.lock().unwrap();
+        data.admin_signatures.retain(|_, expires| *expires > now);
+        match data
+            .admin_signatures
+            .entry((key_id.to_string(), signature.to_string()))
+        {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(e) => {
+                e.insert(expires_at);
+                Ok(true)
+            }
+        }
+    }
+
+    async fn record_admin_action(&self, entry: &NewAdminAudit) -> anyhow::Result<AdminAuditEntry> {
+        Ok(self.data.lock().unwrap().audit(entry))
+    }
+
+    async fn admin_audit(&self, limit: u32) -> anyhow::Result<Vec<AdminAuditEntry>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .admin_audit
+            .iter()
+            .rev()
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
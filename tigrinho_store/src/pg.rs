@@ -0,0 +1,1356 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::postgres::{PgPoolOptions, PgRow};
+use sqlx::{PgPool, Postgres, QueryBuilder, Row, Transaction};
+
+use tigrinho_core::GameDefinition;
+use tigrinho_shared::{
+    AdminAuditEntry, AdminRole, ChainHead, MerkleRoot, PlayerInfo, RotationTrigger, Round,
+    RoundQuery, RoundStatus, RoundTotals, RtpAlert, SeedRotation, SpinLogEntry, SpinQuery,
+};
+
+use crate::{
+    ensure_active_configs, generate_seed, seal_spin, spin_ledger, void_ledger, AdminChange,
+    AdminKey, GameRecord, GameSession, GameStore, IdempotencyRecord, LedgerEntry, LedgerKind,
+    LiveSession, NewAdminAudit, NewAdminKey, NewAlert, NewGame, NewMerkleRoot, NewSession, NewSpin,
+    PoolStats, SeedVault,
+};
+
+const GAME_COLUMNS: &str = "slug, name, sealed_seed, server_seed_hash, seed_committed_at, nonce, active_config_hash, min_bet, max_bet, suspended_at";
+const SPIN_COLUMNS: &str = "id, ts, game, player_id, client_seed, nonce, server_seed_hash, config_hash, result_reels_json, payout, prev_hash, row_hash, round_id, cursor, action";
+
+/// [`GameStore`] over a PostgreSQL database. The schema lives in `migrations_pg/`.
+#[derive(Debug, Clone)]
+pub struct PgStore {
+    db: PgPool,
+}
+
+fn game_from_row(row: &PgRow) -> anyhow::Result<GameRecord> {
+    Ok(GameRecord {
+        slug: row.get("slug"),
+        name: row.get("name"),
+        sealed_seed: row.get("sealed_seed"),
+        server_seed_hash: row.get("server_seed_hash"),
+        seed_committed_at: row.get("seed_committed_at"),
+        nonce: row.get("nonce"),
+        active_config_hash: row.get("active_config_hash"),
+        min_bet: row.get("min_bet"),
+        max_bet: row.get("max_bet"),
+        suspended_at: row.get("suspended_at"),
+    })
+}
+
+fn rotation_from_row(row: &PgRow) -> anyhow::Result<SeedRotation> {
+    let trigger: String = row.get("trigger");
+    Ok(SeedRotation {
+        id: row.get("id"),
+        game: row.get("game"),
+        ts: row.get("ts"),
+        trigger: RotationTrigger::parse(&trigger)
+            .ok_or_else(|| anyhow::anyhow!("unknown rotation trigger {trigger}"))?,
+        previous_seed_hash: row.get("previous_seed_hash"),
+        revealed_seed: row.get("revealed_seed"),
+        final_nonce: row.get("final_nonce"),
+        new_seed_hash: row.get("new_seed_hash"),
+    })
+}
+
+fn spin_from_row(row: &PgRow) -> anyhow::Result<SpinLogEntry> {
+    Ok(SpinLogEntry {
+        id: row.get("id"),
+        ts: row.get("ts"),
+        game: row.get("game"),
+        player_id: row.get("player_id"),
+        client_seed: row.get("client_seed"),
+        nonce: row.get("nonce"),
+        server_seed_hash: row.get("server_seed_hash"),
+        config_hash: row.get("config_hash"),
+        result_reels: serde_json::from_str(row.get("result_reels_json"))?,
+        payout: row.get("payout"),
+        // NULL until sealed; a row is read unsealed only while sealing it
+        prev_hash: row
+            .get::<Option<String>, _>("prev_hash")
+            .unwrap_or_default(),
+        row_hash: row.get::<Option<String>, _>("row_hash").unwrap_or_default(),
+        round_id: row.get("round_id"),
+        cursor: row.get("cursor"),
+        action: row.get("action"),
+    })
+}
+
+fn chain_from_row(row: &PgRow) -> ChainHead {
+    ChainHead {
+        length: row.get("length"),
+        last_spin_id: row.get("last_spin_id"),
+        head_hash: row.get("head_hash"),
+    }
+}
+
+/// Lock the chain head until `tx` ends, so spins are chained in id order.
+async fn lock_chain_head(tx: &mut Transaction<'_, Postgres>) -> anyhow::Result<ChainHead> {
+    let row = sqlx::query("SELECT * FROM spin_chain WHERE id = 1 FOR UPDATE")
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(chain_from_row(&row))
+}
+
+/// Store `spin`'s link and advance the chain head past it, inside `tx`.
+async fn seal_in(
+    tx: &mut Transaction<'_, Postgres>,
+    spin: &mut SpinLogEntry,
+    head: &ChainHead,
+) -> anyhow::Result<ChainHead> {
+    let next = seal_spin(spin, head);
+    sqlx::query("UPDATE spins SET prev_hash = $1, row_hash = $2 WHERE id = $3")
+        .bind(&spin.prev_hash)
+        .bind(&spin.row_hash)
+        .bind(spin.id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        "UPDATE spin_chain SET length = $1, last_spin_id = $2, head_hash = $3 WHERE id = 1",
+    )
+    .bind(next.length)
+    .bind(next.last_spin_id)
+    .bind(&next.head_hash)
+    .execute(&mut **tx)
+    .await?;
+    Ok(next)
+}
+
+/// Lock a game's row until `tx` ends and read it, so that no other writer
+/// changes it in between.
+async fn lock_game(
+    tx: &mut Transaction<'_, Postgres>,
+    slug: &str,
+) -> anyhow::Result<Option<GameRecord>> {
+    sqlx::query(&format!(
+        "SELECT {GAME_COLUMNS} FROM games WHERE slug = $1 FOR UPDATE"
+    ))
+    .bind(slug)
+    .fetch_optional(&mut **tx)
+    .await?
+    .as_ref()
+    .map(game_from_row)
+    .transpose()
+}
+
+/// Append an entry to the admin audit trail inside `tx`.
+async fn audit_in(
+    tx: &mut Transaction<'_, Postgres>,
+    entry: &NewAdminAudit,
+) -> anyhow::Result<AdminAuditEntry> {
+    let row = sqlx::query(
+        "INSERT INTO admin_audit (ts, actor, action, target, before_json, after_json) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+    )
+    .bind(entry.ts)
+    .bind(&entry.actor)
+    .bind(&entry.action)
+    .bind(&entry.target)
+    .bind(entry.before.as_ref().map(|v| v.to_string()))
+    .bind(entry.after.as_ref().map(|v| v.to_string()))
+    .fetch_one(&mut **tx)
+    .await?;
+    admin_audit_from_row(&row)
+}
+
+/// Audit `change` to a game inside `tx`, against the game as it is now.
+async fn audit_game_in(
+    tx: &mut Transaction<'_, Postgres>,
+    change: &AdminChange,
+    ts: DateTime<Utc>,
+    before: Option<&GameRecord>,
+    slug: &str,
+) -> anyhow::Result<()> {
+    let after = lock_game(tx, slug)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("no such game: {slug}"))?;
+    audit_in(tx, &change.game_entry(ts, before, &after)?).await?;
+    Ok(())
+}
+
+fn ledger_from_row(row: &PgRow) -> anyhow::Result<LedgerEntry> {
+    let kind: String = row.get("kind");
+    Ok(LedgerEntry {
+        id: row.get("id"),
+        ts: row.get("ts"),
+        player_id: row.get("player_id"),
+        game: row.get("game"),
+        spin_id: row.get("spin_id"),
+        round_id: row.get("round_id"),
+        kind: LedgerKind::parse(&kind)
+            .ok_or_else(|| anyhow::anyhow!("unknown ledger kind {kind}"))?,
+        amount: row.get("amount"),
+    })
+}
+
+fn round_from_row(row: &PgRow) -> anyhow::Result<Round> {
+    let status: String = row.get("status");
+    Ok(Round {
+        id: row.get("id"),
+        game: row.get("game"),
+        player_id: row.get("player_id"),
+        bet: row.get("bet"),
+        payout: row.get("payout"),
+        status: RoundStatus::parse(&status)
+            .ok_or_else(|| anyhow::anyhow!("unknown round status {status}"))?,
+        opened_at: row.get("opened_at"),
+        settled_at: row.get("settled_at"),
+        voided_at: row.get("voided_at"),
+        void_reason: row.get("void_reason"),
+    })
+}
+
+fn game_session_from_row(row: &PgRow) -> anyhow::Result<GameSession> {
+    Ok(GameSession {
+        round_id: row.get("round_id"),
+        game: row.get("game"),
+        player_id: row.get("player_id"),
+        client_seed: row.get("client_seed"),
+        nonce: row.get("nonce"),
+        server_seed_hash: row.get("server_seed_hash"),
+        config_hash: row.get("config_hash"),
+        bet: row.get("bet"),
+        cursor: row.get("cursor"),
+        state: row
+            .get::<Option<String>, _>("state_json")
+            .map(|s| serde_json::from_str(&s))
+            .transpose()?,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+fn player_from_row(row: &PgRow) -> PlayerInfo {
+    PlayerInfo {
+        id: row.get("id"),
+        username: row.get("username"),
+        created_at: row.get("created_at"),
+        client_seed: row.get("client_seed"),
+    }
+}
+
+fn admin_key_from_row(row: &PgRow) -> anyhow::Result<AdminKey> {
+    let role: String = row.get("role");
+    Ok(AdminKey {
+        id: row.get("id"),
+        key_id: row.get("key_id"),
+        name: row.get("name"),
+        role: AdminRole::parse(&role)
+            .ok_or_else(|| anyhow::anyhow!("unknown admin role {role}"))?,
+        secret_hash: row.get("secret_hash"),
+        sealed_signing_key: row.get("sealed_signing_key"),
+        created_at: row.get("created_at"),
+        expires_at: row.get("expires_at"),
+        revoked_at: row.get("revoked_at"),
+    })
+}
+
+fn snapshot(json: Option<String>) -> anyhow::Result<Option<serde_json::Value>> {
+    Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+}
+
+fn admin_audit_from_row(row: &PgRow) -> anyhow::Result<AdminAuditEntry> {
+    Ok(AdminAuditEntry {
+        id: row.get("id"),
+        ts: row.get("ts"),
+        actor: row.get("actor"),
+        action: row.get("action"),
+        target: row.get("target"),
+        before: snapshot(row.get("before_json"))?,
+        after: snapshot(row.get("after_json"))?,
+    })
+}
+
+fn alert_from_row(row: &PgRow) -> RtpAlert {
+    RtpAlert {
+        id: row.get("id"),
+        game: row.get("game"),
+        ts: row.get("ts"),
+        config_hash: row.get("config_hash"),
+        spins: row.get("spins"),
+        observed_rtp: row.get("observed_rtp"),
+        theoretical_rtp: row.get("theoretical_rtp"),
+        z_score: row.get("z_score"),
+        suspended: row.get("suspended"),
+    }
+}
+
+fn merkle_root_from_row(row: &PgRow) -> MerkleRoot {
+    MerkleRoot {
+        id: row.get("id"),
+        ts: row.get("ts"),
+        first_spin_id: row.get("first_spin_id"),
+        last_spin_id: row.get("last_spin_id"),
+        leaf_count: row.get("leaf_count"),
+        chain_length: row.get("chain_length"),
+        root: row.get("root"),
+    }
+}
+
+impl PgStore {
+    pub fn new(db: PgPool) -> Self {
+        Self { db }
+    }
+
+    pub async fn connect(url: &str, max_connections: u32) -> anyhow::Result<Self> {
+        let db = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await?;
+        Ok(Self::new(db))
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.db
+    }
+
+    /// Chain the spins recorded before the log was hash-chained, in id order,
+    /// once; after that every spin is sealed as it is inserted, and an
+    /// unsealed one means the log was written behind the store's back.
+    async fn seal_spin_chain(&self) -> anyhow::Result<()> {
+        let mut tx = self.db.begin().await?;
+        let through: Option<i64> =
+            sqlx::query_scalar("SELECT backfill_through FROM spin_chain WHERE id = 1 FOR UPDATE")
+                .fetch_one(&mut *tx)
+                .await?;
+        if let Some(through) = through {
+            let mut head = lock_chain_head(&mut tx).await?;
+            loop {
+                let rows = sqlx::query(&format!(
+                    "SELECT {SPIN_COLUMNS} FROM spins WHERE row_hash IS NULL AND id <= $1 ORDER BY id LIMIT 1000"
+                ))
+                .bind(through)
+                .fetch_all(&mut *tx)
+                .await?;
+                if rows.is_empty() {
+                    break;
+                }
+                for row in &rows {
+                    head = seal_in(&mut tx, &mut spin_from_row(row)?, &head).await?;
+                }
+            }
+            sqlx::query("UPDATE spin_chain SET backfill_through = NULL WHERE id = 1")
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        let unsealed: Option<i64> =
+            sqlx::query_scalar("SELECT MIN(id) FROM spins WHERE row_hash IS NULL")
+                .fetch_one(&self.db)
+                .await?;
+        if let Some(id) = unsealed {
+            anyhow::bail!("spin {id} is not sealed into the spin chain");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GameStore for PgStore {
+    async fn migrate(&self) -> anyhow::Result<()> {
+        sqlx::migrate!("./migrations_pg").run(&self.db).await?;
+        ensure_active_configs(self).await?;
+        self.seal_spin_chain().await
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats {
+            size: self.db.size(),
+            idle: self.db.num_idle(),
+            max: self.db.options().get_max_connections(),
+        })
+    }
+
+    async fn list_games(&self) -> anyhow::Result<Vec<GameRecord>> {
+        sqlx::query(&format!("SELECT {GAME_COLUMNS} FROM games ORDER BY slug"))
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(game_from_row)
+            .collect()
+    }
+
+    async fn game(&self, slug: &str) -> anyhow::Result<Option<GameRecord>> {
+        sqlx::query(&format!("SELECT {GAME_COLUMNS} FROM games WHERE slug = $1"))
+            .bind(slug)
+            .fetch_optional(&self.db)
+            .await?
+            .as_ref()
+            .map(game_from_row)
+            .transpose()
+    }
+
+    async fn create_game(
+        &self,
+        game: &NewGame,
+        vault: &SeedVault,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool> {
+        let seed = generate_seed();
+        let now = Utc::now();
+        let mut tx = self.db.begin().await?;
+        let done = sqlx::query(
+            "INSERT INTO games (slug, name, created_at, sealed_seed, server_seed_hash, seed_committed_at, nonce, active_config_hash, min_bet, max_bet) VALUES ($1, $2, $3, $4, $5, $3, 0, $6, $7, $8) ON CONFLICT (slug) DO NOTHING",
+        )
+        .bind(&game.slug)
+        .bind(&game.name)
+        .bind(now)
+        .bind(vault.seal(&game.slug, &seed))
+        .bind(tigrinho_core::derive_hash_hex(seed.as_bytes()))
+        .bind(&game.config_hash)
+        .bind(game.min_bet)
+        .bind(game.max_bet)
+        .execute(&mut *tx)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Ok(false);
+        }
+        audit_game_in(&mut tx, change, now, None, &game.slug).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn set_limits(
+        &self,
+        slug: &str,
+        min_bet: f64,
+        max_bet: f64,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let Some(before) = lock_game(&mut tx, slug).await? else {
+            return Ok(false);
+        };
+        sqlx::query("UPDATE games SET min_bet = $1, max_bet = $2 WHERE slug = $3")
+            .bind(min_bet)
+            .bind(max_bet)
+            .bind(slug)
+            .execute(&mut *tx)
+            .await?;
+        audit_game_in(&mut tx, change, Utc::now(), Some(&before), slug).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn insert_config(&self, def: &GameDefinition) -> anyhow::Result<String> {
+        let hash = def.config_hash();
+        sqlx::query(
+            "INSERT INTO game_configs (config_hash, definition_json, created_at) VALUES ($1, $2, $3) ON CONFLICT (config_hash) DO NOTHING",
+        )
+        .bind(&hash)
+        .bind(def.canonical_json())
+        .bind(Utc::now())
+        .execute(&self.db)
+        .await?;
+        Ok(hash)
+    }
+
+    async fn config(&self, hash: &str) -> anyhow::Result<Option<GameDefinition>> {
+        let row = sqlx::query("SELECT definition_json FROM game_configs WHERE config_hash = $1")
+            .bind(hash)
+            .fetch_optional(&self.db)
+            .await?;
+        match row {
+            Some(row) => {
+                let def: GameDefinition = serde_json::from_str(row.get("definition_json"))?;
+                // refuse to serve a row whose content no longer matches its key
+                anyhow::ensure!(
+                    def.config_hash() == hash,
+                    "config {hash} fails its hash check"
+                );
+                Ok(Some(def))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn set_active_config(
+        &self,
+        slug: &str,
+        hash: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let Some(before) = lock_game(&mut tx, slug).await? else {
+            return Ok(false);
+        };
+        sqlx::query("UPDATE games SET active_config_hash = $1 WHERE slug = $2")
+            .bind(hash)
+            .bind(slug)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(change) = change {
+            audit_game_in(&mut tx, change, Utc::now(), Some(&before), slug).await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn reserve_nonce(&self, slug: &str) -> anyhow::Result<Option<GameRecord>> {
+        sqlx::query(&format!(
+            "UPDATE games SET nonce = nonce + 1 WHERE slug = $1 RETURNING {GAME_COLUMNS}"
+        ))
+        .bind(slug)
+        .fetch_optional(&self.db)
+        .await?
+        .as_ref()
+        .map(game_from_row)
+        .transpose()
+    }
+
+    async fn rotate_seed(
+        &self,
+        slug: &str,
+        trigger: RotationTrigger,
+        only_if_hash: Option<&str>,
+        vault: &SeedVault,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<Option<SeedRotation>> {
+        let mut tx = self.db.begin().await?;
+        // the row lock keeps spins from reserving a nonce under the old seed
+        // between reading it and replacing it
+        let Some(old) = lock_game(&mut tx, slug).await? else {
+            return Ok(None);
+        };
+        if only_if_hash.is_some_and(|h| h != old.server_seed_hash) {
+            return Ok(None);
+        }
+        let revealed = vault.open(slug, &old.sealed_seed)?;
+
+        let new_seed = generate_seed();
+        let new_hash = tigrinho_core::derive_hash_hex(new_seed.as_bytes());
+        let now = Utc::now();
+        sqlx::query(
+            "UPDATE games SET sealed_seed = $1, server_seed_hash = $2, nonce = 0, seed_committed_at = $3 WHERE slug = $4",
+        )
+        .bind(vault.seal(slug, &new_seed))
+        .bind(&new_hash)
+        .bind(now)
+        .bind(slug)
+        .execute(&mut *tx)
+        .await?;
+        let row = sqlx::query(
+            "INSERT INTO seed_rotations (game, ts, trigger, previous_seed_hash, revealed_seed, final_nonce, new_seed_hash) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(slug)
+        .bind(now)
+        .bind(trigger.as_str())
+        .bind(&old.server_seed_hash)
+        .bind(&revealed)
+        .bind(old.nonce)
+        .bind(&new_hash)
+        .fetch_one(&mut *tx)
+        .await?;
+        let rotation = rotation_from_row(&row)?;
+        if let Some(change) = change {
+            audit_game_in(&mut tx, change, now, Some(&old), slug).await?;
+        }
+        tx.commit().await?;
+        Ok(Some(rotation))
+    }
+
+    async fn replace_sealed_seed(
+        &self,
+        slug: &str,
+        current: &str,
+        sealed: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let done =
+            sqlx::query("UPDATE games SET sealed_seed = $1 WHERE slug = $2 AND sealed_seed = $3")
+                .bind(sealed)
+                .bind(slug)
+                .bind(current)
+                .execute(&mut *tx)
+                .await?;
+        if done.rows_affected() == 0 {
+            return Ok(false);
+        }
+        if let Some(change) = change {
+            audit_in(&mut tx, &change.reseal_entry(slug, current, sealed)).await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn seed_rotations(&self, slug: &str, limit: u32) -> anyhow::Result<Vec<SeedRotation>> {
+        sqlx::query("SELECT * FROM seed_rotations WHERE game = $1 ORDER BY id DESC LIMIT $2")
+            .bind(slug)
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(rotation_from_row)
+            .collect()
+    }
+
+    async fn seed_rotation_of(
+        &self,
+        slug: &str,
+        seed_hash: &str,
+    ) -> anyhow::Result<Option<SeedRotation>> {
+        sqlx::query("SELECT * FROM seed_rotations WHERE game = $1 AND previous_seed_hash = $2")
+            .bind(slug)
+            .bind(seed_hash)
+            .fetch_optional(&self.db)
+            .await?
+            .as_ref()
+            .map(rotation_from_row)
+            .transpose()
+    }
+
+    async fn record_spin(&self, spin: &NewSpin) -> anyhow::Result<Option<SpinLogEntry>> {
+        let mut tx = self.db.begin().await?;
+        let head = lock_chain_head(&mut tx).await?;
+        let status = if spin.settle {
+            RoundStatus::Settled
+        } else {
+            RoundStatus::Open
+        };
+        let settled_at = spin.settle.then_some(spin.ts);
+        let round_id: i64 = match spin.round_id {
+            Some(id) => {
+                let continued = sqlx::query(
+                    "UPDATE rounds SET payout = payout + $1, status = $2, settled_at = $3 WHERE id = $4 AND status = 'open' AND game = $5 AND player_id = $6",
+                )
+                .bind(spin.payout)
+                .bind(status.as_str())
+                .bind(settled_at)
+                .bind(id)
+                .bind(&spin.game)
+                .bind(spin.player_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                    == 1;
+                if !continued {
+                    return Ok(None);
+                }
+                id
+            }
+            None => {
+                sqlx::query_scalar(
+                    "INSERT INTO rounds (game, player_id, bet, payout, status, opened_at, settled_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+                )
+                .bind(&spin.game)
+                .bind(spin.player_id)
+                .bind(spin.bet)
+                .bind(spin.payout)
+                .bind(status.as_str())
+                .bind(spin.ts)
+                .bind(settled_at)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
+        if let Some(step) = &spin.session {
+            let state_json = step.state.as_ref().map(|s| s.to_string());
+            if spin.round_id.is_none() {
+                anyhow::ensure!(step.cursor == 0, "a spin opening a round plays step 0");
+                sqlx::query(
+                    "INSERT INTO game_sessions (round_id, game, player_id, client_seed, nonce, server_seed_hash, config_hash, bet, cursor, state_json, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 1, $9, $10, $10)",
+                )
+                .bind(round_id)
+                .bind(&spin.game)
+                .bind(spin.player_id)
+                .bind(&spin.client_seed)
+                .bind(spin.nonce)
+                .bind(&spin.server_seed_hash)
+                .bind(&spin.config_hash)
+                .bind(spin.bet)
+                .bind(&state_json)
+                .bind(spin.ts)
+                .execute(&mut *tx)
+                .await?;
+            } else {
+                let advanced = sqlx::query(
+                    "UPDATE game_sessions SET cursor = cursor + 1, state_json = $1, updated_at = $2 WHERE round_id = $3 AND cursor = $4 AND state_json IS NOT NULL",
+                )
+                .bind(&state_json)
+                .bind(spin.ts)
+                .bind(round_id)
+                .bind(step.cursor)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+                    == 1;
+                if !advanced {
+                    return Ok(None);
+                }
+            }
+        }
+        let (cursor, action) = spin.step();
+        let row = sqlx::query(&format!(
+            "INSERT INTO spins (ts, client_seed, nonce, server_seed_hash, result_reels_json, payout, player_id, config_hash, game, round_id, cursor, action) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING {SPIN_COLUMNS}"
+        ))
+        .bind(spin.ts)
+        .bind(&spin.client_seed)
+        .bind(spin.nonce)
+        .bind(&spin.server_seed_hash)
+        .bind(serde_json::to_string(&spin.result_reels)?)
+        .bind(spin.payout)
+        .bind(spin.player_id)
+        .bind(&spin.config_hash)
+        .bind(&spin.game)
+        .bind(round_id)
+        .bind(cursor)
+        .bind(action)
+        .fetch_one(&mut *tx)
+        .await?;
+        let mut entry = spin_from_row(&row)?;
+        seal_in(&mut tx, &mut entry, &head).await?;
+        for (kind, amount) in spin_ledger(spin) {
+            sqlx::query(
+                "INSERT INTO ledger (ts, player_id, game, spin_id, round_id, kind, amount) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            )
+            .bind(spin.ts)
+            .bind(spin.player_id)
+            .bind(&spin.game)
+            .bind(entry.id)
+            .bind(round_id)
+            .bind(kind.as_str())
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+        if let Some(key) = &spin.idempotency_key {
+            sqlx::query(
+                "UPDATE idempotency_keys SET spin_id = $1 WHERE player_id = $2 AND key = $3",
+            )
+            .bind(entry.id)
+            .bind(spin.player_id)
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Some(entry))
+    }
+
+    async fn game_session(&self, round_id: i64) -> anyhow::Result<Option<GameSession>> {
+        sqlx::query("SELECT * FROM game_sessions WHERE round_id = $1")
+            .bind(round_id)
+            .fetch_optional(&self.db)
+            .await?
+            .as_ref()
+            .map(game_session_from_row)
+            .transpose()
+    }
+
+    async fn game_sessions(&self, player_id: i64) -> anyhow::Result<Vec<GameSession>> {
+        sqlx::query(
+            "SELECT * FROM game_sessions WHERE player_id = $1 AND state_json IS NOT NULL ORDER BY round_id",
+        )
+        .bind(player_id)
+        .fetch_all(&self.db)
+        .await?
+        .iter()
+        .map(game_session_from_row)
+        .collect()
+    }
+
+    async fn spins(
+        &self,
+        q: &SpinQuery,
+        player_id: Option<i64>,
+        limit: u32,
+    ) -> anyhow::Result<Vec<SpinLogEntry>> {
+        let mut qb: QueryBuilder<Postgres> =
+            QueryBuilder::new(format!("SELECT {SPIN_COLUMNS} FROM spins WHERE 1 = 1"));
+        if let Some(player_id) = player_id {
+            qb.push(" AND player_id = ").push_bind(player_id);
+        }
+        if let Some(cursor) = q.cursor {
+            qb.push(" AND id < ").push_bind(cursor);
+        }
+        if let Some(from) = q.from {
+            qb.push(" AND ts >= ").push_bind(from);
+        }
+        if let Some(to) = q.to {
+            qb.push(" AND ts < ").push_bind(to);
+        }
+        if let Some(game) = &q.game {
+            qb.push(" AND game = ").push_bind(game);
+        }
+        if let Some(client_seed) = &q.client_seed {
+            qb.push(" AND client_seed = ").push_bind(client_seed);
+        }
+        if let Some(hash) = &q.server_seed_hash {
+            qb.push(" AND server_seed_hash = ").push_bind(hash);
+        }
+        if let Some(min) = q.min_payout {
+            qb.push(" AND payout >= ").push_bind(min);
+        }
+        if let Some(max) = q.max_payout {
+            qb.push(" AND payout <= ").push_bind(max);
+        }
+        if let Some(round_id) = q.round_id {
+            qb.push(" AND round_id = ").push_bind(round_id);
+        }
+        qb.push(" ORDER BY id DESC LIMIT ").push_bind(limit as i64);
+        qb.build()
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(spin_from_row)
+            .collect()
+    }
+
+    async fn spin(&self, id: i64) -> anyhow::Result<Option<SpinLogEntry>> {
+        sqlx::query(&format!("SELECT {SPIN_COLUMNS} FROM spins WHERE id = $1"))
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .as_ref()
+            .map(spin_from_row)
+            .transpose()
+    }
+
+    async fn spins_after(&self, after_id: i64, limit: u32) -> anyhow::Result<Vec<SpinLogEntry>> {
+        sqlx::query(&format!(
+            "SELECT {SPIN_COLUMNS} FROM spins WHERE id > $1 ORDER BY id ASC LIMIT $2"
+        ))
+        .bind(after_id)
+        .bind(limit as i64)
+        .fetch_all(&self.db)
+        .await?
+        .iter()
+        .map(spin_from_row)
+        .collect()
+    }
+
+    async fn chain_head(&self) -> anyhow::Result<ChainHead> {
+        let row = sqlx::query("SELECT * FROM spin_chain WHERE id = 1")
+            .fetch_optional(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("the spin chain head is missing"))?;
+        Ok(chain_from_row(&row))
+    }
+
+    async fn row_hashes(
+        &self,
+        after_id: i64,
+        through_id: i64,
+    ) -> anyhow::Result<Vec<(i64, String)>> {
+        let rows =
+            sqlx::query("SELECT id, row_hash FROM spins WHERE id > $1 AND id <= $2 ORDER BY id")
+                .bind(after_id)
+                .bind(through_id)
+                .fetch_all(&self.db)
+                .await?;
+        Ok(rows
+            .iter()
+            .map(|r| (r.get("id"), r.get("row_hash")))
+            .collect())
+    }
+
+    async fn insert_merkle_root(&self, root: &NewMerkleRoot) -> anyhow::Result<MerkleRoot> {
+        let row = sqlx::query(
+            "INSERT INTO merkle_roots (ts, first_spin_id, last_spin_id, leaf_count, chain_length, root) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        )
+        .bind(root.ts)
+        .bind(root.first_spin_id)
+        .bind(root.last_spin_id)
+        .bind(root.leaf_count)
+        .bind(root.chain_length)
+        .bind(&root.root)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(merkle_root_from_row(&row))
+    }
+
+    async fn merkle_roots(&self, limit: u32) -> anyhow::Result<Vec<MerkleRoot>> {
+        let rows = sqlx::query("SELECT * FROM merkle_roots ORDER BY id DESC LIMIT $1")
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.iter().map(merkle_root_from_row).collect())
+    }
+
+    async fn merkle_root_of(&self, spin_id: i64) -> anyhow::Result<Option<MerkleRoot>> {
+        let row = sqlx::query(
+            "SELECT * FROM merkle_roots WHERE first_spin_id <= $1 AND last_spin_id >= $1",
+        )
+        .bind(spin_id)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.as_ref().map(merkle_root_from_row))
+    }
+
+    async fn ledger(&self, player_id: i64, limit: u32) -> anyhow::Result<Vec<LedgerEntry>> {
+        sqlx::query("SELECT * FROM ledger WHERE player_id = $1 ORDER BY id DESC LIMIT $2")
+            .bind(player_id)
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(ledger_from_row)
+            .collect()
+    }
+
+    async fn round(&self, id: i64) -> anyhow::Result<Option<Round>> {
+        sqlx::query("SELECT * FROM rounds WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.db)
+            .await?
+            .as_ref()
+            .map(round_from_row)
+            .transpose()
+    }
+
+    async fn rounds(&self, q: &RoundQuery, limit: u32) -> anyhow::Result<Vec<Round>> {
+        let mut qb: QueryBuilder<Postgres> = QueryBuilder::new("SELECT * FROM rounds WHERE 1 = 1");
+        if let Some(game) = &q.game {
+            qb.push(" AND game = ").push_bind(game);
+        }
+        if let Some(player_id) = q.player_id {
+            qb.push(" AND player_id = ").push_bind(player_id);
+        }
+        if let Some(status) = q.status {
+            qb.push(" AND status = ").push_bind(status.as_str());
+        }
+        qb.push(" ORDER BY id DESC LIMIT ").push_bind(limit as i64);
+        qb.build()
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(round_from_row)
+            .collect()
+    }
+
+    async fn void_round(
+        &self,
+        id: i64,
+        at: DateTime<Utc>,
+        reason: &str,
+        change: &AdminChange,
+    ) -> anyhow::Result<Option<Round>> {
+        let mut tx = self.db.begin().await?;
+        let Some(row) = sqlx::query(
+            "UPDATE rounds SET status = 'voided', voided_at = $1, void_reason = $2 WHERE id = $3 AND status <> 'voided' RETURNING *",
+        )
+        .bind(at)
+        .bind(reason)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let round = round_from_row(&row)?;
+        sqlx::query(
+            "UPDATE game_sessions SET state_json = NULL, updated_at = $1 WHERE round_id = $2 AND state_json IS NOT NULL",
+        )
+        .bind(at)
+        .bind(round.id)
+        .execute(&mut *tx)
+        .await?;
+        for (kind, amount) in void_ledger(&round) {
+            sqlx::query(
+                "INSERT INTO ledger (ts, player_id, game, spin_id, round_id, kind, amount) VALUES ($1, $2, $3, NULL, $4, $5, $6)",
+            )
+            .bind(at)
+            .bind(round.player_id)
+            .bind(&round.game)
+            .bind(round.id)
+            .bind(kind.as_str())
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+        audit_in(&mut tx, &change.void_entry(&round)?).await?;
+        tx.commit().await?;
+        Ok(Some(round))
+    }
+
+    async fn round_totals(
+        &self,
+        game: &str,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<(RoundStatus, RoundTotals)>> {
+        let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+            "SELECT status, COUNT(*) AS rounds, SUM(bet) AS wagered, SUM(payout) AS paid FROM rounds WHERE game = ",
+        );
+        qb.push_bind(game);
+        if let Some(from) = from {
+            qb.push(" AND opened_at >= ").push_bind(from);
+        }
+        if let Some(to) = to {
+            qb.push(" AND opened_at < ").push_bind(to);
+        }
+        qb.push(" GROUP BY status");
+        qb.build()
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(|row| {
+                let status: String = row.get("status");
+                let status = RoundStatus::parse(&status)
+                    .ok_or_else(|| anyhow::anyhow!("unknown round status {status}"))?;
+                let totals = RoundTotals {
+                    rounds: row.get("rounds"),
+                    wagered: row.get("wagered"),
+                    paid: row.get("paid"),
+                };
+                Ok((status, totals))
+            })
+            .collect()
+    }
+
+    async fn create_player(
+        &self,
+        username: &str,
+        password_hash: &str,
+    ) -> anyhow::Result<Option<PlayerInfo>> {
+        let row = sqlx::query(
+            "INSERT INTO players (username, password_hash, created_at) VALUES ($1, $2, $3) ON CONFLICT (username) DO NOTHING RETURNING id, username, created_at, client_seed",
+        )
+        .bind(username)
+        .bind(password_hash)
+        .bind(Utc::now())
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.as_ref().map(player_from_row))
+    }
+
+    async fn player(&self, id: i64) -> anyhow::Result<Option<PlayerInfo>> {
+        let row =
+            sqlx::query("SELECT id, username, created_at, client_seed FROM players WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.db)
+                .await?;
+        Ok(row.as_ref().map(player_from_row))
+    }
+
+    async fn set_client_seed(
+        &self,
+        player_id: i64,
+        client_seed: &str,
+    ) -> anyhow::Result<Option<PlayerInfo>> {
+        let row = sqlx::query(
+            "UPDATE players SET client_seed = $1 WHERE id = $2 RETURNING id, username, created_at, client_seed",
+        )
+        .bind(client_seed)
+        .bind(player_id)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.as_ref().map(player_from_row))
+    }
+
+    async fn player_credentials(&self, username: &str) -> anyhow::Result<Option<(i64, String)>> {
+        let row = sqlx::query("SELECT id, password_hash FROM players WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.db)
+            .await?;
+        Ok(row.map(|row| (row.get("id"), row.get("password_hash"))))
+    }
+
+    async fn create_session(&self, session: &NewSession) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO sessions (id, player_id, issued_at, expires_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(&session.id)
+        .bind(session.player_id)
+        .bind(session.issued_at)
+        .bind(session.expires_at)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn session(&self, id: &str) -> anyhow::Result<Option<LiveSession>> {
+        let row = sqlx::query(
+            "SELECT player_id, expires_at FROM sessions WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(row.map(|row| LiveSession {
+            player_id: row.get("player_id"),
+            expires_at: row.get("expires_at"),
+        }))
+    }
+
+    async fn revoke_session(&self, id: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE sessions SET revoked_at = $1 WHERE id = $2")
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn revoke_player_sessions(&self, player_id: i64) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET revoked_at = $1 WHERE player_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(Utc::now())
+        .bind(player_id)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn claim_idempotency_key(
+        &self,
+        player_id: i64,
+        key: &str,
+        request_hash: &str,
+        ttl: Duration,
+    ) -> anyhow::Result<Option<IdempotencyRecord>> {
+        let now = Utc::now();
+        sqlx::query("DELETE FROM idempotency_keys WHERE created_at < $1")
+            .bind(now - ttl)
+            .execute(&self.db)
+            .await?;
+        let inserted = sqlx::query(
+            "INSERT INTO idempotency_keys (player_id, key, request_hash, created_at) VALUES ($1, $2, $3, $4) ON CONFLICT (player_id, key) DO NOTHING",
+        )
+        .bind(player_id)
+        .bind(key)
+        .bind(request_hash)
+        .bind(now)
+        .execute(&self.db)
+        .await?;
+        if inserted.rows_affected() == 1 {
+            return Ok(None);
+        }
+        let row = sqlx::query(
+            "SELECT request_hash, response_json, spin_id FROM idempotency_keys WHERE player_id = $1 AND key = $2",
+        )
+        .bind(player_id)
+        .bind(key)
+        .fetch_one(&self.db)
+        .await?;
+        Ok(Some(IdempotencyRecord {
+            request_hash: row.get("request_hash"),
+            response_json: row.get("response_json"),
+            spin_id: row.get("spin_id"),
+        }))
+    }
+
+    async fn complete_idempotency_key(
+        &self,
+        player_id: i64,
+        key: &str,
+        response_json: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET response_json = $1 WHERE player_id = $2 AND key = $3",
+        )
+        .bind(response_json)
+        .bind(player_id)
+        .bind(key)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn release_idempotency_key(&self, player_id: i64, key: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            "DELETE FROM idempotency_keys WHERE player_id = $1 AND key = $2 AND response_json IS NULL AND spin_id IS NULL",
+        )
+        .bind(player_id)
+        .bind(key)
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn raise_alert(&self, alert: &NewAlert, suspend: bool) -> anyhow::Result<RtpAlert> {
+        let now = Utc::now();
+        let mut tx = self.db.begin().await?;
+        let row = sqlx::query(
+            "INSERT INTO rtp_alerts (game, ts, config_hash, spins, observed_rtp, theoretical_rtp, z_score, suspended) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *",
+        )
+        .bind(&alert.game)
+        .bind(now)
+        .bind(&alert.config_hash)
+        .bind(alert.spins)
+        .bind(alert.observed_rtp)
+        .bind(alert.theoretical_rtp)
+        .bind(alert.z_score)
+        .bind(suspend)
+        .fetch_one(&mut *tx)
+        .await?;
+        let recorded = alert_from_row(&row);
+        if suspend {
+            sqlx::query(
+                "UPDATE games SET suspended_at = $1, suspended_reason = $2 WHERE slug = $3 AND suspended_at IS NULL",
+            )
+            .bind(now)
+            .bind(format!("rtp alert {}", recorded.id))
+            .bind(&alert.game)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    async fn resume_game(&self, slug: &str, change: &AdminChange) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let Some(before) = lock_game(&mut tx, slug).await? else {
+            return Ok(false);
+        };
+        sqlx::query(
+            "UPDATE games SET suspended_at = NULL, suspended_reason = NULL WHERE slug = $1",
+        )
+        .bind(slug)
+        .execute(&mut *tx)
+        .await?;
+        audit_game_in(&mut tx, change, Utc::now(), Some(&before), slug).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn rtp_alerts(&self, limit: u32) -> anyhow::Result<Vec<RtpAlert>> {
+        let rows = sqlx::query("SELECT * FROM rtp_alerts ORDER BY id DESC LIMIT $1")
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?;
+        Ok(rows.iter().map(alert_from_row).collect())
+    }
+
+    async fn create_admin_key(
+        &self,
+        key: &NewAdminKey,
+        change: &AdminChange,
+    ) -> anyhow::Result<Option<AdminKey>> {
+        let mut tx = self.db.begin().await?;
+        // name and key_id are both unique; only a taken name is expected
+        let res = sqlx::query(
+            "INSERT INTO admin_keys (key_id, name, role, secret_hash, sealed_signing_key, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(&key.key_id)
+        .bind(&key.name)
+        .bind(key.role.as_str())
+        .bind(&key.secret_hash)
+        .bind(&key.sealed_signing_key)
+        .bind(key.created_at)
+        .bind(key.expires_at)
+        .fetch_one(&mut *tx)
+        .await;
+        let created = match res {
+            Ok(row) => admin_key_from_row(&row)?,
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        audit_in(&mut tx, &change.key_entry(key.created_at, None, &created)).await?;
+        tx.commit().await?;
+        Ok(Some(created))
+    }
+
+    async fn admin_key(&self, key_id: &str) -> anyhow::Result<Option<AdminKey>> {
+        sqlx::query("SELECT * FROM admin_keys WHERE key_id = $1")
+            .bind(key_id)
+            .fetch_optional(&self.db)
+            .await?
+            .as_ref()
+            .map(admin_key_from_row)
+            .transpose()
+    }
+
+    async fn admin_keys(&self) -> anyhow::Result<Vec<AdminKey>> {
+        sqlx::query("SELECT * FROM admin_keys ORDER BY id")
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(admin_key_from_row)
+            .collect()
+    }
+
+    async fn revoke_admin_key(
+        &self,
+        name: &str,
+        at: DateTime<Utc>,
+        change: &AdminChange,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let Some(row) = sqlx::query(
+            "UPDATE admin_keys SET revoked_at = $1 WHERE name = $2 AND revoked_at IS NULL RETURNING *",
+        )
+        .bind(at)
+        .bind(name)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+        let after = admin_key_from_row(&row)?;
+        let before = AdminKey {
+            revoked_at: None,
+            ..after.clone()
+        };
+        audit_in(&mut tx, &change.key_entry(at, Some(&before), &after)).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn replace_sealed_signing_key(
+        &self,
+        key_id: &str,
+        current: &str,
+        sealed: &str,
+        change: Option<&AdminChange>,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        let Some(name) = sqlx::query_scalar::<_, String>(
+            "UPDATE admin_keys SET sealed_signing_key = $1 WHERE key_id = $2 AND sealed_signing_key = $3 RETURNING name",
+        )
+        .bind(sealed)
+        .bind(key_id)
+        .bind(current)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+        if let Some(change) = change {
+            audit_in(&mut tx, &change.reseal_entry(&name, current, sealed)).await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn claim_admin_signature(
+        &self,
+        key_id: &str,
+        signature: &str,
+        expires_at: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        sqlx::query("DELETE FROM admin_request_signatures WHERE expires_at <= $1")
+            .bind(Utc::now())
+            .execute(&self.db)
+            .await?;
+        let inserted = sqlx::query(
+            "INSERT INTO admin_request_signatures (key_id, signature, expires_at) VALUES ($1, $2, $3) ON CONFLICT (key_id, signature) DO NOTHING",
+        )
+        .bind(key_id)
+        .bind(signature)
+        .bind(expires_at)
+        .execute(&self.db)
+        .await?;
+        Ok(inserted.rows_affected() == 1)
+    }
+
+    async fn record_admin_action(&self, entry: &NewAdminAudit) -> anyhow::Result<AdminAuditEntry> {
+        let mut tx = self.db.begin().await?;
+        let recorded = audit_in(&mut tx, entry).await?;
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    async fn admin_audit(&self, limit: u32) -> anyhow::Result<Vec<AdminAuditEntry>> {
+        sqlx::query("SELECT * FROM admin_audit ORDER BY id DESC LIMIT $1")
+            .bind(limit as i64)
+            .fetch_all(&self.db)
+            .await?
+            .iter()
+            .map(admin_audit_from_row)
+            .collect()
+    }
+}
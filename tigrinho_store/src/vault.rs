@@ -0,0 +1,242 @@
+use anyhow::{bail, Context};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+
+// Server seeds are stored sealed with envelope encryption: each seed is
+// encrypted under its own random data key, and the data key is wrapped under a
+// key-encryption key (KEK) from the operator's keyring. Both use
+// XChaCha20-Poly1305 with the game slug as associated data, so a sealed seed
+// cannot be moved to another game. Rotating the KEK only re-wraps data keys;
+// the seeds themselves are decrypted solely by the process that spins.
+//
+// Stored form: `sealed-v1:<kek id>:<hex wrapped data key>:<hex sealed seed>`,
+// each ciphertext prefixed with its 24-byte nonce.
+
+const SEALED_PREFIX: &str = "sealed-v1";
+const NONCE_LEN: usize = 24;
+
+/// Whether a stored seed is sealed, rather than plaintext written before seeds
+/// were encrypted.
+pub fn is_sealed(stored: &str) -> bool {
+    parse_sealed(stored).is_some()
+}
+
+/// Id of the KEK a stored seed is sealed under.
+pub fn sealed_key_id(stored: &str) -> Option<&str> {
+    parse_sealed(stored).map(|(key_id, _, _)| key_id)
+}
+
+fn parse_sealed(stored: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = stored.split(':');
+    match (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) {
+        (Some(SEALED_PREFIX), Some(key_id), Some(wrapped), Some(sealed), None) => {
+            Some((key_id, wrapped, sealed))
+        }
+        _ => None,
+    }
+}
+
+fn encrypt(cipher: &XChaCha20Poly1305, msg: &[u8], aad: &str) -> String {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg,
+                aad: aad.as_bytes(),
+            },
+        )
+        .expect("XChaCha20-Poly1305 encryption");
+    hex::encode([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn decrypt(cipher: &XChaCha20Poly1305, sealed_hex: &str, aad: &str) -> Option<Vec<u8>> {
+    let sealed = hex::decode(sealed_hex).ok()?;
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: aad.as_bytes(),
+            },
+        )
+        .ok()
+}
+
+/// The keyring that seals and opens server seeds.
+#[derive(Clone)]
+pub struct SeedVault {
+    /// The active KEK first, then the older ones still able to unwrap.
+    keys: Vec<(String, XChaCha20Poly1305)>,
+}
+
+impl SeedVault {
+    /// From `<id>:<64 hex digits>` entries separated by commas or whitespace.
+    /// The first entry is the active key; the others only unwrap seeds sealed
+    /// before it.
+    pub fn parse(keyring: &str) -> anyhow::Result<Self> {
+        let mut keys: Vec<(String, XChaCha20Poly1305)> = Vec::new();
+        let entries = keyring
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|e| !e.is_empty());
+        for (n, entry) in entries.enumerate() {
+            // never echo the entry, it holds the key
+            let (id, key_hex) = entry
+                .split_once(':')
+                .with_context(|| format!("seed keyring entry {}: expected <id>:<key>", n + 1))?;
+            if id.is_empty()
+                || !id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!(
+                    "seed keyring entry {}: key ids use letters, digits, - and _",
+                    n + 1
+                );
+            }
+            if keys.iter().any(|(known, _)| known == id) {
+                bail!("seed keyring: key id {id} appears twice");
+            }
+            let key: [u8; 32] = hex::decode(key_hex)
+                .ok()
+                .and_then(|k| k.try_into().ok())
+                .with_context(|| format!("seed keyring key {id}: expected 32 bytes of hex"))?;
+            keys.push((
+                id.to_string(),
+                XChaCha20Poly1305::new(Key::from_slice(&key)),
+            ));
+        }
+        if keys.is_empty() {
+            bail!("the seed keyring is empty");
+        }
+        Ok(Self { keys })
+    }
+
+    /// Id of the key new seeds are sealed under.
+    pub fn active_key_id(&self) -> &str {
+        &self.keys[0].0
+    }
+
+    fn kek(&self, id: &str) -> Option<&XChaCha20Poly1305> {
+        self.keys.iter().find(|(k, _)| k == id).map(|(_, kek)| kek)
+    }
+
+    /// Seal a game's seed under a fresh data key wrapped by the active KEK.
+    pub fn seal(&self, game: &str, seed: &str) -> String {
+        let mut data_key = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut data_key);
+        let sealed = encrypt(
+            &XChaCha20Poly1305::new(Key::from_slice(&data_key)),
+            seed.as_bytes(),
+            game,
+        );
+        let (key_id, kek) = &self.keys[0];
+        let wrapped = encrypt(kek, &data_key, game);
+        format!("{SEALED_PREFIX}:{key_id}:{wrapped}:{sealed}")
+    }
+
+    fn unwrap_data_key(&self, game: &str, key_id: &str, wrapped: &str) -> anyhow::Result<Vec<u8>> {
+        let kek = self.kek(key_id).with_context(|| {
+            format!(
+                "the seed of game {game} is sealed under key {key_id}, which is not in the keyring"
+            )
+        })?;
+        decrypt(kek, wrapped, game)
+            .filter(|k| k.len() == 32)
+            .with_context(|| {
+                format!("the data key of game {game} does not open under key {key_id}")
+            })
+    }
+
+    /// The plaintext seed of a game. Fails for seeds that are not sealed.
+    pub fn open(&self, game: &str, stored: &str) -> anyhow::Result<String> {
+        let Some((key_id, wrapped, sealed)) = parse_sealed(stored) else {
+            bail!("the seed of game {game} is not sealed; run `tigrinho_cli rotate-kek`");
+        };
+        let data_key = self.unwrap_data_key(game, key_id, wrapped)?;
+        let seed = decrypt(
+            &XChaCha20Poly1305::new(Key::from_slice(&data_key)),
+            sealed,
+            game,
+        )
+        .with_context(|| format!("the seed of game {game} does not open"))?;
+        String::from_utf8(seed).context("sealed seed is not UTF-8")
+    }
+
+    /// `stored` re-wrapped under the active KEK, or sealed if it is still
+    /// plaintext; `None` when it already is sealed under the active KEK.
+    pub fn reseal(&self, game: &str, stored: &str) -> anyhow::Result<Option<String>> {
+        let Some((key_id, wrapped, sealed)) = parse_sealed(stored) else {
+            return Ok(Some(self.seal(game, stored)));
+        };
+        if key_id == self.active_key_id() {
+            return Ok(None);
+        }
+        let data_key = self.unwrap_data_key(game, key_id, wrapped)?;
+        let (active_id, kek) = &self.keys[0];
+        let wrapped = encrypt(kek, &data_key, game);
+        Ok(Some(format!(
+            "{SEALED_PREFIX}:{active_id}:{wrapped}:{sealed}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = "old:1111111111111111111111111111111111111111111111111111111111111111";
+    const NEW: &str = "new:2222222222222222222222222222222222222222222222222222222222222222";
+
+    #[test]
+    fn seeds_open_only_for_their_game_and_keyring() {
+        let vault = SeedVault::parse(OLD).unwrap();
+        let stored = vault.seal("ox", "the-seed");
+        assert!(!stored.contains("the-seed"));
+        assert_eq!(sealed_key_id(&stored), Some("old"));
+        assert_eq!(vault.open("ox", &stored).unwrap(), "the-seed");
+        assert!(vault.open("tigrinho", &stored).is_err());
+        assert!(SeedVault::parse(NEW).unwrap().open("ox", &stored).is_err());
+        assert!(vault.open("ox", "the-seed").is_err());
+        assert_ne!(vault.seal("ox", "the-seed"), stored);
+    }
+
+    #[test]
+    fn resealing_moves_seeds_to_the_active_key() {
+        let old = SeedVault::parse(OLD).unwrap();
+        let stored = old.seal("ox", "the-seed");
+        let rotated = SeedVault::parse(&format!("{NEW}\n{OLD}")).unwrap();
+        assert_eq!(rotated.active_key_id(), "new");
+        let resealed = rotated.reseal("ox", &stored).unwrap().unwrap();
+        assert_eq!(sealed_key_id(&resealed), Some("new"));
+        assert_eq!(rotated.reseal("ox", &resealed).unwrap(), None);
+        let new = SeedVault::parse(NEW).unwrap();
+        assert_eq!(new.open("ox", &resealed).unwrap(), "the-seed");
+        // the seed ciphertext itself is untouched
+        assert_eq!(stored.rsplit(':').next(), resealed.rsplit(':').next());
+
+        let legacy = new.reseal("ox", "plain-seed").unwrap().unwrap();
+        assert_eq!(new.open("ox", &legacy).unwrap(), "plain-seed");
+    }
+
+    #[test]
+    fn malformed_keyrings_are_rejected() {
+        assert!(SeedVault::parse("").is_err());
+        assert!(SeedVault::parse("no-separator").is_err());
+        assert!(SeedVault::parse("short:abcd").is_err());
+        assert!(SeedVault::parse(&format!("{OLD},{OLD}")).is_err());
+        assert!(SeedVault::parse(&format!("a:b:{}", "11".repeat(32))).is_err());
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use wasm_bindgen::JsValue;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, MessageEvent, WebSocket};
 use yew::prelude::*;
 
+fn backend_url() -> &'static str {
+    option_env!("BACKEND_URL").unwrap_or("http://127.0.0.1:8080")
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct SpinRequest {
     client_seed: String,
@@ -14,23 +18,137 @@ struct SpinRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct SpinResponse {
     server_seed_hash: String,
+    config_hash: String,
     nonce: u64,
     reels: Vec<Vec<u8>>,
     payout: f64,
+    #[serde(default)]
+    key_id: String,
+    #[serde(default)]
+    signature: String,
+    // step of the round's feature; signed when present
+    #[serde(default)]
+    cursor: Option<u64>,
+}
+// Receipt signing key published at `GET /keys`
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct ReceiptKey {
+    key_id: String,
+    public_key: String,
+}
+
+// Check the response's signature against the published keys
+fn receipt_status(res: &SpinResponse, keys: &[ReceiptKey]) -> String {
+    let Some(key) = keys.iter().find(|k| k.key_id == res.key_id) else {
+        return format!("receipt signed by unknown key {:?}", res.key_id);
+    };
+    let receipt = tigrinho_core::Receipt {
+        config_hash: &res.config_hash,
+        server_seed_hash: &res.server_seed_hash,
+        nonce: res.nonce,
+        reels: &res.reels,
+        payout: res.payout,
+        cursor: res.cursor,
+    };
+    match tigrinho_core::verify_receipt(&receipt, &key.public_key, &res.signature) {
+        Ok(()) => format!("receipt verified with key {}", key.key_id),
+        Err(e) => format!("receipt INVALID: {e}"),
+    }
+}
+// Error envelope returned by the server for every failed request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct ApiErrorBody {
+    code: String,
+    message: String,
+    request_id: String,
+}
+
+// Public live feed frame (`GET /ws?channel=public`); other event types are ignored
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+struct BigWinEvent {
+    r#type: String,
+    game: String,
+    payout: f64,
+    multiplier: f64,
+}
+
+const TICKER_LEN: usize = 10;
+
+#[derive(Default, PartialEq)]
+struct Ticker(Vec<BigWinEvent>);
+
+impl Reducible for Ticker {
+    type Action = BigWinEvent;
+
+    fn reduce(self: Rc<Self>, win: BigWinEvent) -> Rc<Self> {
+        let mut wins = vec![win];
+        wins.extend(self.0.iter().take(TICKER_LEN - 1).cloned());
+        Rc::new(Ticker(wins))
+    }
 }
 
 #[function_component(App)]
 fn app() -> Html {
     let client_seed = use_state(|| "demo-seed".to_string());
+    let token = use_state(String::new);
     let result = use_state(|| None as Option<SpinResponse>);
+    let error = use_state(|| None as Option<String>);
+    let ticker = use_reducer(Ticker::default);
+    let keys = use_state(Vec::<ReceiptKey>::new);
+
+    {
+        let keys = keys.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(r) = reqwest::get(format!("{}/keys", backend_url())).await {
+                    if let Ok(list) = r.json::<Vec<ReceiptKey>>().await {
+                        keys.set(list);
+                    }
+                }
+            });
+        });
+    }
+
+    {
+        let ticker = ticker.dispatcher();
+        use_effect_with((), move |_| {
+            let url = format!(
+                "{}/ws?channel=public",
+                backend_url().replacen("http", "ws", 1)
+            );
+            let ws = WebSocket::new(&url).ok();
+            if let Some(ws) = &ws {
+                let onmessage = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+                    if let Some(text) = e.data().as_string() {
+                        if let Ok(win) = serde_json::from_str::<BigWinEvent>(&text) {
+                            if win.r#type == "big_win" {
+                                ticker.dispatch(win);
+                            }
+                        }
+                    }
+                });
+                ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+                onmessage.forget();
+            }
+            move || {
+                if let Some(ws) = ws {
+                    let _ = ws.close();
+                }
+            }
+        });
+    }
 
     let do_spin = {
         let client_seed = client_seed.clone();
+        let token = token.clone();
         let result = result.clone();
+        let error = error.clone();
         Callback::from(move |_| {
             let client_seed = (*client_seed).clone();
+            let token = (*token).clone();
             wasm_bindgen_futures::spawn_local({
                 let result = result.clone();
+                let error = error.clone();
                 async move {
                     let req = SpinRequest {
                         client_seed,
@@ -38,17 +156,32 @@ fn app() -> Html {
                         lines: 1,
                     };
                     let resp = match reqwest::Client::new()
-                        .post(&format!(
-                            "{}/spin",
-                            option_env!("BACKEND_URL").unwrap_or("http://127.0.0.1:8080")
-                        ))
+                        .post(format!("{}/spin", backend_url()))
+                        .bearer_auth(token)
                         .json(&req)
                         .send()
                         .await
                     {
-                        Ok(r) => r.json::<SpinResponse>().await.ok(),
-                        Err(_) => None,
+                        Ok(r) if r.status().is_success() => r.json::<SpinResponse>().await.ok(),
+                        Ok(r) => {
+                            // branch on the machine-readable code, show the message
+                            error.set(Some(match r.json::<ApiErrorBody>().await {
+                                Ok(e) if e.code == "unauthorized" => {
+                                    "log in and paste your session token first".to_string()
+                                }
+                                Ok(e) => format!("{} (request {})", e.message, e.request_id),
+                                Err(_) => "unexpected server response".to_string(),
+                            }));
+                            None
+                        }
+                        Err(_) => {
+                            error.set(Some("server unreachable".to_string()));
+                            None
+                        }
                     };
+                    if resp.is_some() {
+                        error.set(None);
+                    }
                     result.set(resp);
                 }
             });
@@ -58,11 +191,17 @@ fn app() -> Html {
     html! {
         <div>
             <h1>{"Tigrinho (Demo)"}</h1>
+            <input placeholder="session token" value={(*token).clone()} oninput={{ let token = token.clone(); Callback::from(move |e: InputEvent| { let input: web_sys::HtmlInputElement = e.target_unchecked_into(); token.set(input.value()); }) }} />
             <input value={(*client_seed).clone()} oninput={{ let client_seed = client_seed.clone(); Callback::from(move |e: InputEvent| { let input: web_sys::HtmlInputElement = e.target_unchecked_into(); client_seed.set(input.value()); }) }} />
             <button onclick={do_spin}>{"Spin"}</button>
+            if let Some(err) = &*error { <p style="color: #e74c3c">{err.clone()}</p> }
             <Canvas result={(*result).clone()} />
-            if let Some(res) = &*result { <pre>{format!("hash={} nonce={} payout={}", res.server_seed_hash, res.nonce, res.payout)}</pre> }
-            <p>{"Note: Front-end is for demo only. Verify RNG by recomputing HMAC on the server-revealed seed (not implemented here)."}</p>
+            if let Some(res) = &*result { <pre>{format!("hash={} nonce={} payout={}\n{}", res.server_seed_hash, res.nonce, res.payout, receipt_status(res, &keys))}</pre> }
+            <h2>{"Big wins"}</h2>
+            <ul>
+                { for ticker.0.iter().map(|w| html! { <li>{format!("{}: {:.2} ({:.0}x)", w.game, w.payout, w.multiplier)}</li> }) }
+            </ul>
+            <p>{"Note: Front-end is for demo only. Spin receipts are checked against `GET /keys`; verify the RNG itself by recomputing the HMAC once the seed is revealed (not implemented here)."}</p>
         </div>
     }
 }
@@ -86,20 +225,20 @@ fn canvas(props: &CanvasProps) -> Html {
                     .unwrap()
                     .dyn_into()
                     .unwrap();
-                ctx.set_fill_style(&JsValue::from_str("#111"));
+                ctx.set_fill_style_str("#111");
                 ctx.fill_rect(0.0, 0.0, 300.0, 150.0);
                 if let Some(r) = res {
                     for (row_idx, row) in r.reels.iter().enumerate() {
                         for (col_idx, sym) in row.iter().enumerate() {
                             let x = (col_idx as f64) * 90.0 + 10.0;
                             let y = (row_idx as f64) * 40.0 + 20.0;
-                            ctx.set_fill_style(&JsValue::from_str(match sym {
+                            ctx.set_fill_style_str(match sym {
                                 0 => "#e74c3c",
                                 1 => "#3498db",
                                 2 => "#2ecc71",
                                 3 => "#f1c40f",
                                 _ => "#9b59b6",
-                            }));
+                            });
                             ctx.fill_rect(x, y, 80.0, 30.0);
                         }
                     }